@@ -0,0 +1,223 @@
+//! Builders for the program's core instructions, for off-chain Rust clients.
+//! Each function lays out the accounts in the order the handler reads them
+//! and encodes the payload after the instruction tag.
+
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+
+use crate::{
+    IX_ADD_PROPOSAL, IX_CHANGE_VOTE, IX_CLOSE_POLL, IX_DELEGATE, IX_GIVE_RIGHT, IX_INITIALIZE,
+    IX_VOTE, IX_WINNER_NAME, IX_WINNING_PROPOSAL,
+};
+
+/// Grants `voter` a right to vote with the given weight.
+pub fn give_right_to_vote_ix(
+    program_id: &Pubkey,
+    chairperson: &Pubkey,
+    system: &Pubkey,
+    voter: &Pubkey,
+    weight: u64,
+) -> Instruction {
+    let mut data = vec![IX_GIVE_RIGHT];
+    data.extend_from_slice(&weight.to_le_bytes());
+
+    Instruction::new_with_bytes(
+        *program_id,
+        &data,
+        vec![
+            AccountMeta::new_readonly(*chairperson, true),
+            AccountMeta::new(*system, false),
+            AccountMeta::new(*voter, false),
+        ],
+    )
+}
+
+/// Address of the account holding proposal `index` of the ballot whose state
+/// account is `system`, for ballots storing one proposal per account.
+pub fn proposal_address(program_id: &Pubkey, system: &Pubkey, index: u32) -> Pubkey {
+    crate::proposal_address(program_id, system, index).0
+}
+
+/// Casts the voter's vote for proposal `index`, which is either the
+/// per-proposal account at [`proposal_address`] or the proposal list holding
+/// it. Pass
+/// [`crate::ABSTAIN`] to abstain.
+pub fn vote_ix(
+    program_id: &Pubkey,
+    voter: &Pubkey,
+    proposal: &Pubkey,
+    system: &Pubkey,
+    index: u32,
+) -> Instruction {
+    let mut data = vec![IX_VOTE];
+    data.extend_from_slice(&index.to_le_bytes());
+
+    Instruction::new_with_bytes(
+        *program_id,
+        &data,
+        vec![
+            AccountMeta::new(*voter, true),
+            AccountMeta::new(*proposal, false),
+            AccountMeta::new(*system, false),
+        ],
+    )
+}
+
+/// Queries the index of the winning proposal and the number of proposals tied
+/// with it.
+pub fn winning_proposal_ix(
+    program_id: &Pubkey,
+    system: &Pubkey,
+    proposals: &[Pubkey],
+) -> Instruction {
+    let mut accounts = vec![AccountMeta::new_readonly(*system, false)];
+    accounts.extend(
+        proposals
+            .iter()
+            .map(|proposal| AccountMeta::new_readonly(*proposal, false)),
+    );
+
+    Instruction::new_with_bytes(*program_id, &[IX_WINNING_PROPOSAL], accounts)
+}
+
+/// Queries the name of the winning proposal in a proposal list.
+pub fn winner_name_ix(program_id: &Pubkey, proposals: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &[IX_WINNER_NAME],
+        vec![AccountMeta::new_readonly(*proposals, false)],
+    )
+}
+
+/// Sets up a blank state account with the given deadline (0 for none), quorum
+/// (0 for none) and proposal names, each encoded with a `u16` length prefix.
+pub fn initialize_ix(
+    program_id: &Pubkey,
+    chairperson: &Pubkey,
+    system: &Pubkey,
+    end_timestamp: i64,
+    quorum: u64,
+    names: &[&[u8]],
+) -> Instruction {
+    let mut data = vec![IX_INITIALIZE];
+    data.extend_from_slice(&end_timestamp.to_le_bytes());
+    data.extend_from_slice(&quorum.to_le_bytes());
+    for name in names {
+        data.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        data.extend_from_slice(name);
+    }
+
+    Instruction::new_with_bytes(
+        *program_id,
+        &data,
+        vec![
+            AccountMeta::new_readonly(*chairperson, true),
+            AccountMeta::new(*system, false),
+        ],
+    )
+}
+
+/// Hands the weight of `from`, or `amount` of it, to `to`. `proposal` is the
+/// account holding the proposal `to` voted for, needed once `to` has voted.
+pub fn delegate_ix(
+    program_id: &Pubkey,
+    from: &Pubkey,
+    to: &Pubkey,
+    system: &Pubkey,
+    proposal: Option<&Pubkey>,
+    amount: Option<u64>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*from, true),
+        AccountMeta::new(*to, false),
+        AccountMeta::new(*system, false),
+    ];
+    if let Some(proposal) = proposal {
+        accounts.push(AccountMeta::new(*proposal, false));
+    }
+
+    let mut data = vec![IX_DELEGATE];
+    if let Some(amount) = amount {
+        data.extend_from_slice(&amount.to_le_bytes());
+    }
+
+    Instruction::new_with_bytes(*program_id, &data, accounts)
+}
+
+/// Moves the voter's vote from proposal `old_index` to `new_index`. With a
+/// proposal list, pass the list as both proposal accounts.
+pub fn change_vote_ix(
+    program_id: &Pubkey,
+    voter: &Pubkey,
+    old_proposal: &Pubkey,
+    new_proposal: &Pubkey,
+    system: &Pubkey,
+    old_index: u32,
+    new_index: u32,
+) -> Instruction {
+    let mut data = vec![IX_CHANGE_VOTE];
+    data.extend_from_slice(&old_index.to_le_bytes());
+    data.extend_from_slice(&new_index.to_le_bytes());
+
+    Instruction::new_with_bytes(
+        *program_id,
+        &data,
+        vec![
+            AccountMeta::new(*voter, true),
+            AccountMeta::new(*old_proposal, false),
+            AccountMeta::new(*new_proposal, false),
+            AccountMeta::new(*system, false),
+        ],
+    )
+}
+
+/// Appends a proposal with the given name, and optionally the hash of its
+/// description, to the ballot.
+pub fn add_proposal_ix(
+    program_id: &Pubkey,
+    chairperson: &Pubkey,
+    system: &Pubkey,
+    name: &[u8],
+    desc_hash: Option<&[u8; 32]>,
+) -> Instruction {
+    let mut data = vec![IX_ADD_PROPOSAL];
+    data.extend_from_slice(&(name.len() as u16).to_le_bytes());
+    data.extend_from_slice(name);
+    if let Some(desc_hash) = desc_hash {
+        data.extend_from_slice(desc_hash);
+    }
+
+    Instruction::new_with_bytes(
+        *program_id,
+        &data,
+        vec![
+            AccountMeta::new_readonly(*chairperson, true),
+            AccountMeta::new(*system, false),
+        ],
+    )
+}
+
+/// Closes the given accounts of a finished poll, its state account or its
+/// voter records, moving their lamports to `destination`.
+pub fn close_poll_ix(
+    program_id: &Pubkey,
+    chairperson: &Pubkey,
+    system: &Pubkey,
+    destination: &Pubkey,
+    closed: &[Pubkey],
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*chairperson, true),
+        AccountMeta::new(*system, false),
+        AccountMeta::new(*destination, false),
+    ];
+    accounts.extend(
+        closed
+            .iter()
+            .map(|account| AccountMeta::new(*account, false)),
+    );
+
+    Instruction::new_with_bytes(*program_id, &[IX_CLOSE_POLL], accounts)
+}
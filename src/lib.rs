@@ -1,360 +1,4942 @@
+use std::{
+    cmp::Reverse,
+    io::{self, Write},
+};
+
+use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
     entrypoint,
     entrypoint::ProgramResult,
+    hash::{hash, hashv, Hash},
     msg,
+    program::{invoke_signed, set_return_data, MAX_RETURN_DATA},
     program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey,
     pubkey::Pubkey,
+    system_instruction, system_program,
+    sysvar::{clock::Clock, rent::Rent, Sysvar},
 };
 
-#[derive(Debug)]
-struct Voter {
-    weight: u32,
-    voted: bool,
-    vote: u32,
-}
+#[cfg(feature = "client")]
+pub mod client;
+
+/// Integer type of a proposal's vote count. It is as wide as a voter's weight,
+/// which holds token balances; building with the `u128-counts` feature widens
+/// it for ballots whose total supply does not fit in 64 bits, and the
+/// serialized proposal record grows to match.
+#[cfg(not(feature = "u128-counts"))]
+pub type VoteCount = u64;
+#[cfg(feature = "u128-counts")]
+pub type VoteCount = u128;
+
+/// Integer type that sums of vote counts are accumulated in.
+pub type VoteTotal = u128;
+
+const COUNT_LEN: usize = std::mem::size_of::<VoteCount>();
+const TOTAL_LEN: usize = std::mem::size_of::<VoteTotal>();
+
+/// Byte order used for the integer fields of a serialized record.
+///
+/// Account data is always written little-endian; `Big` is offered so
+/// external tools that expect network byte order can re-encode records read
+/// from the chain.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Endian {
+    #[default]
+    Little,
+    Big,
+}
+
+impl Endian {
+    fn read_u16(self, bytes: &[u8]) -> u16 {
+        let bytes = bytes.try_into().unwrap();
+        match self {
+            Endian::Little => u16::from_le_bytes(bytes),
+            Endian::Big => u16::from_be_bytes(bytes),
+        }
+    }
+
+    fn read_u32(self, bytes: &[u8]) -> u32 {
+        let bytes = bytes.try_into().unwrap();
+        match self {
+            Endian::Little => u32::from_le_bytes(bytes),
+            Endian::Big => u32::from_be_bytes(bytes),
+        }
+    }
+
+    fn read_u64(self, bytes: &[u8]) -> u64 {
+        let bytes = bytes.try_into().unwrap();
+        match self {
+            Endian::Little => u64::from_le_bytes(bytes),
+            Endian::Big => u64::from_be_bytes(bytes),
+        }
+    }
+
+    fn read_count(self, bytes: &[u8]) -> VoteCount {
+        let bytes = bytes.try_into().unwrap();
+        match self {
+            Endian::Little => VoteCount::from_le_bytes(bytes),
+            Endian::Big => VoteCount::from_be_bytes(bytes),
+        }
+    }
+
+    fn write_u16(self, value: u16) -> [u8; 2] {
+        match self {
+            Endian::Little => value.to_le_bytes(),
+            Endian::Big => value.to_be_bytes(),
+        }
+    }
+
+    fn write_u32(self, value: u32) -> [u8; 4] {
+        match self {
+            Endian::Little => value.to_le_bytes(),
+            Endian::Big => value.to_be_bytes(),
+        }
+    }
+
+    fn write_u64(self, value: u64) -> [u8; 8] {
+        match self {
+            Endian::Little => value.to_le_bytes(),
+            Endian::Big => value.to_be_bytes(),
+        }
+    }
+
+    fn write_count(self, value: VoteCount) -> [u8; COUNT_LEN] {
+        match self {
+            Endian::Little => value.to_le_bytes(),
+            Endian::Big => value.to_be_bytes(),
+        }
+    }
+}
+
+/// Failures specific to the voting program, returned to clients as
+/// `ProgramError::Custom` codes. Code 0 is left to the dispatcher's generic
+/// failure.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VotingError {
+    /// The voter's weight is below the ballot's minimum participation weight.
+    WeightBelowMinimum = 1,
+    /// The voter has already cast their vote.
+    AlreadyVoted,
+    /// The voter has already been given the right to vote with a different
+    /// weight, or on another ballot.
+    AlreadyGranted,
+    /// The vote would take a strict proposal past its expected maximum.
+    ExpectedVotesExceeded,
+    /// Voting weights can no longer change past the snapshot slot.
+    WeightsFrozen,
+    /// The chairperson has not started voting yet.
+    VotingNotStarted,
+    /// The voter has not been given the right to vote on this ballot.
+    NoRightToVote,
+    /// The proposal index does not resolve to a proposal.
+    InvalidProposalIndex,
+    /// The signer is not the ballot's chairperson.
+    NotChairperson,
+    /// An account is too small to hold the record it should contain.
+    AccountTooSmall,
+    /// Adding the voter's weight would overflow the proposal's vote count.
+    VoteCountOverflow,
+    /// Too little weight or too few voters have voted to seal the results, or
+    /// too few votes have been cast to declare a winner.
+    QuorumNotMet,
+    /// The ballot's voting deadline has passed.
+    VotingClosed,
+    /// The voter has not cast a vote that could be changed.
+    NotVoted,
+    /// An account written by the instruction does not hold enough lamports to
+    /// be exempt from rent.
+    NotRentExempt,
+    /// The poll cannot be closed or archived before its deadline has passed or
+    /// its results have been sealed.
+    PollNotFinished,
+}
+
+impl From<VotingError> for ProgramError {
+    fn from(error: VotingError) -> Self {
+        ProgramError::Custom(error as u32)
+    }
+}
+
+/// A voter's record.
+///
+/// `delegate` is the voter account this voter handed their weight to, or the
+/// default key if they have not delegated. Delegating counts as voting, so
+/// `voted` is set and `vote` is meaningless for a delegating voter.
+///
+/// `ballot` is the state account of the ballot that granted the right to
+/// vote, the default key until then. The voter can only vote on that ballot.
+///
+/// `approvals` is the mask of proposals backed by an approval ballot, whose
+/// `vote` is [`APPROVAL`]; it is 0 for any other voter.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Voter {
+    pub weight: u64,
+    pub voted: bool,
+    pub vote: u32,
+    pub delegate: Pubkey,
+    pub ballot: Pubkey,
+    pub approvals: u32,
+}
+
+impl Voter {
+    const VOTED_OFFSET: usize = 8;
+    const VOTE_OFFSET: usize = 9;
+    const DELEGATE_OFFSET: usize = 13;
+    const BALLOT_OFFSET: usize = Self::DELEGATE_OFFSET + 32;
+    const APPROVALS_OFFSET: usize = Self::BALLOT_OFFSET + 32;
+
+    /// Size of a serialized voter record. A voter account may be larger, in
+    /// which case the bytes past the record are left untouched.
+    pub const LEN: usize = Self::APPROVALS_OFFSET + 4;
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProgramError> {
+        Self::from_bytes_endian(bytes, Endian::Little)
+    }
+
+    pub fn from_bytes_endian(bytes: &[u8], endian: Endian) -> Result<Self, ProgramError> {
+        if bytes.len() < Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if endian == Endian::Little {
+            return Self::try_from_slice(&bytes[..Self::LEN])
+                .map_err(|_| ProgramError::InvalidAccountData);
+        }
+
+        let weight = endian.read_u64(&bytes[..Self::VOTED_OFFSET]);
+        let voted = bytes[Self::VOTED_OFFSET] != 0;
+        let vote = endian.read_u32(&bytes[Self::VOTE_OFFSET..Self::DELEGATE_OFFSET]);
+        let delegate = read_pubkey(bytes, Self::DELEGATE_OFFSET)?;
+        let ballot = read_pubkey(bytes, Self::BALLOT_OFFSET)?;
+        let approvals = endian.read_u32(&bytes[Self::APPROVALS_OFFSET..Self::LEN]);
+
+        Ok(Voter {
+            weight,
+            voted,
+            vote,
+            delegate,
+            ballot,
+            approvals,
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.try_to_vec().unwrap()
+    }
+
+    pub fn to_bytes_endian(&self, endian: Endian) -> Vec<u8> {
+        if endian == Endian::Little {
+            return self.to_bytes();
+        }
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&endian.write_u64(self.weight));
+        bytes.push(self.voted as u8);
+        bytes.extend_from_slice(&endian.write_u32(self.vote));
+        bytes.extend_from_slice(&self.delegate.to_bytes());
+        bytes.extend_from_slice(&self.ballot.to_bytes());
+        bytes.extend_from_slice(&endian.write_u32(self.approvals));
+
+        bytes
+    }
+
+    /// Whether the voter has handed their weight to another voter.
+    pub fn has_delegated(&self) -> bool {
+        self.delegate != Pubkey::default()
+    }
+
+    /// Whether the voter cast an approval ballot rather than a single vote.
+    pub fn cast_approvals(&self) -> bool {
+        self.voted && self.vote == APPROVAL
+    }
+
+    /// Whether the voter holds a right to vote on the ballot whose state
+    /// account is `system_key`.
+    pub fn may_vote_on(&self, system_key: &Pubkey) -> bool {
+        self.weight != 0 && self.ballot == *system_key
+    }
+
+    /// Number of proposals the voter may still vote for. Every ballot is
+    /// single-choice, an approval ballot included, as it is cast in one
+    /// instruction, so this is 1 until the voter has voted and 0 afterwards or
+    /// without a right to vote.
+    pub fn remaining_choices(&self) -> u32 {
+        if self.weight == 0 || self.voted {
+            0
+        } else {
+            1
+        }
+    }
+}
+
+impl Sealed for Voter {}
+
+/// A voter counts as initialized once granted a right to vote or once they
+/// have voted. Use `Pack::unpack_unchecked` to read a voter without a right.
+impl IsInitialized for Voter {
+    fn is_initialized(&self) -> bool {
+        self.weight != 0 || self.voted
+    }
+}
+
+impl Pack for Voter {
+    const LEN: usize = Voter::LEN;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        dst[..Voter::LEN].copy_from_slice(&self.to_bytes());
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        Self::from_bytes(src)
+    }
+}
+
+/// Strips the trailing zero padding from a fixed-width field, such as the
+/// 32-byte proposal names written before names were length-prefixed or the
+/// ballot title.
+pub fn trim_name(name: &[u8]) -> &[u8] {
+    let len = name
+        .iter()
+        .rposition(|&byte| byte != 0)
+        .map_or(0, |last| last + 1);
+    &name[..len]
+}
+
+/// Longest proposal name, in bytes.
+pub const MAX_NAME_LEN: usize = 128;
+
+/// Checks a proposal name given at creation: it has to be non-empty UTF-8 of
+/// at most [`MAX_NAME_LEN`] bytes, without zero bytes.
+fn validate_name(name: &[u8]) -> ProgramResult {
+    if name.is_empty() || name.contains(&0) || std::str::from_utf8(name).is_err() {
+        msg!("Invalid proposal name");
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    if name.len() > MAX_NAME_LEN {
+        msg!(
+            "Proposal name of {} bytes is longer than {}",
+            name.len(),
+            MAX_NAME_LEN
+        );
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    Ok(())
+}
+
+/// Reads the optional description hash following a name in an instruction
+/// payload, all zeros if none is given.
+fn desc_hash_field(bytes: &[u8]) -> Result<[u8; 32], ProgramError> {
+    match bytes.len() {
+        0 => Ok([0; 32]),
+        32 => Ok(bytes.try_into().unwrap()),
+        len => {
+            msg!("Invalid description hash length {}", len);
+            Err(ProgramError::InvalidInstructionData)
+        }
+    }
+}
+
+/// Splits a length-prefixed name (`u16` length, then the name) off the front
+/// of an instruction payload, returning the name and the bytes after it.
+fn split_name(payload: &[u8]) -> Result<(&[u8], &[u8]), ProgramError> {
+    let (len, rest) = payload
+        .get(..2)
+        .map(|len| {
+            (
+                u16::from_le_bytes(len.try_into().unwrap()) as usize,
+                &payload[2..],
+            )
+        })
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    if rest.len() < len {
+        msg!("Name of {} bytes overruns the payload", len);
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    Ok(rest.split_at(len))
+}
+
+/// A ballot option.
+///
+/// The runtime write-locks an account for the duration of a transaction, so
+/// two votes can never interleave their read-modify-write of `vote_count`;
+/// the last writer simply wins. What the runtime cannot catch is a client that
+/// builds its transaction from a stale read of the account. `nonce` is bumped
+/// on every write, and a `vote` carrying the nonce it observed is rejected if
+/// the proposal has been written since.
+///
+/// `max_expected_votes` is a sanity bound registered ahead of the vote (0 for
+/// none). A vote that takes the count past it is logged, or rejected if
+/// `reject_unexpected` is set.
+///
+/// `desc_hash` is the SHA-256 hash of the proposal's off-chain description,
+/// set when the proposal is created; all zeros if it has none.
+///
+/// The name is stored as a `u16` length followed by the name, in a slot of
+/// [`MAX_NAME_LEN`] bytes padded with zeros, so that every record has the same
+/// size and a proposal list can be indexed. Records written before names were
+/// length-prefixed started with a 32-byte zero-padded name and are read with
+/// [`Proposal::from_legacy_bytes`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Proposal {
+    pub name: Vec<u8>,
+    pub vote_count: VoteCount,
+    pub nonce: u64,
+    pub max_expected_votes: VoteCount,
+    pub reject_unexpected: bool,
+    pub desc_hash: [u8; 32],
+}
+
+impl Proposal {
+    const NAME_OFFSET: usize = 2;
+    const COUNT_OFFSET: usize = Self::NAME_OFFSET + MAX_NAME_LEN;
+    const NONCE_OFFSET: usize = Self::COUNT_OFFSET + COUNT_LEN;
+    const MAX_EXPECTED_OFFSET: usize = Self::NONCE_OFFSET + 8;
+    const REJECT_OFFSET: usize = Self::MAX_EXPECTED_OFFSET + COUNT_LEN;
+    const DESC_HASH_OFFSET: usize = Self::REJECT_OFFSET + 1;
+
+    /// Size of one serialized proposal record.
+    pub const LEN: usize = Self::DESC_HASH_OFFSET + 32;
+    /// Size of a proposal record with a 32-byte name, as written before names
+    /// were length-prefixed.
+    pub const LEGACY_LEN: usize = Self::LEN - Self::COUNT_OFFSET + 32;
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProgramError> {
+        Self::from_bytes_endian(bytes, Endian::Little)
+    }
+
+    pub fn from_bytes_endian(bytes: &[u8], endian: Endian) -> Result<Self, ProgramError> {
+        if bytes.len() < Self::COUNT_OFFSET {
+            msg!(
+                "Proposal record of {} bytes is too short for a name",
+                bytes.len()
+            );
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if bytes.len() < Self::LEN {
+            msg!(
+                "Proposal record of {} bytes is too short for its counts, expected {}",
+                bytes.len(),
+                Self::LEN
+            );
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if endian == Endian::Little {
+            return Self::try_from_slice(&bytes[..Self::LEN])
+                .map_err(|_| ProgramError::InvalidAccountData);
+        }
+
+        let name_len = endian.read_u16(&bytes[..Self::NAME_OFFSET]);
+        let name = Self::name_from_slot(name_len, &bytes[Self::NAME_OFFSET..Self::COUNT_OFFSET])?;
+        let vote_count = endian.read_count(&bytes[Self::COUNT_OFFSET..Self::NONCE_OFFSET]);
+        let nonce = endian.read_u64(&bytes[Self::NONCE_OFFSET..Self::MAX_EXPECTED_OFFSET]);
+        let max_expected_votes =
+            endian.read_count(&bytes[Self::MAX_EXPECTED_OFFSET..Self::REJECT_OFFSET]);
+        let reject_unexpected = bytes[Self::REJECT_OFFSET] != 0;
+        let desc_hash = bytes[Self::DESC_HASH_OFFSET..Self::LEN].try_into().unwrap();
+
+        Ok(Proposal {
+            name,
+            vote_count,
+            nonce,
+            max_expected_votes,
+            reject_unexpected,
+            desc_hash,
+        })
+    }
+
+    /// The name of `name_len` bytes at the start of its slot. A name running
+    /// over its slot, or followed by anything but padding, marks a record in
+    /// another layout.
+    fn name_from_slot(name_len: u16, slot: &[u8]) -> Result<Vec<u8>, ProgramError> {
+        let name_len = name_len as usize;
+        if name_len > MAX_NAME_LEN || slot[name_len..].iter().any(|&byte| byte != 0) {
+            msg!("Proposal record does not hold a length-prefixed name");
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(slot[..name_len].to_vec())
+    }
+
+    /// Reads a record written before names were length-prefixed, whose name
+    /// fills a 32-byte field padded with zeros.
+    pub fn from_legacy_bytes(bytes: &[u8]) -> Result<Self, ProgramError> {
+        let (name, rest) = bytes
+            .get(..Self::LEGACY_LEN)
+            .map(|record| record.split_at(32))
+            .ok_or(ProgramError::InvalidAccountData)?;
+        let name = trim_name(name);
+
+        let mut current = vec![0; Self::COUNT_OFFSET];
+        current[..Self::NAME_OFFSET].copy_from_slice(&(name.len() as u16).to_le_bytes());
+        current[Self::NAME_OFFSET..Self::NAME_OFFSET + name.len()].copy_from_slice(name);
+        current.extend_from_slice(rest);
+        Self::from_bytes(&current)
+    }
+
+    /// Serializes the record. Names longer than [`MAX_NAME_LEN`] are refused
+    /// at creation; one set on the struct directly is cut to its slot, so the
+    /// record keeps its size.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.try_to_vec().unwrap()
+    }
+
+    pub fn to_bytes_endian(&self, endian: Endian) -> Vec<u8> {
+        if endian == Endian::Little {
+            return self.to_bytes();
+        }
+
+        let name = self.slot_name();
+        let mut bytes = Vec::with_capacity(Self::LEN);
+        bytes.extend_from_slice(&endian.write_u16(name.len() as u16));
+        bytes.extend_from_slice(name);
+        bytes.resize(Self::COUNT_OFFSET, 0);
+        bytes.extend_from_slice(&endian.write_count(self.vote_count));
+        bytes.extend_from_slice(&endian.write_u64(self.nonce));
+        bytes.extend_from_slice(&endian.write_count(self.max_expected_votes));
+        bytes.push(self.reject_unexpected as u8);
+        bytes.extend_from_slice(&self.desc_hash);
+
+        bytes
+    }
+
+    /// The proposal's name.
+    pub fn name_bytes(&self) -> &[u8] {
+        &self.name
+    }
+
+    /// The part of the name that fits its slot.
+    fn slot_name(&self) -> &[u8] {
+        &self.name[..self.name.len().min(MAX_NAME_LEN)]
+    }
+
+    /// Returns whether `description` hashes to the stored `desc_hash`.
+    pub fn matches_description(&self, description: &[u8]) -> bool {
+        hash(description).to_bytes() == self.desc_hash
+    }
+
+    /// Checks a prospective vote count against the expected maximum, logging
+    /// an overrun or, for a proposal registered as strict, rejecting it.
+    fn check_expected_votes(
+        index: u32,
+        vote_count: VoteCount,
+        max_expected_votes: VoteCount,
+        reject_unexpected: bool,
+    ) -> ProgramResult {
+        if max_expected_votes == 0 || vote_count <= max_expected_votes {
+            return Ok(());
+        }
+
+        msg!(
+            "Proposal {} reached {} votes, above its expected maximum of {}",
+            index,
+            vote_count,
+            max_expected_votes
+        );
+        if reject_unexpected {
+            return Err(VotingError::ExpectedVotesExceeded.into());
+        }
+
+        Ok(())
+    }
+
+    fn deserialize_list(data: &[u8]) -> Result<Vec<Self>, ProgramError> {
+        if !data.starts_with(&PROPOSAL_LIST_TAG) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let mut proposals = Vec::new();
+        let mut offset = PROPOSAL_LIST_TAG.len();
+
+        // Trailing bytes too few for a whole record, such as slack left in the
+        // account for rent exemption, are ignored
+        while offset + Proposal::LEN <= data.len() {
+            proposals.push(Proposal::from_bytes(&data[offset..offset + Proposal::LEN])?);
+
+            offset += Proposal::LEN;
+        }
+
+        Ok(proposals)
+    }
+}
+
+/// Written by hand rather than derived, as the name fills a slot of
+/// [`MAX_NAME_LEN`] bytes instead of taking only its own length.
+impl BorshSerialize for Proposal {
+    fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let name = self.slot_name();
+        let mut slot = [0; MAX_NAME_LEN];
+        slot[..name.len()].copy_from_slice(name);
+
+        (name.len() as u16).serialize(writer)?;
+        slot.serialize(writer)?;
+        self.vote_count.serialize(writer)?;
+        self.nonce.serialize(writer)?;
+        self.max_expected_votes.serialize(writer)?;
+        self.reject_unexpected.serialize(writer)?;
+        self.desc_hash.serialize(writer)
+    }
+}
+
+impl BorshDeserialize for Proposal {
+    fn deserialize(buf: &mut &[u8]) -> io::Result<Self> {
+        let name_len = u16::deserialize(buf)?;
+        let slot = <[u8; MAX_NAME_LEN]>::deserialize(buf)?;
+        let name = Self::name_from_slot(name_len, &slot)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid proposal name"))?;
+
+        Ok(Proposal {
+            name,
+            vote_count: VoteCount::deserialize(buf)?,
+            nonce: u64::deserialize(buf)?,
+            max_expected_votes: VoteCount::deserialize(buf)?,
+            reject_unexpected: bool::deserialize(buf)?,
+            desc_hash: <[u8; 32]>::deserialize(buf)?,
+        })
+    }
+}
+
+impl Sealed for Proposal {}
+
+/// Every proposal is created with a non-empty name.
+impl IsInitialized for Proposal {
+    fn is_initialized(&self) -> bool {
+        !self.name_bytes().is_empty()
+    }
+}
+
+impl Pack for Proposal {
+    const LEN: usize = Proposal::LEN;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        dst[..Proposal::LEN].copy_from_slice(&self.to_bytes());
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        Self::from_bytes(src)
+    }
+}
+
+/// Compact summary of a finished ballot, small enough to keep once the
+/// ballot's own accounts have been closed.
+#[derive(Debug, PartialEq, Eq)]
+pub struct BallotArchive {
+    pub winning_proposal: u32,
+    /// Stored like a proposal's name, length-prefixed in a slot of
+    /// [`MAX_NAME_LEN`] bytes.
+    pub winner_name: Vec<u8>,
+    pub total_votes: VoteTotal,
+    pub participants: u32,
+    pub end_slot: u64,
+    pub decimals: u8,
+}
+
+impl BallotArchive {
+    const TOTAL_OFFSET: usize = 4 + Proposal::COUNT_OFFSET;
+
+    /// Size of a serialized archive record.
+    pub const LEN: usize = Self::TOTAL_OFFSET + TOTAL_LEN + 13;
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProgramError> {
+        if bytes.len() < Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let winning_proposal = u32::from_le_bytes(bytes[..4].try_into().unwrap());
+        let name_len = u16::from_le_bytes(bytes[4..6].try_into().unwrap()) as usize;
+        let winner_name = bytes
+            .get(6..6 + name_len)
+            .filter(|_| name_len <= MAX_NAME_LEN)
+            .ok_or(ProgramError::InvalidAccountData)?
+            .to_vec();
+        let total_votes = VoteTotal::from_le_bytes(
+            bytes[Self::TOTAL_OFFSET..Self::TOTAL_OFFSET + TOTAL_LEN]
+                .try_into()
+                .unwrap(),
+        );
+        let offset = Self::TOTAL_OFFSET + TOTAL_LEN;
+        let participants = u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap());
+        let end_slot = u64::from_le_bytes(bytes[offset + 4..offset + 12].try_into().unwrap());
+        let decimals = bytes[offset + 12];
+
+        Ok(BallotArchive {
+            winning_proposal,
+            winner_name,
+            total_votes,
+            participants,
+            end_slot,
+            decimals,
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&self.winning_proposal.to_le_bytes());
+        bytes.extend_from_slice(&(self.winner_name.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(&self.winner_name);
+        bytes.resize(Self::TOTAL_OFFSET, 0);
+        bytes.extend_from_slice(&self.total_votes.to_le_bytes());
+        bytes.extend_from_slice(&self.participants.to_le_bytes());
+        bytes.extend_from_slice(&self.end_slot.to_le_bytes());
+        bytes.push(self.decimals);
+
+        bytes
+    }
+}
+
+/// Layout version written as the first byte of the state account. Version 0
+/// accounts predate the version byte; `migrate` converts them.
+const STATE_VERSION: u8 = 1;
+
+/// Proposal index a voter votes for to abstain.
+pub const ABSTAIN: u32 = u32::MAX;
+
+/// Recorded as the vote of a voter who cast an approval ballot; the proposals
+/// approved are in the voter's `approvals`.
+pub const APPROVAL: u32 = u32::MAX - 1;
+
+/// Marks the boundary between the voter roll and the proposals in the state
+/// account.
+const PROPOSALS_SEPARATOR: [u8; 4] = *b"PRPS";
+
+/// Header opening a proposal list account, which tells it apart from a state
+/// account and from a per-proposal account.
+pub const PROPOSAL_LIST_TAG: [u8; 4] = *b"LIST";
+
+/// Contents of the ballot's state account.
+#[derive(Debug, PartialEq, Eq)]
+pub struct SimpleVotingSystem {
+    pub chairperson: Pubkey,
+    pub min_participation_weight: u32,
+    pub title: [u8; 64],
+    /// Number of decimal places clients should apply when displaying vote
+    /// counts, for ballots weighted by token amounts. Metadata only.
+    pub decimals: u8,
+    /// Set once the results are final; no instruction may change the counts
+    /// afterwards.
+    pub results_sealed: bool,
+    /// Slot after which voting weights are frozen, so they cannot be moved
+    /// once the snapshot has been taken. 0 if no snapshot is scheduled.
+    pub snapshot_slot: u64,
+    /// Set by `start_voting` once setup is done; votes are refused before.
+    pub voting_started: bool,
+    /// Slot the ballot was initialized in. 0 for ballots predating the field.
+    pub created_slot: u64,
+    /// Unix timestamp after which votes are refused. 0 if voting has no
+    /// deadline.
+    pub end_timestamp: i64,
+    /// Total vote count below which no winner is declared. 0 for none.
+    pub quorum: u64,
+    /// Total weight of the voters who abstained. It counts toward the quorum
+    /// but backs no proposal.
+    pub abstain_weight: u64,
+    /// Mint of the governance token whose balance weighs a
+    /// `vote_with_token`. The default key if token voting is disabled.
+    pub gov_mint: Pubkey,
+    pub voters: Vec<(Pubkey, Voter)>,
+    pub proposals: Vec<Proposal>,
+}
+
+/// Written by hand rather than derived, to open the state with its layout
+/// version and to close the voter roll with [`PROPOSALS_SEPARATOR`].
+impl BorshSerialize for SimpleVotingSystem {
+    fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        STATE_VERSION.serialize(writer)?;
+        self.chairperson.serialize(writer)?;
+        self.min_participation_weight.serialize(writer)?;
+        self.title.serialize(writer)?;
+        self.decimals.serialize(writer)?;
+        self.results_sealed.serialize(writer)?;
+        self.snapshot_slot.serialize(writer)?;
+        self.voting_started.serialize(writer)?;
+        self.created_slot.serialize(writer)?;
+        self.end_timestamp.serialize(writer)?;
+        self.quorum.serialize(writer)?;
+        self.abstain_weight.serialize(writer)?;
+        self.gov_mint.serialize(writer)?;
+        self.voters.serialize(writer)?;
+        PROPOSALS_SEPARATOR.serialize(writer)?;
+        self.proposals.serialize(writer)
+    }
+}
+
+impl BorshDeserialize for SimpleVotingSystem {
+    fn deserialize(buf: &mut &[u8]) -> io::Result<Self> {
+        let invalid = |error| io::Error::new(io::ErrorKind::InvalidData, error);
+        if u8::deserialize(buf)? != STATE_VERSION {
+            return Err(invalid("Unknown state layout version"));
+        }
+
+        let chairperson = Pubkey::deserialize(buf)?;
+        let min_participation_weight = u32::deserialize(buf)?;
+        let title = <[u8; 64]>::deserialize(buf)?;
+        let decimals = u8::deserialize(buf)?;
+        let results_sealed = bool::deserialize(buf)?;
+        let snapshot_slot = u64::deserialize(buf)?;
+        let voting_started = bool::deserialize(buf)?;
+        let created_slot = u64::deserialize(buf)?;
+        let end_timestamp = i64::deserialize(buf)?;
+        let quorum = u64::deserialize(buf)?;
+        let abstain_weight = u64::deserialize(buf)?;
+        let gov_mint = Pubkey::deserialize(buf)?;
+        let voters = Vec::deserialize(buf)?;
+        if <[u8; 4]>::deserialize(buf)? != PROPOSALS_SEPARATOR {
+            return Err(invalid("Missing proposals separator"));
+        }
+        let proposals = Vec::deserialize(buf)?;
+
+        Ok(SimpleVotingSystem {
+            chairperson,
+            min_participation_weight,
+            title,
+            decimals,
+            results_sealed,
+            snapshot_slot,
+            voting_started,
+            created_slot,
+            end_timestamp,
+            quorum,
+            abstain_weight,
+            gov_mint,
+            voters,
+            proposals,
+        })
+    }
+}
+
+// Instruction tags, carried in the first byte of the instruction data. Tags
+// below 16 are reserved for the core ballot instructions mirrored from the
+// Solidity contract; auxiliary instructions are numbered from 16.
+
+/// Grants the voter account the right to vote (chairperson only). Payload:
+/// optionally the voter's weight (`u64`, non-zero), 1 if omitted.
+///
+/// The voter is added to the roll in the state account, which needs room for
+/// one more entry ([`ROLL_ENTRY_LEN`] bytes). Instructions that later change
+/// the voter record keep the roll entry in sync with it.
+pub const IX_GIVE_RIGHT: u8 = 0;
+/// Casts a vote; the voter account has to sign. Payload: proposal index
+/// (`u32`), optionally followed by the proposal nonce the client last observed
+/// (`u64`).
+///
+/// The index [`ABSTAIN`] records an abstention instead: the voter's weight is
+/// added to the state account's `abstain_weight`, and the proposal account is
+/// not read.
+///
+/// With a proposal list account the index must be below the number of
+/// records in the list; with a per-proposal account it must be the index the
+/// account's address is derived from, together with the state account, as for
+/// [`IX_WRITE_IN_VOTE`]; with the state account passed as the
+/// proposal account, it must be below the number of proposals the state
+/// holds. Any other index is rejected with
+/// [`VotingError::InvalidProposalIndex`].
+///
+/// A velocity account may follow the state account to record the vote's slot
+/// for [`IX_RECENT_VELOCITY`]. It has to be the program-owned account at the
+/// address derived from the state account.
+pub const IX_VOTE: u8 = 1;
+/// Returns the index of the winning proposal (`u32`) and the number of
+/// proposals sharing its vote count (`u32`). A count above 1 is a tie, and the
+/// index is then the earliest of the tied proposals. Fails with
+/// [`VotingError::QuorumNotMet`] while fewer votes than the ballot's quorum
+/// have been cast. Accounts: the state account, then the proposals. No
+/// payload.
+pub const IX_WINNING_PROPOSAL: u8 = 2;
+/// Returns the name of the winning proposal, at most [`MAX_NAME_LEN`] bytes.
+/// The winner is the leading proposal as for [`IX_WINNING_PROPOSAL`], the
+/// earliest on ties. Accounts: a proposal list or the state account holding
+/// the proposals. No payload.
+pub const IX_WINNER_NAME: u8 = 3;
+/// Sets up an empty state account with the signer as chairperson. Voting
+/// starts closed. Payload: the voting deadline as a Unix timestamp (`i64`, 0
+/// for none) and the quorum of votes a winner needs (`u64`, 0 for none),
+/// followed by the proposal names, possibly none, each a `u16` length and the
+/// name of at most [`MAX_NAME_LEN`] bytes.
+pub const IX_INITIALIZE: u8 = 4;
+/// Hands the signing voter's weight to another voter, or straight to the
+/// proposal that voter chose if they have already voted. Accounts: the
+/// delegating voter (signer), the delegate, the state account, and the
+/// delegate's proposal account once the delegate has voted. Payload: none to
+/// hand on the whole weight, or the part of it to hand on (`u64`), leaving the
+/// voter free to vote with the rest. Only a whole delegation can be moved with
+/// [`IX_REDELEGATE`].
+pub const IX_DELEGATE: u8 = 5;
+/// Moves the signing voter's vote from one proposal to another while voting
+/// is open. Accounts: the voter (signer), the old and the new proposal
+/// account (the same proposal list account twice, with a list), and the
+/// state account. Payload: old and new proposal index (`u32` each).
+pub const IX_CHANGE_VOTE: u8 = 6;
+/// Appends a proposal to the ballot before voting opens (chairperson only).
+/// The state account has to be large enough for the extra record. Payload:
+/// proposal name as a `u16` length and the name of at most [`MAX_NAME_LEN`]
+/// bytes, optionally followed by the description hash (32 bytes).
+///
+/// Proposals added here, or named at [`IX_INITIALIZE`], are held by the state
+/// account: instructions taking a proposal account accept the state account
+/// in its place to vote on them.
+pub const IX_ADD_PROPOSAL: u8 = 7;
+/// Closes finished poll accounts and moves their lamports to a destination
+/// account (chairperson only). A poll is finished once its deadline has passed
+/// or its results are sealed. Accounts: the chairperson (signer), the state
+/// account, the destination, then the accounts to close: the state account
+/// and the voter records of this ballot, token voter records included.
+/// Proposal accounts are not tied to one ballot and cannot be closed. No
+/// payload.
+pub const IX_CLOSE_POLL: u8 = 8;
+/// Returns the 1-based rank of a proposal. Payload: proposal index (`u32`).
+pub const IX_PROPOSAL_RANK: u8 = 16;
+/// Checks a vote receipt. Payload: proposal index (`u32`) and tag (32 bytes).
+pub const IX_VERIFY_RECEIPT: u8 = 17;
+/// Casts an approval ballot; the voter account has to sign. Payload: approval
+/// mask (`u32`).
+pub const IX_APPROVAL_VOTE: u8 = 18;
+/// Returns the ballot's decimals (`u8`) followed by a page of the standings.
+/// Payload: offset and limit (`u32` each).
+pub const IX_LEADERBOARD: u8 = 19;
+/// Returns the ballot's decimals (`u8`) followed by the sum of all proposals'
+/// vote counts and the abstained weight as a [`VoteTotal`]. No payload.
+pub const IX_TOTAL_VOTES: u8 = 21;
+/// Writes a compact record of a finished ballot into its archive account
+/// (chairperson only). Accounts: the chairperson, who pays for the archive
+/// account, the state account, the proposal account, the archive account at
+/// the address derived from the state account, and the system program if the
+/// archive account has yet to be created. No payload.
+///
+/// The ballot is finished once its results are sealed or its deadline has
+/// passed. Its archive is written once and never overwritten.
+pub const IX_ARCHIVE_RESULTS: u8 = 22;
+/// Returns how many more votes a proposal needs to pass every other proposal
+/// as a [`VoteTotal`]. Payload: proposal index (`u32`).
+pub const IX_OVERTAKE_GAP: u8 = 23;
+/// Returns one chunk of the raw proposal records, without the list header.
+/// Payload: chunk index (`u32`).
+pub const IX_DUMP_PROPOSALS: u8 = 24;
+/// Sets the ballot title (chairperson only). Payload: title, zero-padded to
+/// 64 bytes.
+pub const IX_SET_TITLE: u8 = 25;
+/// Returns the ballot title without its zero padding. No payload.
+pub const IX_GET_TITLE: u8 = 26;
+/// Returns 1 if the outstanding weight on the voter roll can no longer change
+/// the leader, 0 otherwise. No payload.
+pub const IX_IS_DECIDED: u8 = 27;
+/// Returns a page of the pubkeys on the voter roll that hold voting weight
+/// but have not voted. Payload: offset and limit (`u32` each).
+pub const IX_UNVOTED_VOTERS: u8 = 28;
+/// Registers the expected maximum vote count of a proposal (chairperson only).
+/// Payload: proposal index (`u32`), maximum ([`VoteCount`], 0 for none) and a
+/// reject flag (`u8`) making votes past the maximum fail rather than only be
+/// logged.
+pub const IX_SET_EXPECTED_VOTES: u8 = 29;
+/// Returns how many of the recently recorded votes were cast in the last N
+/// slots, as a `u32`. Payload: N (`u64`).
+pub const IX_RECENT_VELOCITY: u8 = 30;
+/// Revokes the right to vote of every voter account passed after the state
+/// account (chairperson only), skipping those who have already voted. Returns
+/// the positions of the skipped accounts as `u32`s. No payload.
+pub const IX_BATCH_REVOKE: u8 = 31;
+/// Sets the number of decimals for displaying vote counts (chairperson only).
+/// Payload: decimals (`u8`).
+pub const IX_SET_DECIMALS: u8 = 32;
+/// Converts a version 0 state account to the current layout, writing it into
+/// a new state account (chairperson only). No payload.
+pub const IX_MIGRATE: u8 = 33;
+/// Seals the results (chairperson only): from then on no vote or count-editing
+/// instruction succeeds. Accounts: the chairperson and the state account,
+/// optionally followed by the proposal accounts. Payload: optionally a weight
+/// quorum (`u64`) and a voter quorum (`u32`) that participation has to meet,
+/// as for [`IX_QUORUM_PROGRESS`].
+///
+/// The ballot's own quorum has to be met as for [`IX_WINNING_PROPOSAL`], by
+/// the votes for the proposals passed, or for those the state holds if none
+/// are, together with the abstentions.
+pub const IX_SEAL_RESULTS: u8 = 34;
+/// Returns the chairperson's voter record from the voter roll, or nothing if
+/// the chairperson is not on the roll. No payload.
+pub const IX_CHAIRPERSON_VOTER: u8 = 35;
+/// Returns the weight that has voted, abstentions included ([`VoteTotal`]), the
+/// number of voters who have voted (`u32`), and the progress toward the weight
+/// quorum and the voter quorum in basis points (`u16` each, capped at 10000).
+/// Payload: weight quorum (`u64`) and voter quorum (`u32`); a zero quorum
+/// counts as met.
+pub const IX_QUORUM_PROGRESS: u8 = 36;
+/// Votes for a proposal by name, creating it as a write-in candidate if the
+/// ballot has no proposal of that name. Only for ballots storing each proposal
+/// in its own account. The voter account has to sign. Payload: name as a `u16`
+/// length and the name of at most [`MAX_NAME_LEN`] bytes, optionally followed
+/// by the description hash of the proposal to create (32 bytes).
+///
+/// Each proposal account is at the address derived from the seeds
+/// `"proposal"`, the state account and the proposal index (`u32`). Queries
+/// taking no state account of their own take it ahead of such proposal
+/// accounts.
+pub const IX_WRITE_IN_VOTE: u8 = 37;
+/// Returns one chunk of the ballot's configuration: layout version (`u8`),
+/// chairperson (32 bytes), minimum participation weight (`u32`), title (64
+/// bytes), decimals (`u8`), sealed flag (`u8`), snapshot slot (`u64`), started
+/// flag (`u8`), voting deadline (`i64`, 0 for none), quorum (`u64`), governance
+/// mint (32 bytes), and the proposal and voter counts of the state account
+/// (`u32` each). Payload: chunk index (`u32`).
+pub const IX_CONFIG: u8 = 38;
+/// Returns the minimum, maximum and mean (rounded down) weight across the
+/// voter roll, as `u64`s, all zero for an empty roll. No payload.
+pub const IX_WEIGHT_STATS: u8 = 39;
+/// Schedules the slot after which voting weights are frozen (chairperson
+/// only). Payload: snapshot slot (`u64`), 0 for none.
+pub const IX_SET_SNAPSHOT_SLOT: u8 = 40;
+/// Returns how many proposals have received votes, as a `u32`. No payload.
+pub const IX_CONTESTED_COUNT: u8 = 41;
+/// Ends the setup phase and opens voting (chairperson only). Accounts: the
+/// chairperson and the state account, optionally followed by the proposal
+/// accounts. No payload.
+///
+/// A ballot needs a proposal to vote for: the proposals passed, or those the
+/// state holds if none are.
+pub const IX_START_VOTING: u8 = 42;
+/// Returns the median weight across the voter roll, rounded down (`u64`), and
+/// 1 if the exact median is half a unit above it (`u8`), or zeros for an empty
+/// roll. No payload.
+pub const IX_MEDIAN_WEIGHT: u8 = 43;
+/// Returns the state of the race as one byte: 0 if the quorum is not met, 1 if
+/// undecided, 2 if decided and 3 if tied. Payload: weight quorum (`u64`) and
+/// voter quorum (`u32`), as for [`IX_QUORUM_PROGRESS`].
+pub const IX_RACE_STATUS: u8 = 44;
+/// Removes every voter without voting weight from the voter roll
+/// (chairperson only). No payload.
+pub const IX_COMPACT_ROLL: u8 = 45;
+/// Returns the leading proposal's index (`u32`) and its share of all votes in
+/// basis points (`u16`), 0 if no votes have been cast. No payload.
+pub const IX_WINNER_SHARE: u8 = 46;
+/// Returns 1 if the leading proposal's share of the votes is below the
+/// threshold, 0 otherwise (`u8`), followed by the indices of the two leading
+/// proposals (`u32` each, `u32::MAX` where the ballot has fewer). Payload:
+/// threshold in basis points (`u16`), 0 for a simple majority (5000).
+pub const IX_NEEDS_RUNOFF: u8 = 47;
+/// Returns the serialized record of one proposal, including its description
+/// hash, read from the state account, a proposal list or the proposal's own
+/// account following the state account. Payload: proposal index (`u32`).
+pub const IX_GET_PROPOSAL: u8 = 48;
+/// Returns 1 if the description hashes to the proposal's stored description
+/// hash, 0 otherwise (`u8`). The proposal is read as for [`IX_GET_PROPOSAL`].
+/// Payload: proposal index (`u32`) followed by the full description bytes.
+pub const IX_VERIFY_DESCRIPTION: u8 = 49;
+/// Returns how many more proposals the voter may vote for (`u32`). No
+/// payload.
+pub const IX_REMAINING_CHOICES: u8 = 50;
+/// Returns raw voter roll entries, each a voter's key followed by its
+/// [`Voter`] record ([`ROLL_ENTRY_LEN`] bytes), as many as fit in the return
+/// data. Payload: index of the first entry (`u32`) and the most entries to
+/// return (`u32`).
+pub const IX_DUMP_ROLL: u8 = 51;
+/// Returns the slot the ballot was initialized in (`u64`), 0 if it predates
+/// the record. No payload.
+pub const IX_CREATED_AT: u8 = 52;
+/// Sets the governance token mint that weighs [`IX_VOTE_WITH_TOKEN`] ballots
+/// (chairperson only, before voting starts). Payload: mint address (32
+/// bytes), the default key to disable token voting.
+pub const IX_SET_GOV_MINT: u8 = 53;
+/// Casts a vote weighted by the signer's balance of the governance token.
+/// Accounts: the signer's token voter record, the signer's token account, the
+/// signer, the proposal account and the state account, followed by the system
+/// program the first time the signer votes on the ballot. Payload: proposal
+/// index (`u32`).
+///
+/// Each token holder has one voter record per ballot, at the address derived
+/// from the seeds `"token_voter"`, the state account and the holder. The
+/// program creates it, funded by the holder, on their first vote; a record
+/// [`TOKEN_VOTER_LEN`] bytes long, owned by this program, may also be created
+/// ahead of time.
+pub const IX_VOTE_WITH_TOKEN: u8 = 54;
+/// Moves the signing voter's delegation from one delegate to another while
+/// voting is open. Accounts: the delegating voter (signer), the current
+/// delegate, the new delegate, the state account, then the proposal account
+/// of each delegate, current first, that has voted for a proposal. No
+/// payload.
+pub const IX_REDELEGATE: u8 = 55;
+/// Hands the signing voter's weight to the ballot's chairperson, as
+/// [`IX_DELEGATE`] does for any delegate. Accounts: the delegating voter
+/// (signer), the chairperson's voter record, at the chairperson's address, the
+/// state account, and the chairperson's proposal account once the chairperson
+/// has voted. Payload: as for [`IX_DELEGATE`].
+pub const IX_DELEGATE_TO_CHAIR: u8 = 56;
+
+/// A decoded core ballot instruction.
+///
+/// Only the four core instructions, [`IX_GIVE_RIGHT`], [`IX_VOTE`],
+/// [`IX_WINNING_PROPOSAL`] and [`IX_WINNER_NAME`], are decoded here; `unpack`
+/// rejects every other tag. The remaining instructions, from [`IX_INITIALIZE`]
+/// on, are dispatched on their tag and parse their own payload after
+/// `validate_payload` has checked its length.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VotingInstruction {
+    /// [`IX_GIVE_RIGHT`]
+    GiveRightToVote { weight: u64 },
+    /// [`IX_VOTE`]
+    Vote {
+        proposal_index: u32,
+        expected_nonce: Option<u64>,
+    },
+    /// [`IX_WINNING_PROPOSAL`]
+    WinningProposal,
+    /// [`IX_WINNER_NAME`]
+    WinnerName,
+}
+
+impl VotingInstruction {
+    /// Decodes a core instruction, checking its tag, payload length and field
+    /// ranges.
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        let (&tag, payload) = input
+            .split_first()
+            .ok_or(ProgramError::InvalidInstructionData)?;
+        let read_u32 = |at: usize| u32::from_le_bytes(payload[at..at + 4].try_into().unwrap());
+        let read_u64 = |at: usize| u64::from_le_bytes(payload[at..at + 8].try_into().unwrap());
+
+        let instruction = match (tag, payload.len()) {
+            (IX_GIVE_RIGHT, 0) => Self::GiveRightToVote { weight: 1 },
+            (IX_GIVE_RIGHT, 8) => match read_u64(0) {
+                0 => {
+                    msg!("A voting weight has to be non-zero");
+                    return Err(ProgramError::InvalidInstructionData);
+                }
+                weight => Self::GiveRightToVote { weight },
+            },
+            (IX_VOTE, 4) => Self::Vote {
+                proposal_index: read_u32(0),
+                expected_nonce: None,
+            },
+            (IX_VOTE, 12) => Self::Vote {
+                proposal_index: read_u32(0),
+                expected_nonce: Some(read_u64(4)),
+            },
+            (IX_WINNING_PROPOSAL, 0) => Self::WinningProposal,
+            (IX_WINNER_NAME, 0) => Self::WinnerName,
+            (IX_GIVE_RIGHT | IX_VOTE | IX_WINNING_PROPOSAL | IX_WINNER_NAME, payload_len) => {
+                msg!(
+                    "Invalid payload length {} for instruction {}",
+                    payload_len,
+                    tag
+                );
+                return Err(ProgramError::InvalidInstructionData);
+            }
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        Ok(instruction)
+    }
+}
+
+/// Evaluates a handler call between compute-unit log markers when the crate is
+/// built with the `profiling` feature, and is a plain call otherwise.
+macro_rules! profile {
+    ($handler:literal, $call:expr) => {{
+        #[cfg(feature = "profiling")]
+        {
+            msg!(concat!("PROFILE ", $handler, " start"));
+            solana_program::log::sol_log_compute_units();
+        }
+
+        let result = $call;
+
+        #[cfg(feature = "profiling")]
+        {
+            msg!(concat!("PROFILE ", $handler, " end"));
+            solana_program::log::sol_log_compute_units();
+        }
+
+        result
+    }};
+}
+
+entrypoint!(process_instruction);
+
+/// Decodes and runs one instruction. This is the program entrypoint, also
+/// callable directly from off-chain tests.
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    msg!("Simple Voting System Rust program entrypoint");
+
+    // Every instruction starts with its tag
+    if instruction_data.is_empty() {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    validate_payload(instruction_data)?;
+    validate_account_count(instruction_data[0], accounts)?;
+
+    // Parse the instruction data and call the appropriate function based on its value
+    match instruction_data[0] {
+        IX_GIVE_RIGHT | IX_VOTE | IX_WINNING_PROPOSAL | IX_WINNER_NAME => {
+            return process_core_instruction(
+                program_id,
+                accounts,
+                VotingInstruction::unpack(instruction_data)?,
+            );
+        }
+        IX_INITIALIZE => profile!(
+            "initialize",
+            initialize(program_id, accounts, instruction_data)
+        )?,
+        IX_DELEGATE => profile!("delegate", delegate(program_id, accounts, instruction_data))?,
+        IX_CHANGE_VOTE => profile!(
+            "change_vote",
+            change_vote(program_id, accounts, instruction_data)
+        )?,
+        IX_ADD_PROPOSAL => profile!(
+            "add_proposal",
+            add_proposal(program_id, accounts, instruction_data)
+        )?,
+        IX_CLOSE_POLL => profile!(
+            "close_poll",
+            close_poll(program_id, accounts, instruction_data)
+        )?,
+        IX_PROPOSAL_RANK => profile!(
+            "proposal_rank",
+            proposal_rank(program_id, accounts, instruction_data)
+        )?,
+        IX_VERIFY_RECEIPT => profile!(
+            "verify_receipt",
+            verify_receipt(program_id, accounts, instruction_data)
+        )?,
+        IX_APPROVAL_VOTE => profile!(
+            "approval_vote",
+            approval_vote(program_id, accounts, instruction_data)
+        )?,
+        IX_LEADERBOARD => profile!(
+            "leaderboard",
+            leaderboard(program_id, accounts, instruction_data)
+        )?,
+        IX_TOTAL_VOTES => profile!(
+            "total_votes",
+            total_votes(program_id, accounts, instruction_data)
+        )?,
+        IX_ARCHIVE_RESULTS => profile!(
+            "archive_results",
+            archive_results(program_id, accounts, instruction_data)
+        )?,
+        IX_OVERTAKE_GAP => profile!(
+            "overtake_gap",
+            overtake_gap(program_id, accounts, instruction_data)
+        )?,
+        IX_DUMP_PROPOSALS => profile!(
+            "dump_proposals",
+            dump_proposals(program_id, accounts, instruction_data)
+        )?,
+        IX_SET_TITLE => profile!(
+            "set_title",
+            set_title(program_id, accounts, instruction_data)
+        )?,
+        IX_GET_TITLE => profile!(
+            "get_title",
+            get_title(program_id, accounts, instruction_data)
+        )?,
+        IX_IS_DECIDED => profile!(
+            "is_decided",
+            is_decided(program_id, accounts, instruction_data)
+        )?,
+        IX_UNVOTED_VOTERS => profile!(
+            "unvoted_voters",
+            unvoted_voters(program_id, accounts, instruction_data)
+        )?,
+        IX_SET_EXPECTED_VOTES => profile!(
+            "set_expected_votes",
+            set_expected_votes(program_id, accounts, instruction_data)
+        )?,
+        IX_RECENT_VELOCITY => profile!(
+            "recent_velocity",
+            recent_velocity(program_id, accounts, instruction_data)
+        )?,
+        IX_BATCH_REVOKE => profile!(
+            "batch_revoke",
+            batch_revoke(program_id, accounts, instruction_data)
+        )?,
+        IX_SET_DECIMALS => profile!(
+            "set_decimals",
+            set_decimals(program_id, accounts, instruction_data)
+        )?,
+        IX_MIGRATE => profile!("migrate", migrate(program_id, accounts, instruction_data))?,
+        IX_SEAL_RESULTS => profile!(
+            "seal_results",
+            seal_results(program_id, accounts, instruction_data)
+        )?,
+        IX_CHAIRPERSON_VOTER => profile!(
+            "chairperson_voter",
+            chairperson_voter(program_id, accounts, instruction_data)
+        )?,
+        IX_QUORUM_PROGRESS => profile!(
+            "quorum_progress",
+            quorum_progress(program_id, accounts, instruction_data)
+        )?,
+        IX_WRITE_IN_VOTE => profile!(
+            "write_in_vote",
+            write_in_vote(program_id, accounts, instruction_data)
+        )?,
+        IX_CONFIG => profile!("config", config(program_id, accounts, instruction_data))?,
+        IX_WEIGHT_STATS => profile!(
+            "weight_stats",
+            weight_stats(program_id, accounts, instruction_data)
+        )?,
+        IX_SET_SNAPSHOT_SLOT => profile!(
+            "set_snapshot_slot",
+            set_snapshot_slot(program_id, accounts, instruction_data)
+        )?,
+        IX_CONTESTED_COUNT => profile!(
+            "contested_count",
+            contested_count(program_id, accounts, instruction_data)
+        )?,
+        IX_START_VOTING => profile!(
+            "start_voting",
+            start_voting(program_id, accounts, instruction_data)
+        )?,
+        IX_MEDIAN_WEIGHT => profile!(
+            "median_weight",
+            median_weight(program_id, accounts, instruction_data)
+        )?,
+        IX_RACE_STATUS => profile!(
+            "race_status",
+            race_status(program_id, accounts, instruction_data)
+        )?,
+        IX_COMPACT_ROLL => profile!(
+            "compact_roll",
+            compact_roll(program_id, accounts, instruction_data)
+        )?,
+        IX_WINNER_SHARE => profile!(
+            "winner_share",
+            winner_share(program_id, accounts, instruction_data)
+        )?,
+        IX_NEEDS_RUNOFF => profile!(
+            "needs_runoff",
+            needs_runoff(program_id, accounts, instruction_data)
+        )?,
+        IX_GET_PROPOSAL => profile!(
+            "get_proposal",
+            get_proposal(program_id, accounts, instruction_data)
+        )?,
+        IX_VERIFY_DESCRIPTION => profile!(
+            "verify_description",
+            verify_description(program_id, accounts, instruction_data)
+        )?,
+        IX_CREATED_AT => profile!(
+            "created_at",
+            created_at(program_id, accounts, instruction_data)
+        )?,
+        IX_SET_GOV_MINT => profile!(
+            "set_gov_mint",
+            set_gov_mint(program_id, accounts, instruction_data)
+        )?,
+        IX_VOTE_WITH_TOKEN => profile!(
+            "vote_with_token",
+            vote_with_token(program_id, accounts, instruction_data)
+        )?,
+        IX_REDELEGATE => profile!(
+            "redelegate",
+            redelegate(program_id, accounts, instruction_data)
+        )?,
+        IX_DELEGATE_TO_CHAIR => profile!(
+            "delegate_to_chair",
+            delegate_to_chair(program_id, accounts, instruction_data)
+        )?,
+        IX_DUMP_ROLL => profile!(
+            "dump_roll",
+            dump_roll(program_id, accounts, instruction_data)
+        )?,
+        IX_REMAINING_CHOICES => profile!(
+            "remaining_choices",
+            remaining_choices(program_id, accounts, instruction_data)
+        )?,
+        _ => {
+            msg!("Invalid Instruction");
+            return Err(ProgramError::InvalidInstructionData);
+        }
+    }
+    Ok(())
+}
+
+/// Runs a decoded core instruction, logging its outcome. Program-specific
+/// errors and invalid account data are passed through; any other failure is
+/// reported as `ProgramError::Custom(0)`.
+fn process_core_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction: VotingInstruction,
+) -> ProgramResult {
+    let (result, success) = match instruction {
+        VotingInstruction::GiveRightToVote { weight } => (
+            profile!(
+                "give_right_to_vote",
+                give_right_to_vote(program_id, accounts, weight)
+            ),
+            "Voting rights granted successfully",
+        ),
+        VotingInstruction::Vote {
+            proposal_index,
+            expected_nonce,
+        } => (
+            profile!(
+                "vote",
+                vote(program_id, accounts, proposal_index, expected_nonce)
+            ),
+            "Vote cast successfully",
+        ),
+        VotingInstruction::WinningProposal => (
+            profile!("winning_proposal", winning_proposal(program_id, accounts)),
+            "Winning proposal computed successfully",
+        ),
+        VotingInstruction::WinnerName => (
+            profile!("winner_name", winner_name(program_id, accounts)),
+            "Winner name retrieved successfully",
+        ),
+    };
+
+    match result {
+        Ok(()) => {
+            // Handle success case
+            msg!(success);
+            Ok(())
+        }
+        Err(error) => match error {
+            ProgramError::InvalidAccountData => {
+                // Handle specific error case
+                msg!("Encountered InvalidAccountData: {:?}", error);
+                Err(error)
+            }
+            ProgramError::Custom(_) => {
+                // Program-specific errors are passed through for the client to decode
+                msg!("Encountered a voting error: {:?}", error);
+                Err(error)
+            }
+            _ => {
+                // Handle any other error case
+                msg!("Encountered an unknown error: {:?}", error);
+                Err(ProgramError::Custom(0))
+            }
+        },
+    }
+}
+
+/// Checks that an instruction was given at least as many accounts as it always
+/// reads, so that a short account list fails with a clear error before the
+/// handler runs. Handlers still check any further accounts they read.
+fn validate_account_count(tag: u8, accounts: &[AccountInfo]) -> ProgramResult {
+    let min_accounts = match tag {
+        IX_WINNER_NAME
+        | IX_PROPOSAL_RANK
+        | IX_VERIFY_RECEIPT
+        | IX_OVERTAKE_GAP
+        | IX_DUMP_PROPOSALS
+        | IX_GET_TITLE
+        | IX_UNVOTED_VOTERS
+        | IX_RECENT_VELOCITY
+        | IX_CHAIRPERSON_VOTER
+        | IX_QUORUM_PROGRESS
+        | IX_CONFIG
+        | IX_WEIGHT_STATS
+        | IX_CONTESTED_COUNT
+        | IX_MEDIAN_WEIGHT
+        | IX_WINNER_SHARE
+        | IX_NEEDS_RUNOFF
+        | IX_GET_PROPOSAL
+        | IX_VERIFY_DESCRIPTION
+        | IX_REMAINING_CHOICES
+        | IX_DUMP_ROLL
+        | IX_CREATED_AT => 1,
+        IX_WINNING_PROPOSAL | IX_INITIALIZE | IX_ADD_PROPOSAL | IX_LEADERBOARD | IX_TOTAL_VOTES
+        | IX_SET_TITLE | IX_IS_DECIDED | IX_BATCH_REVOKE | IX_SET_DECIMALS | IX_SEAL_RESULTS
+        | IX_SET_SNAPSHOT_SLOT | IX_START_VOTING | IX_RACE_STATUS | IX_COMPACT_ROLL
+        | IX_SET_GOV_MINT => 2,
+        IX_GIVE_RIGHT
+        | IX_VOTE
+        | IX_APPROVAL_VOTE
+        | IX_SET_EXPECTED_VOTES
+        | IX_MIGRATE
+        | IX_DELEGATE
+        | IX_CLOSE_POLL
+        | IX_DELEGATE_TO_CHAIR => 3,
+        IX_ARCHIVE_RESULTS | IX_CHANGE_VOTE | IX_REDELEGATE => 4,
+        IX_WRITE_IN_VOTE | IX_VOTE_WITH_TOKEN => 5,
+        // Unknown tags are rejected by the dispatcher
+        _ => 0,
+    };
+
+    if accounts.len() < min_accounts {
+        msg!(
+            "Instruction {} expects at least {} accounts, got {}",
+            tag,
+            min_accounts,
+            accounts.len()
+        );
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    Ok(())
+}
+
+/// Checks that the payload following the instruction tag has exactly the
+/// length that instruction expects. Trailing bytes are rejected rather than
+/// ignored, so a client encoding instructions wrongly fails loudly instead of
+/// having part of its input silently dropped. Core instructions are checked
+/// when `VotingInstruction::unpack` decodes them.
+fn validate_payload(instruction_data: &[u8]) -> ProgramResult {
+    let expected_lens: &[usize] = match instruction_data[0] {
+        IX_TOTAL_VOTES | IX_ARCHIVE_RESULTS | IX_GET_TITLE | IX_IS_DECIDED | IX_BATCH_REVOKE
+        | IX_MIGRATE | IX_CHAIRPERSON_VOTER | IX_WEIGHT_STATS | IX_CONTESTED_COUNT
+        | IX_START_VOTING | IX_MEDIAN_WEIGHT | IX_COMPACT_ROLL | IX_WINNER_SHARE
+        | IX_REMAINING_CHOICES | IX_CREATED_AT | IX_CLOSE_POLL | IX_REDELEGATE => &[0],
+        IX_PROPOSAL_RANK | IX_APPROVAL_VOTE | IX_OVERTAKE_GAP | IX_DUMP_PROPOSALS | IX_CONFIG
+        | IX_GET_PROPOSAL | IX_VOTE_WITH_TOKEN => &[4],
+        IX_VERIFY_RECEIPT => &[36],
+        IX_SET_GOV_MINT => &[32],
+        IX_LEADERBOARD | IX_UNVOTED_VOTERS | IX_RECENT_VELOCITY | IX_SET_SNAPSHOT_SLOT => &[8],
+        IX_QUORUM_PROGRESS | IX_RACE_STATUS => &[12],
+        IX_DUMP_ROLL | IX_CHANGE_VOTE => &[8],
+        IX_SEAL_RESULTS => &[0, 12],
+        IX_DELEGATE | IX_DELEGATE_TO_CHAIR => &[0, 8],
+        IX_SET_TITLE => &[64],
+        IX_SET_DECIMALS => &[1],
+        IX_NEEDS_RUNOFF => &[2],
+        IX_SET_EXPECTED_VOTES => &[5 + COUNT_LEN],
+        // Any number of proposal names may follow the deadline and quorum.
+        // Names have a length of their own, checked as they are parsed
+        IX_INITIALIZE if instruction_data.len() > 17 => return Ok(()),
+        IX_INITIALIZE => &[16],
+        IX_WRITE_IN_VOTE | IX_ADD_PROPOSAL => return Ok(()),
+        // The description after the proposal index may have any length
+        IX_VERIFY_DESCRIPTION if instruction_data.len() > 4 => return Ok(()),
+        IX_VERIFY_DESCRIPTION => &[4],
+        // Unknown tags are rejected by the dispatcher
+        _ => return Ok(()),
+    };
+
+    let payload_len = instruction_data.len() - 1;
+    if !expected_lens.contains(&payload_len) {
+        msg!(
+            "Invalid payload length {} for instruction {}",
+            payload_len,
+            instruction_data[0]
+        );
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    Ok(())
+}
+
+fn initialize(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let chairperson_account = next_account_info(accounts_iter)?;
+    let system_account = next_account_info(accounts_iter)?;
+
+    if !chairperson_account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    // Later instructions only read a state from accounts this program owns
+    if system_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    check_rent_exempt(&Rent::get()?, system_account)?;
+
+    // A fresh account is all zeros; a stored version or chairperson means the
+    // ballot has been set up already
+    let system_data = &mut system_account.data.borrow_mut();
+    if system_data.iter().take(33).any(|&byte| byte != 0) {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    // Parse the deadline, the quorum and the proposal names from the
+    // instruction data
+    let end_timestamp = i64::from_le_bytes(instruction_data[1..9].try_into().unwrap());
+    let quorum = u64::from_le_bytes(instruction_data[9..17].try_into().unwrap());
+    let clock = Clock::get()?;
+    if end_timestamp != 0 && end_timestamp <= clock.unix_timestamp {
+        msg!("The voting deadline has to lie in the future");
+        return Err(ProgramError::InvalidArgument);
+    }
+    let mut proposals = Vec::new();
+    let mut names = &instruction_data[17..];
+    while !names.is_empty() {
+        if proposals.len() == MAX_PROPOSALS {
+            msg!("A ballot holds at most {} proposals", MAX_PROPOSALS);
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let (name, rest) = split_name(names)?;
+        validate_name(name)?;
+        proposals.push(Proposal {
+            name: name.to_vec(),
+            vote_count: 0,
+            nonce: 0,
+            max_expected_votes: 0,
+            reject_unexpected: false,
+            desc_hash: [0; 32],
+        });
+        names = rest;
+    }
+
+    let system = SimpleVotingSystem {
+        chairperson: *chairperson_account.key,
+        min_participation_weight: 0,
+        title: [0; 64],
+        decimals: 0,
+        results_sealed: false,
+        snapshot_slot: 0,
+        voting_started: false,
+        created_slot: clock.slot,
+        end_timestamp,
+        quorum,
+        abstain_weight: 0,
+        gov_mint: Pubkey::default(),
+        voters: Vec::new(),
+        proposals,
+    };
+    system.write_into(system_data)?;
+
+    msg!("Ballot initialized");
+    Ok(())
+}
+
+/// Appends a proposal to the ballot. Proposals can only be added while the
+/// ballot is still being set up, so every voter sees the same ballot.
+///
+/// The state account is not grown here: it has to be created with room for
+/// the proposals the chairperson intends to add.
+fn add_proposal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let chairperson_account = next_account_info(accounts_iter)?;
+    let system_account = next_account_info(accounts_iter)?;
+
+    // Check if the sender is the chairperson
+    let mut system = require_chairperson(program_id, system_account, chairperson_account)?;
+
+    // Keep the ballot fixed once voting has opened or votes have been cast
+    if system.voting_started
+        || system.results_sealed
+        || system
+            .proposals
+            .iter()
+            .any(|proposal| proposal.vote_count != 0)
+    {
+        msg!("Proposals cannot be added once voting has started");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Parse and validate the name from the instruction data
+    let (name, desc_hash) = split_name(&instruction_data[1..])?;
+    let desc_hash = desc_hash_field(desc_hash)?;
+    validate_name(name)?;
+    if system
+        .proposals
+        .iter()
+        .any(|proposal| proposal.name == name)
+    {
+        msg!("A proposal with this name already exists");
+        return Err(ProgramError::InvalidArgument);
+    }
+    if system.proposals.len() >= MAX_PROPOSALS {
+        msg!("A ballot holds at most {} proposals", MAX_PROPOSALS);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    system.proposals.push(Proposal {
+        name: name.to_vec(),
+        vote_count: 0,
+        nonce: 0,
+        max_expected_votes: 0,
+        reject_unexpected: false,
+        desc_hash,
+    });
+    system.write_into(&mut system_account.try_borrow_mut_data()?)?;
+
+    msg!("Proposal {} added", system.proposals.len() - 1);
+    Ok(())
+}
+
+/// Closes accounts of a finished poll. Each account's lamports go to the
+/// destination and its data is zeroed; the runtime purges an account left
+/// without lamports at the end of the transaction, handing it back to the
+/// system program.
+fn close_poll(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let chairperson_account = next_account_info(accounts_iter)?;
+    let system_account = next_account_info(accounts_iter)?;
+    let destination_account = next_account_info(accounts_iter)?;
+    let closed_accounts = accounts_iter.as_slice();
+
+    // Check if the sender is the chairperson
+    let system = require_chairperson(program_id, system_account, chairperson_account)?;
+
+    // Check if the poll is over
+    system.check_finished()?;
+
+    // Check every account before touching any, so the instruction never
+    // leaves the poll half closed
+    for account in closed_accounts {
+        if account.key == destination_account.key {
+            msg!("The destination cannot be closed");
+            return Err(ProgramError::InvalidArgument);
+        }
+        if account.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        // Other ballots' accounts are owned by this program too, so each one
+        // has to be tied to this poll
+        if account.key != system_account.key
+            && !is_ballot_voter(program_id, system_account.key, account)
+        {
+            msg!("{} does not belong to this poll", account.key);
+            return Err(ProgramError::InvalidArgument);
+        }
+    }
+
+    for account in closed_accounts {
+        // An account passed twice has already been emptied
+        let lamports = account.lamports();
+        let destination_lamports = destination_account
+            .lamports()
+            .checked_add(lamports)
+            .ok_or(ProgramError::InvalidArgument)?;
+        **destination_account.try_borrow_mut_lamports()? = destination_lamports;
+        **account.try_borrow_mut_lamports()? = 0;
+        account.try_borrow_mut_data()?.fill(0);
+    }
+
+    msg!("Closed {} accounts", closed_accounts.len());
+    Ok(())
+}
+
+/// Whether `account` is a voter record of the ballot whose state account is
+/// `system_key`: either a token holder's record at the address derived from
+/// the ballot and the holder it stores, or a record granted on that ballot,
+/// which like any voter account may be larger than the record.
+fn is_ballot_voter(program_id: &Pubkey, system_key: &Pubkey, account: &AccountInfo) -> bool {
+    let data = account.data.borrow();
+    if data.len() == TOKEN_VOTER_LEN
+        && matches!(
+            read_pubkey(&data, Voter::LEN),
+            Ok(holder) if token_voter_address(program_id, system_key, &holder).0 == *account.key
+        )
+    {
+        return true;
+    }
+
+    matches!(Voter::from_bytes(&data), Ok(voter) if voter.ballot == *system_key)
+}
+
+fn delegate(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let from_account = next_account_info(accounts_iter)?;
+    let to_account = next_account_info(accounts_iter)?;
+    let system_account = next_account_info(accounts_iter)?;
+    // The delegate's proposal account is only read once the delegate has voted
+    let proposal_account = accounts_iter.next();
+
+    delegate_weight(
+        program_id,
+        from_account,
+        to_account,
+        system_account,
+        proposal_account,
+        delegated_amount(instruction_data),
+    )
+}
+
+/// Delegates to the chairperson stored in the state rather than to a voter of
+/// the caller's choosing, for ballots run as proxy votes.
+fn delegate_to_chair(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let from_account = next_account_info(accounts_iter)?;
+    let chairperson_account = next_account_info(accounts_iter)?;
+    let system_account = next_account_info(accounts_iter)?;
+    // The chairperson's proposal account is only read once they have voted
+    let proposal_account = accounts_iter.next();
+
+    let system = load_state(program_id, system_account)?;
+    if *chairperson_account.key != system.chairperson {
+        msg!(
+            "{} is not the chairperson's voter record",
+            chairperson_account.key
+        );
+        return Err(VotingError::NotChairperson.into());
+    }
+
+    delegate_weight(
+        program_id,
+        from_account,
+        chairperson_account,
+        system_account,
+        proposal_account,
+        delegated_amount(instruction_data),
+    )
+}
+
+/// Parses the part of the weight to delegate from the instruction data, if
+/// one is given.
+fn delegated_amount(instruction_data: &[u8]) -> Option<u64> {
+    instruction_data
+        .get(1..9)
+        .map(|amount| u64::from_le_bytes(amount.try_into().unwrap()))
+}
+
+/// Hands the weight of the voter at `from_account`, or the given `amount` of
+/// it, to the voter at `to_account`, or straight to the proposal `to_account`
+/// chose through `proposal_account` if they have already voted.
+fn delegate_weight(
+    program_id: &Pubkey,
+    from_account: &AccountInfo,
+    to_account: &AccountInfo,
+    system_account: &AccountInfo,
+    proposal_account: Option<&AccountInfo>,
+    amount: Option<u64>,
+) -> ProgramResult {
+    // Check if the voter delegating signed the transaction
+    if !from_account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    check_key_set(to_account.key)?;
+    // Both records are borrowed mutably below, so they have to live in
+    // distinct accounts owned by this program
+    if from_account.key == to_account.key {
+        msg!("A voter cannot delegate to themselves");
+        return Err(ProgramError::InvalidArgument);
+    }
+    // The state is written alongside them to keep the roll in sync
+    if from_account.key == system_account.key || to_account.key == system_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if from_account.owner != program_id || to_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Delegating moves weight like a vote does
+    let mut system = load_state(program_id, system_account)?;
+    system.check_voting_open()?;
+    system.check_weights_unfrozen()?;
+
+    let from_data = &mut from_account.data.borrow_mut();
+    let to_data = &mut to_account.data.borrow_mut();
+    if from_data.len() < Voter::LEN || to_data.len() < Voter::LEN {
+        return Err(VotingError::AccountTooSmall.into());
+    }
+    let mut from = Voter::from_bytes(from_data)?;
+    let mut to = Voter::from_bytes(to_data)?;
+
+    if from.voted {
+        return Err(VotingError::AlreadyVoted.into());
+    }
+    if !from.may_vote_on(system_account.key) {
+        return Err(VotingError::NoRightToVote.into());
+    }
+    check_delegate(from_account.key, &to, system_account.key)?;
+
+    let weight = amount.unwrap_or(from.weight);
+    if weight == 0 || weight > from.weight {
+        msg!("Cannot delegate {} of a weight of {}", weight, from.weight);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    give_weight(
+        program_id,
+        system_account,
+        &mut system,
+        &mut to,
+        proposal_account,
+        weight,
+    )?;
+    to_data[..Voter::LEN].copy_from_slice(&to.to_bytes());
+
+    if weight == from.weight {
+        from.voted = true;
+        from.delegate = *to_account.key;
+    } else {
+        // A partial delegation leaves the voter the rest to vote with, so it
+        // is not recorded as their delegate
+        from.weight -= weight;
+    }
+    from_data[..Voter::LEN].copy_from_slice(&from.to_bytes());
+    system.record_voter(to_account.key, &to);
+    sync_roll(system_account, &mut system, from_account.key, &from)?;
+
+    msg!(
+        "DELEGATE from={} to={} weight={}",
+        from_account.key,
+        to_account.key,
+        weight
+    );
+    Ok(())
+}
+
+/// Moves a delegation to another delegate. The weight is taken back from the
+/// current delegate, out of the vote they cast with it if they have voted, and
+/// handed to the new delegate as a fresh delegation would.
+fn redelegate(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let from_account = next_account_info(accounts_iter)?;
+    let old_account = next_account_info(accounts_iter)?;
+    let to_account = next_account_info(accounts_iter)?;
+    let system_account = next_account_info(accounts_iter)?;
+
+    // Check if the voter delegating signed the transaction
+    if !from_account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    // The three records are borrowed mutably below, so they have to live in
+    // distinct accounts owned by this program
+    if to_account.key == from_account.key || old_account.key == from_account.key {
+        msg!("A voter cannot delegate to themselves");
+        return Err(ProgramError::InvalidArgument);
+    }
+    if to_account.key == old_account.key {
+        msg!("The vote is already delegated to {}", to_account.key);
+        return Err(ProgramError::InvalidArgument);
+    }
+    // The state is written alongside them to keep the roll in sync
+    if [from_account.key, old_account.key, to_account.key].contains(&system_account.key) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if from_account.owner != program_id
+        || old_account.owner != program_id
+        || to_account.owner != program_id
+    {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Redelegating moves weight like a vote does
+    let mut system = load_state(program_id, system_account)?;
+    system.check_voting_open()?;
+    system.check_weights_unfrozen()?;
+
+    let from_data = &mut from_account.data.borrow_mut();
+    let old_data = &mut old_account.data.borrow_mut();
+    let to_data = &mut to_account.data.borrow_mut();
+    if from_data.len() < Voter::LEN || old_data.len() < Voter::LEN || to_data.len() < Voter::LEN {
+        return Err(VotingError::AccountTooSmall.into());
+    }
+    let mut from = Voter::from_bytes(from_data)?;
+    let mut old = Voter::from_bytes(old_data)?;
+    let mut to = Voter::from_bytes(to_data)?;
+
+    if from.ballot != *system_account.key {
+        return Err(VotingError::NoRightToVote.into());
+    }
+    if from.delegate != *old_account.key {
+        msg!("The vote is not delegated to {}", old_account.key);
+        return Err(ProgramError::InvalidArgument);
+    }
+    check_delegate(from_account.key, &to, system_account.key)?;
+
+    // Each delegate's proposal account is only passed once they voted for one
+    let old_proposal_account = if old.voted && old.vote != ABSTAIN {
+        Some(next_account_info(accounts_iter)?)
+    } else {
+        None
+    };
+    take_weight(
+        program_id,
+        system_account,
+        &mut system,
+        &mut old,
+        old_proposal_account,
+        from.weight,
+    )?;
+    give_weight(
+        program_id,
+        system_account,
+        &mut system,
+        &mut to,
+        accounts_iter.next(),
+        from.weight,
+    )?;
+    old_data[..Voter::LEN].copy_from_slice(&old.to_bytes());
+    to_data[..Voter::LEN].copy_from_slice(&to.to_bytes());
+
+    from.delegate = *to_account.key;
+    from_data[..Voter::LEN].copy_from_slice(&from.to_bytes());
+    system.record_voter(old_account.key, &old);
+    system.record_voter(to_account.key, &to);
+    sync_roll(system_account, &mut system, from_account.key, &from)?;
+
+    // With five arguments msg! would log them as plain numbers
+    msg!(&format!(
+        "REDELEGATE from={} old={} to={} weight={}",
+        from_account.key, old_account.key, to_account.key, from.weight
+    ));
+    Ok(())
+}
+
+/// Fails unless the voter `to` can take on weight delegated by the voter at
+/// `from_key` on the ballot whose state account is `system_key`.
+fn check_delegate(from_key: &Pubkey, to: &Voter, system_key: &Pubkey) -> ProgramResult {
+    if !to.may_vote_on(system_key) {
+        return Err(VotingError::NoRightToVote.into());
+    }
+    // Delegation is a single hop: a delegate who handed their own weight on,
+    // in particular back to this voter, cannot receive more
+    if to.delegate == *from_key {
+        msg!("Delegation would form a loop");
+        return Err(ProgramError::InvalidArgument);
+    }
+    if to.has_delegated() {
+        msg!("The delegate has delegated their own vote");
+        return Err(ProgramError::InvalidArgument);
+    }
+    // An approval ballot backs several proposals, none of which the weight
+    // could be added to on its own
+    if to.cast_approvals() {
+        msg!("The delegate cast an approval ballot");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    Ok(())
+}
+
+/// Hands `weight` to the delegate `to`. Once the delegate has voted, the
+/// weight is also added to the proposal they chose, read from
+/// `proposal_account`, or to their abstention.
+fn give_weight(
+    program_id: &Pubkey,
+    system_account: &AccountInfo,
+    system: &mut SimpleVotingSystem,
+    to: &mut Voter,
+    proposal_account: Option<&AccountInfo>,
+    weight: u64,
+) -> ProgramResult {
+    if to.voted && to.vote == ABSTAIN {
+        // The delegate abstained, so the weight joins their abstention
+        system.abstain_weight = system
+            .abstain_weight
+            .checked_add(weight)
+            .ok_or(VotingError::VoteCountOverflow)?;
+    } else if to.voted {
+        // The delegate's vote is already counted, so the weight goes straight
+        // to the proposal they chose
+        let proposal_account = proposal_account.ok_or(ProgramError::NotEnoughAccountKeys)?;
+        add_votes(
+            program_id,
+            system_account,
+            system,
+            proposal_account,
+            to.vote,
+            VoteCount::from(weight),
+        )?;
+    }
+    // Either way the delegate now carries the weight, so it is counted once,
+    // through them, and moves with them on a changed vote
+    to.weight = to
+        .weight
+        .checked_add(weight)
+        .ok_or(VotingError::VoteCountOverflow)?;
+
+    Ok(())
+}
+
+/// Takes back `weight` handed to the delegate `from` by [`give_weight`].
+fn take_weight(
+    program_id: &Pubkey,
+    system_account: &AccountInfo,
+    system: &mut SimpleVotingSystem,
+    from: &mut Voter,
+    proposal_account: Option<&AccountInfo>,
+    weight: u64,
+) -> ProgramResult {
+    // The delegate carried the weight since it was handed to them
+    let missing_weight = || {
+        msg!("The delegate holds less weight than was delegated");
+        ProgramError::InvalidAccountData
+    };
+    from.weight = from.weight.checked_sub(weight).ok_or_else(missing_weight)?;
+
+    if from.voted && from.vote == ABSTAIN {
+        system.abstain_weight = system
+            .abstain_weight
+            .checked_sub(weight)
+            .ok_or_else(missing_weight)?;
+    } else if from.voted {
+        let proposal_account = proposal_account.ok_or(ProgramError::NotEnoughAccountKeys)?;
+        remove_votes(
+            program_id,
+            system_account,
+            system,
+            proposal_account,
+            from.vote,
+            VoteCount::from(weight),
+        )?;
+    }
+
+    Ok(())
+}
+
+fn change_vote(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let voter_account = next_account_info(accounts_iter)?;
+    let old_proposal_account = next_account_info(accounts_iter)?;
+    let new_proposal_account = next_account_info(accounts_iter)?;
+    let system_account = next_account_info(accounts_iter)?;
+
+    // Parse the old and new proposal index from the instruction data
+    let old_index = u32::from_le_bytes(instruction_data[1..5].try_into().unwrap());
+    let new_index = u32::from_le_bytes(instruction_data[5..9].try_into().unwrap());
+
+    // Check if the voter signed the transaction
+    if !voter_account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    // The voter record is borrowed mutably alongside the proposals, so it has
+    // to live in its own account; all of them have to be owned by this program
+    if voter_account.key == old_proposal_account.key
+        || voter_account.key == new_proposal_account.key
+        || voter_account.key == system_account.key
+    {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if voter_account.owner != program_id
+        || old_proposal_account.owner != program_id
+        || new_proposal_account.owner != program_id
+    {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Check if voting is open
+    let mut system = load_state(program_id, system_account)?;
+    system.check_voting_open()?;
+
+    let voter_data = &mut voter_account.data.borrow_mut();
+    if voter_data.len() < Voter::LEN {
+        return Err(VotingError::AccountTooSmall.into());
+    }
+    let mut voter = Voter::from_bytes(voter_data)?;
+
+    if voter.ballot != *system_account.key {
+        return Err(VotingError::NoRightToVote.into());
+    }
+    if !voter.voted {
+        return Err(VotingError::NotVoted.into());
+    }
+    if voter.has_delegated() {
+        msg!("A delegated vote is cast by the delegate");
+        return Err(ProgramError::InvalidArgument);
+    }
+    if voter.cast_approvals() {
+        msg!("An approval ballot cannot be changed");
+        return Err(ProgramError::InvalidArgument);
+    }
+    if voter.vote != old_index {
+        msg!(
+            "The voter voted for proposal {}, not {}",
+            voter.vote,
+            old_index
+        );
+        return Err(ProgramError::InvalidArgument);
+    }
+    if old_index == new_index {
+        msg!("Vote unchanged");
+        return Ok(());
+    }
+    if old_index == ABSTAIN || new_index == ABSTAIN {
+        msg!("An abstention cannot be changed");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let weight = VoteCount::from(voter.weight);
+    let in_state = |account: &AccountInfo| account.key == system_account.key;
+    if in_state(old_proposal_account) || in_state(new_proposal_account) {
+        // Both proposals are held by the state itself
+        if !in_state(old_proposal_account) || !in_state(new_proposal_account) {
+            msg!("The proposals are held by the state account");
+            return Err(ProgramError::InvalidArgument);
+        }
+        let proposal = |index: u32| {
+            system
+                .proposals
+                .get(index as usize)
+                .cloned()
+                .ok_or(VotingError::InvalidProposalIndex)
+        };
+        let mut old_proposal = proposal(old_index)?;
+        let mut new_proposal = proposal(new_index)?;
+
+        move_votes(&mut old_proposal, &mut new_proposal, new_index, weight)?;
+        system.proposals[old_index as usize] = old_proposal;
+        system.proposals[new_index as usize] = new_proposal;
+    } else if old_proposal_account.key == new_proposal_account.key {
+        // Both records live in the same proposal list
+        let proposal_data = &mut old_proposal_account.data.borrow_mut();
+        let old_offset = proposal_record_offset(
+            program_id,
+            system_account.key,
+            old_proposal_account,
+            proposal_data,
+            old_index,
+        )
+        .map_err(|_| VotingError::InvalidProposalIndex)?;
+        let new_offset = proposal_record_offset(
+            program_id,
+            system_account.key,
+            new_proposal_account,
+            proposal_data,
+            new_index,
+        )
+        .map_err(|_| VotingError::InvalidProposalIndex)?;
+        let mut old_proposal =
+            Proposal::from_bytes(&proposal_data[old_offset..old_offset + Proposal::LEN])?;
+        let mut new_proposal =
+            Proposal::from_bytes(&proposal_data[new_offset..new_offset + Proposal::LEN])?;
+
+        move_votes(&mut old_proposal, &mut new_proposal, new_index, weight)?;
+        proposal_data[old_offset..old_offset + Proposal::LEN]
+            .copy_from_slice(&old_proposal.to_bytes());
+        proposal_data[new_offset..new_offset + Proposal::LEN]
+            .copy_from_slice(&new_proposal.to_bytes());
+    } else {
+        let old_data = &mut old_proposal_account.data.borrow_mut();
+        let new_data = &mut new_proposal_account.data.borrow_mut();
+        let old_offset = proposal_record_offset(
+            program_id,
+            system_account.key,
+            old_proposal_account,
+            old_data,
+            old_index,
+        )
+        .map_err(|_| VotingError::InvalidProposalIndex)?;
+        let new_offset = proposal_record_offset(
+            program_id,
+            system_account.key,
+            new_proposal_account,
+            new_data,
+            new_index,
+        )
+        .map_err(|_| VotingError::InvalidProposalIndex)?;
+        let mut old_proposal =
+            Proposal::from_bytes(&old_data[old_offset..old_offset + Proposal::LEN])?;
+        let mut new_proposal =
+            Proposal::from_bytes(&new_data[new_offset..new_offset + Proposal::LEN])?;
+
+        move_votes(&mut old_proposal, &mut new_proposal, new_index, weight)?;
+        old_data[old_offset..old_offset + Proposal::LEN].copy_from_slice(&old_proposal.to_bytes());
+        new_data[new_offset..new_offset + Proposal::LEN].copy_from_slice(&new_proposal.to_bytes());
+    }
+
+    voter.vote = new_index;
+    voter_data[..Voter::LEN].copy_from_slice(&voter.to_bytes());
+    sync_roll(system_account, &mut system, voter_account.key, &voter)?;
+
+    msg!("Vote changed from proposal {} to {}", old_index, new_index);
+    Ok(())
+}
+
+/// Moves `weight` votes from `old` to the proposal at `new_index`, bumping
+/// both nonces.
+fn move_votes(
+    old: &mut Proposal,
+    new: &mut Proposal,
+    new_index: u32,
+    weight: VoteCount,
+) -> ProgramResult {
+    // The old proposal counted this voter's weight, so it cannot hold less
+    old.vote_count = old.vote_count.checked_sub(weight).ok_or_else(|| {
+        msg!("Proposal holds fewer votes than the voter's weight");
+        ProgramError::InvalidAccountData
+    })?;
+    new.vote_count = new
+        .vote_count
+        .checked_add(weight)
+        .ok_or(VotingError::VoteCountOverflow)?;
+    Proposal::check_expected_votes(
+        new_index,
+        new.vote_count,
+        new.max_expected_votes,
+        new.reject_unexpected,
+    )?;
+
+    old.nonce += 1;
+    new.nonce += 1;
+
+    Ok(())
+}
+
+/// Reads the state account for an instruction only its chairperson may run.
+/// Fails unless `signer_account` is the chairperson stored in the state
+/// account and signed the transaction: `MissingRequiredSignature` for a
+/// missing signature, `NotChairperson` for any other signer.
+pub fn require_chairperson(
+    program_id: &Pubkey,
+    system_account: &AccountInfo,
+    signer_account: &AccountInfo,
+) -> Result<SimpleVotingSystem, ProgramError> {
+    let system = load_state(program_id, system_account)?;
+    system.check_chairperson(signer_account)?;
+
+    Ok(system)
+}
+
+/// Reads the ballot's state account. Only an account owned by this program can
+/// hold a state the program wrote, so any other account is rejected.
+fn load_state(
+    program_id: &Pubkey,
+    system_account: &AccountInfo,
+) -> Result<SimpleVotingSystem, ProgramError> {
+    if system_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    SimpleVotingSystem::deserialize(&system_account.data.borrow())
+}
+
+/// Mirrors a voter record in the ballot's roll and writes the state back. A
+/// voter the roll does not list yet adds an entry, which the state account
+/// has to have room for.
+fn sync_roll(
+    system_account: &AccountInfo,
+    system: &mut SimpleVotingSystem,
+    voter_key: &Pubkey,
+    voter: &Voter,
+) -> ProgramResult {
+    system.record_voter(voter_key, voter);
+    system.write_into(&mut system_account.try_borrow_mut_data()?)
+}
+
+/// Checks that an account is rent-exempt, so the state written to it cannot be
+/// lost when the runtime collects the account.
+fn check_rent_exempt(rent: &Rent, account: &AccountInfo) -> ProgramResult {
+    if !rent.is_exempt(account.lamports(), account.data_len()) {
+        msg!("Account {} is not rent-exempt", account.key);
+        return Err(VotingError::NotRentExempt.into());
+    }
+
+    Ok(())
+}
+
+fn give_right_to_vote(program_id: &Pubkey, accounts: &[AccountInfo], weight: u64) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let chairperson_account = next_account_info(accounts_iter)?;
+    let system_account = next_account_info(accounts_iter)?;
+    let voter_account = next_account_info(accounts_iter)?;
+
+    // Only accounts owned by this program may have voting state written, and
+    // the voter record is written alongside the roll in the state account
+    if voter_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if voter_account.key == system_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Check if the sender is the chairperson
+    let mut system = require_chairperson(program_id, system_account, chairperson_account)?;
+
+    system.check_weights_unfrozen()?;
+
+    // A registration has to survive until the voter casts their vote
+    check_rent_exempt(&Rent::get()?, voter_account)?;
+
+    // Check if the voter has already voted
+    let mut voter_data = voter_account.data.borrow_mut();
+    if voter_data.len() < Voter::LEN {
+        return Err(VotingError::AccountTooSmall.into());
+    }
+    let mut voter = Voter::from_bytes(&voter_data)?;
+
+    // A voter record belongs to the ballot that first granted it a right
+    if voter.ballot != Pubkey::default() && voter.ballot != *system_account.key {
+        msg!("The voter is registered on another ballot");
+        return Err(VotingError::AlreadyGranted.into());
+    }
+    if voter.voted {
+        return Err(VotingError::AlreadyVoted.into());
+    }
+
+    // A voter who already has the right to vote keeps their record as it is,
+    // so repeating a grant changes nothing. A grant of a different weight is
+    // refused rather than overwriting the one already held.
+    if voter.weight == weight {
+        msg!("Voter already has the right to vote");
+        return Ok(());
+    }
+    if voter.weight != 0 {
+        return Err(VotingError::AlreadyGranted.into());
+    }
+
+    // Give the voter the right to vote
+    voter.weight = weight;
+    voter.ballot = *system_account.key;
+    sync_roll(system_account, &mut system, voter_account.key, &voter)?;
+    voter_data[..Voter::LEN].copy_from_slice(&voter.to_bytes());
+
+    Ok(())
+}
+
+fn batch_revoke(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let chairperson_account = next_account_info(accounts_iter)?;
+    let system_account = next_account_info(accounts_iter)?;
+    let voter_accounts = accounts_iter.as_slice();
+
+    // Check if the sender is the chairperson
+    let mut system = require_chairperson(program_id, system_account, chairperson_account)?;
+
+    system.check_weights_unfrozen()?;
+
+    // A vote already cast cannot be taken back, so those voters are skipped
+    let mut skipped = Vec::new();
+    for (position, voter_account) in voter_accounts.iter().enumerate() {
+        if voter_account.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if voter_account.key == system_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut voter_data = voter_account.data.borrow_mut();
+        let mut voter = Voter::from_bytes(&voter_data)?;
+
+        // Only this ballot's own voters can be revoked
+        if voter.ballot != *system_account.key {
+            msg!(
+                "Voter {} is registered on another ballot",
+                voter_account.key
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        if voter.voted {
+            skipped.extend_from_slice(&(position as u32).to_le_bytes());
+            continue;
+        }
+
+        voter.weight = 0;
+        voter_data[..Voter::LEN].copy_from_slice(&voter.to_bytes());
+        system.record_voter(voter_account.key, &voter);
+    }
+    system.write_into(&mut system_account.try_borrow_mut_data()?)?;
+    set_return_data(&skipped);
+
+    Ok(())
+}
+
+/// Drops revoked voters, whose weight is zero, from the voter roll. The state
+/// shrinks by one roll entry per voter removed; the freed bytes at the end of
+/// the account are zeroed.
+fn compact_roll(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let chairperson_account = next_account_info(accounts_iter)?;
+    let system_account = next_account_info(accounts_iter)?;
+
+    // Check if the sender is the chairperson
+    let mut system = require_chairperson(program_id, system_account, chairperson_account)?;
+
+    let old_len = system.to_bytes().len();
+    system.voters.retain(|(_, voter)| voter.weight != 0);
+    let new_len = system.to_bytes().len();
+
+    let system_data = &mut system_account.try_borrow_mut_data()?;
+    system.write_into(system_data)?;
+    let old_len = old_len.min(system_data.len());
+    system_data[new_len..old_len].fill(0);
+
+    Ok(())
+}
+
+/// Casts the voter's weight for a proposal. `expected_nonce`, if given, is the
+/// proposal nonce the client last observed.
+fn vote(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    proposal_index: u32,
+    expected_nonce: Option<u64>,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let voter_account = next_account_info(accounts_iter)?;
+    let proposal_account = next_account_info(accounts_iter)?;
+    let system_account = next_account_info(accounts_iter)?;
+    // An optional velocity account records the slot of each vote
+    let velocity_account = accounts_iter.next();
+
+    // Check if the voter signed the transaction
+    if !voter_account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if let Some(velocity_account) = velocity_account {
+        check_velocity_account(
+            program_id,
+            system_account,
+            velocity_account,
+            &[voter_account, proposal_account],
+        )?;
+    }
+
+    if proposal_index == ABSTAIN {
+        return abstain(program_id, voter_account, system_account);
+    }
+
+    // The records and the state are all borrowed mutably below, so they have
+    // to live in distinct accounts owned by this program, unless the proposal
+    // is one the state holds itself
+    if voter_account.key == proposal_account.key || voter_account.key == system_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if proposal_account.key == system_account.key {
+        return vote_in_state(
+            program_id,
+            voter_account,
+            system_account,
+            velocity_account,
+            proposal_index,
+            expected_nonce,
+        );
+    }
+    if voter_account.owner != program_id || proposal_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let rent = Rent::get()?;
+    check_rent_exempt(&rent, voter_account)?;
+    check_rent_exempt(&rent, proposal_account)?;
+
+    // Retrieve the voter and proposal data. This is the hottest instruction,
+    // so only the fields it needs are decoded and the records are patched in
+    // place rather than rebuilt.
+    let voter_data = &mut voter_account.data.borrow_mut();
+    let proposal_data = &mut proposal_account.data.borrow_mut();
+    if voter_data.len() < Voter::LEN {
+        return Err(VotingError::AccountTooSmall.into());
+    }
+    let weight = u64::from_le_bytes(voter_data[..Voter::VOTED_OFFSET].try_into().unwrap());
+    let voted = voter_data[Voter::VOTED_OFFSET] != 0;
+    let ballot = read_pubkey(voter_data, Voter::BALLOT_OFFSET)?;
+
+    // The index is recorded on the voter, so it has to resolve to a proposal
+    let proposal_offset = proposal_record_offset(
+        program_id,
+        system_account.key,
+        proposal_account,
+        proposal_data,
+        proposal_index,
+    )
+    .map_err(|_| VotingError::InvalidProposalIndex)?;
+    let proposal_record = &mut proposal_data[proposal_offset..proposal_offset + Proposal::LEN];
+    let vote_count = VoteCount::from_le_bytes(
+        proposal_record[Proposal::COUNT_OFFSET..Proposal::NONCE_OFFSET]
+            .try_into()
+            .unwrap(),
+    );
+    let nonce = u64::from_le_bytes(
+        proposal_record[Proposal::NONCE_OFFSET..Proposal::MAX_EXPECTED_OFFSET]
+            .try_into()
+            .unwrap(),
+    );
+    let max_expected_votes = VoteCount::from_le_bytes(
+        proposal_record[Proposal::MAX_EXPECTED_OFFSET..Proposal::REJECT_OFFSET]
+            .try_into()
+            .unwrap(),
+    );
+    let reject_unexpected = proposal_record[Proposal::REJECT_OFFSET] != 0;
+
+    // Check if the voter has the right to vote on this ballot
+    if weight == 0 || ballot != *system_account.key {
+        return Err(VotingError::NoRightToVote.into());
+    }
+
+    // Check if the voter carries enough weight to take part
+    let mut system = load_state(program_id, system_account)?;
+    if weight < u64::from(system.min_participation_weight) {
+        return Err(VotingError::WeightBelowMinimum.into());
+    }
+
+    // Check if voting is open
+    system.check_voting_open()?;
+
+    // Check if the voter has already voted
+    if voted {
+        return Err(VotingError::AlreadyVoted.into());
+    }
+
+    // Reject a vote built from a stale read of the proposal
+    if let Some(expected_nonce) = expected_nonce {
+        if nonce != expected_nonce {
+            msg!(
+                "Stale proposal nonce: expected {}, found {}",
+                expected_nonce,
+                nonce
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+    }
+
+    let vote_count = vote_count
+        .checked_add(VoteCount::from(weight))
+        .ok_or(VotingError::VoteCountOverflow)?;
+    Proposal::check_expected_votes(
+        proposal_index,
+        vote_count,
+        max_expected_votes,
+        reject_unexpected,
+    )?;
+
+    // Update the voter and proposal data in place
+    voter_data[Voter::VOTED_OFFSET] = true as u8;
+    voter_data[Voter::VOTE_OFFSET..Voter::DELEGATE_OFFSET]
+        .copy_from_slice(&proposal_index.to_le_bytes());
+    proposal_record[Proposal::COUNT_OFFSET..Proposal::NONCE_OFFSET]
+        .copy_from_slice(&vote_count.to_le_bytes());
+    proposal_record[Proposal::NONCE_OFFSET..Proposal::MAX_EXPECTED_OFFSET]
+        .copy_from_slice(&(nonce + 1).to_le_bytes());
+    sync_roll(
+        system_account,
+        &mut system,
+        voter_account.key,
+        &Voter::from_bytes(voter_data)?,
+    )?;
+
+    if let Some(velocity_account) = velocity_account {
+        record_vote_slot(
+            &mut velocity_account.try_borrow_mut_data()?,
+            Clock::get()?.slot,
+        )?;
+    }
+
+    // Hand the voter a receipt they can later present to verify_receipt
+    let mut receipt = Vec::new();
+    receipt.extend_from_slice(&proposal_index.to_le_bytes());
+    receipt.extend_from_slice(
+        receipt_mac(program_id, voter_account.key, &voter_data[..Voter::LEN]).as_ref(),
+    );
+    set_return_data(&receipt);
+
+    Ok(())
+}
+
+/// Casts the voter's weight for one of the proposals held by the state account
+/// itself. Unlike the proposal accounts, the state is decoded and rewritten in
+/// full.
+fn vote_in_state(
+    program_id: &Pubkey,
+    voter_account: &AccountInfo,
+    system_account: &AccountInfo,
+    velocity_account: Option<&AccountInfo>,
+    proposal_index: u32,
+    expected_nonce: Option<u64>,
+) -> ProgramResult {
+    if voter_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    check_rent_exempt(&Rent::get()?, voter_account)?;
+
+    let voter_data = &mut voter_account.data.borrow_mut();
+    if voter_data.len() < Voter::LEN {
+        return Err(VotingError::AccountTooSmall.into());
+    }
+    let mut voter = Voter::from_bytes(voter_data)?;
+
+    // Check if the voter has the right to vote on this ballot
+    if !voter.may_vote_on(system_account.key) {
+        return Err(VotingError::NoRightToVote.into());
+    }
+
+    // Check if the voter carries enough weight to take part
+    let mut system = load_state(program_id, system_account)?;
+    if voter.weight < u64::from(system.min_participation_weight) {
+        return Err(VotingError::WeightBelowMinimum.into());
+    }
+
+    // Check if voting is open
+    system.check_voting_open()?;
+
+    // Check if the voter has already voted
+    if voter.voted {
+        return Err(VotingError::AlreadyVoted.into());
+    }
+
+    // Reject a vote built from a stale read of the proposal
+    let nonce = system
+        .proposals
+        .get(proposal_index as usize)
+        .ok_or(VotingError::InvalidProposalIndex)?
+        .nonce;
+    if let Some(expected_nonce) = expected_nonce {
+        if nonce != expected_nonce {
+            msg!(
+                "Stale proposal nonce: expected {}, found {}",
+                expected_nonce,
+                nonce
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+    }
+
+    add_votes(
+        program_id,
+        system_account,
+        &mut system,
+        system_account,
+        proposal_index,
+        VoteCount::from(voter.weight),
+    )?;
+
+    voter.voted = true;
+    voter.vote = proposal_index;
+    voter_data[..Voter::LEN].copy_from_slice(&voter.to_bytes());
+    sync_roll(system_account, &mut system, voter_account.key, &voter)?;
+
+    if let Some(velocity_account) = velocity_account {
+        record_vote_slot(
+            &mut velocity_account.try_borrow_mut_data()?,
+            Clock::get()?.slot,
+        )?;
+    }
+
+    let mut receipt = Vec::new();
+    receipt.extend_from_slice(&proposal_index.to_le_bytes());
+    receipt.extend_from_slice(
+        receipt_mac(program_id, voter_account.key, &voter_data[..Voter::LEN]).as_ref(),
+    );
+    set_return_data(&receipt);
+
+    Ok(())
+}
+
+/// Records an abstention: the voter is marked as having voted, and their
+/// weight counts toward the quorum without backing any proposal.
+fn abstain(
+    program_id: &Pubkey,
+    voter_account: &AccountInfo,
+    system_account: &AccountInfo,
+) -> ProgramResult {
+    // Both records are borrowed mutably below, so they have to live in
+    // distinct accounts
+    if voter_account.key == system_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if voter_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    check_rent_exempt(&Rent::get()?, voter_account)?;
+
+    let voter_data = &mut voter_account.data.borrow_mut();
+    if voter_data.len() < Voter::LEN {
+        return Err(VotingError::AccountTooSmall.into());
+    }
+    let mut voter = Voter::from_bytes(voter_data)?;
+
+    // Check if the voter has the right to vote on this ballot
+    if !voter.may_vote_on(system_account.key) {
+        return Err(VotingError::NoRightToVote.into());
+    }
+
+    // Check if the voter carries enough weight to take part
+    let mut system = load_state(program_id, system_account)?;
+    if voter.weight < u64::from(system.min_participation_weight) {
+        return Err(VotingError::WeightBelowMinimum.into());
+    }
+
+    // Check if voting is open
+    system.check_voting_open()?;
+
+    // Check if the voter has already voted
+    if voter.voted {
+        return Err(VotingError::AlreadyVoted.into());
+    }
+
+    system.abstain_weight = system
+        .abstain_weight
+        .checked_add(voter.weight)
+        .ok_or(VotingError::VoteCountOverflow)?;
+
+    voter.voted = true;
+    voter.vote = ABSTAIN;
+    voter_data[..Voter::LEN].copy_from_slice(&voter.to_bytes());
+    sync_roll(system_account, &mut system, voter_account.key, &voter)?;
+
+    // An abstention gets a receipt like any other vote
+    let mut receipt = Vec::new();
+    receipt.extend_from_slice(&ABSTAIN.to_le_bytes());
+    receipt.extend_from_slice(
+        receipt_mac(program_id, voter_account.key, &voter_data[..Voter::LEN]).as_ref(),
+    );
+    set_return_data(&receipt);
+
+    msg!("Abstention recorded");
+    Ok(())
+}
+
+/// Address of the SPL Token program.
+pub const TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+
+// Layout of an SPL Token account: the fields read here, and the size of the
+// whole record
+const TOKEN_MINT_OFFSET: usize = 0;
+const TOKEN_OWNER_OFFSET: usize = 32;
+const TOKEN_AMOUNT_OFFSET: usize = 64;
+const TOKEN_STATE_OFFSET: usize = 108;
+const TOKEN_ACCOUNT_LEN: usize = 165;
+
+/// Reads the balance of an SPL Token account, checking that it is initialized
+/// and holds `mint` on behalf of `owner`.
+pub fn token_balance(data: &[u8], mint: &Pubkey, owner: &Pubkey) -> Result<u64, ProgramError> {
+    if data.len() != TOKEN_ACCOUNT_LEN {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if data[TOKEN_STATE_OFFSET] == 0 {
+        return Err(ProgramError::UninitializedAccount);
+    }
+    if read_pubkey(data, TOKEN_MINT_OFFSET)? != *mint {
+        msg!("The token account does not hold the governance token");
+        return Err(ProgramError::InvalidArgument);
+    }
+    if read_pubkey(data, TOKEN_OWNER_OFFSET)? != *owner {
+        msg!("The token account does not belong to the signer");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    Ok(u64::from_le_bytes(
+        data[TOKEN_AMOUNT_OFFSET..TOKEN_AMOUNT_OFFSET + 8]
+            .try_into()
+            .unwrap(),
+    ))
+}
+
+/// Size of a token voter record: a voter record followed by the token holder
+/// it belongs to.
+pub const TOKEN_VOTER_LEN: usize = Voter::LEN + 32;
+
+/// Address of the voter record of the token holder `owner` on the ballot
+/// whose state account is `system_key`.
+fn token_voter_address(program_id: &Pubkey, system_key: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"token_voter", system_key.as_ref(), owner.as_ref()],
+        program_id,
+    )
+}
+
+/// Casts a vote weighted by the governance token balance held at vote time.
+/// The holder's voter record is marked as voted and keeps the balance as its
+/// weight, so they cannot vote again once the tokens have been moved. Tokens
+/// moved to another holder who has not voted yet count again; ballots that
+/// must rule this out need a snapshot of the balances instead.
+fn vote_with_token(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let voter_account = next_account_info(accounts_iter)?;
+    let token_account = next_account_info(accounts_iter)?;
+    let owner_account = next_account_info(accounts_iter)?;
+    let proposal_account = next_account_info(accounts_iter)?;
+    let system_account = next_account_info(accounts_iter)?;
+    // The system program is only needed to create the voter record
+    let system_program_account = accounts_iter.next();
+
+    // Parse the proposal index from the instruction data
+    let proposal_index = u32::from_le_bytes(instruction_data[1..5].try_into().unwrap());
+
+    // Check if the token holder signed the transaction
+    if !owner_account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // The holder votes through the one record derived for them on this
+    // ballot, so a fresh record cannot be used to vote twice
+    let (expected_key, bump) =
+        token_voter_address(program_id, system_account.key, owner_account.key);
+    if *voter_account.key != expected_key {
+        msg!("The voter record is not the token holder's record on this ballot");
+        return Err(ProgramError::InvalidArgument);
+    }
+    if voter_account.data_is_empty() && *voter_account.owner == system_program::id() {
+        let system_program_account =
+            system_program_account.ok_or(ProgramError::NotEnoughAccountKeys)?;
+        invoke_signed(
+            &system_instruction::create_account(
+                owner_account.key,
+                voter_account.key,
+                Rent::get()?.minimum_balance(TOKEN_VOTER_LEN),
+                TOKEN_VOTER_LEN as u64,
+                program_id,
+            ),
+            &[
+                owner_account.clone(),
+                voter_account.clone(),
+                system_program_account.clone(),
+            ],
+            &[&[
+                b"token_voter",
+                system_account.key.as_ref(),
+                owner_account.key.as_ref(),
+                &[bump],
+            ]],
+        )?;
+    }
+    // The voter record is borrowed mutably alongside the proposal and the
+    // state, which may hold the proposal itself
+    if voter_account.key == proposal_account.key || voter_account.key == system_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if voter_account.owner != program_id || proposal_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if *token_account.owner != TOKEN_PROGRAM_ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let rent = Rent::get()?;
+    check_rent_exempt(&rent, voter_account)?;
+    check_rent_exempt(&rent, proposal_account)?;
+
+    let mut system = load_state(program_id, system_account)?;
+    if system.gov_mint == Pubkey::default() {
+        msg!("Token voting is not enabled for this ballot");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Check if voting is open
+    system.check_voting_open()?;
+
+    // The voting weight is the balance held right now
+    let weight = token_balance(
+        &token_account.data.borrow(),
+        &system.gov_mint,
+        owner_account.key,
+    )?;
+    if weight == 0 {
+        return Err(VotingError::NoRightToVote.into());
+    }
+    if weight < u64::from(system.min_participation_weight) {
+        return Err(VotingError::WeightBelowMinimum.into());
+    }
+
+    let voter_data = &mut voter_account.data.borrow_mut();
+    if voter_data.len() < TOKEN_VOTER_LEN {
+        return Err(VotingError::AccountTooSmall.into());
+    }
+    let mut voter = Voter::from_bytes(voter_data)?;
+
+    // A record already used on another ballot or by another holder cannot
+    // vote for this one
+    if voter.ballot != Pubkey::default() && voter.ballot != *system_account.key {
+        return Err(VotingError::NoRightToVote.into());
+    }
+    let holder = read_pubkey(voter_data, Voter::LEN)?;
+    if holder != Pubkey::default() && holder != *owner_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    // Check if the voter has already voted
+    if voter.voted {
+        return Err(VotingError::AlreadyVoted.into());
+    }
+
+    add_votes(
+        program_id,
+        system_account,
+        &mut system,
+        proposal_account,
+        proposal_index,
+        VoteCount::from(weight),
+    )?;
+
+    // Record the vote on the voter
+    voter.weight = weight;
+    voter.voted = true;
+    voter.ballot = *system_account.key;
+    voter.vote = proposal_index;
+    voter_data[..Voter::LEN].copy_from_slice(&voter.to_bytes());
+    voter_data[Voter::LEN..TOKEN_VOTER_LEN].copy_from_slice(owner_account.key.as_ref());
+    sync_roll(system_account, &mut system, voter_account.key, &voter)?;
+
+    msg!("Voted with a token balance of {}", weight);
+    Ok(())
+}
+
+/// Number of vote slots kept by a velocity account, laid out as the `u32`
+/// position of the next write followed by a ring of `u64` slots.
+const RECENT_VOTE_SLOTS: usize = 64;
+const VELOCITY_ACCOUNT_LEN: usize = 4 + RECENT_VOTE_SLOTS * 8;
+
+/// Address of the velocity account of the ballot whose state account is
+/// `system_key`.
+fn velocity_address(program_id: &Pubkey, system_key: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"velocity", system_key.as_ref()], program_id)
+}
+
+/// Fails unless `velocity_account` is the velocity account of the ballot whose
+/// state account is `system_account`. It is written alongside the state and
+/// the vote's `other_accounts`, so it cannot be any of them either.
+fn check_velocity_account(
+    program_id: &Pubkey,
+    system_account: &AccountInfo,
+    velocity_account: &AccountInfo,
+    other_accounts: &[&AccountInfo],
+) -> ProgramResult {
+    if velocity_account.key == system_account.key
+        || other_accounts
+            .iter()
+            .any(|account| account.key == velocity_account.key)
+    {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if *velocity_account.key != velocity_address(program_id, system_account.key).0 {
+        msg!("The velocity account is not this ballot's velocity account");
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if velocity_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    Ok(())
+}
+
+/// Records the slot of a vote in a velocity account, overwriting the oldest
+/// entry once the ring is full.
+fn record_vote_slot(data: &mut [u8], slot: u64) -> ProgramResult {
+    if data.len() < VELOCITY_ACCOUNT_LEN {
+        return Err(ProgramError::AccountDataTooSmall);
+    }
+
+    let next = u32::from_le_bytes(data[..4].try_into().unwrap()) as usize % RECENT_VOTE_SLOTS;
+    let offset = 4 + next * 8;
+    data[offset..offset + 8].copy_from_slice(&slot.to_le_bytes());
+    data[..4].copy_from_slice(&(((next + 1) % RECENT_VOTE_SLOTS) as u32).to_le_bytes());
+
+    Ok(())
+}
+
+/// Largest number of proposals a ballot may grow to.
+const MAX_PROPOSALS: usize = 256;
+
+/// Votes for the proposal with the given name, appending it as a new proposal
+/// account if none of the existing proposals carries that name.
+///
+/// Accounts: the voter, the state account, a signer paying for the new
+/// proposal account, the system program, the account a new proposal would be
+/// created at (the address derived from the next index), then the existing
+/// proposal accounts in index order.
+fn write_in_vote(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let voter_account = next_account_info(accounts_iter)?;
+    let system_account = next_account_info(accounts_iter)?;
+    let payer_account = next_account_info(accounts_iter)?;
+    let system_program_account = next_account_info(accounts_iter)?;
+    let write_in_account = next_account_info(accounts_iter)?;
+    let proposal_accounts = accounts_iter.as_slice();
+
+    // Parse and validate the name from the instruction data
+    let (name, desc_hash) = split_name(&instruction_data[1..])?;
+    let desc_hash = desc_hash_field(desc_hash)?;
+    validate_name(name)?;
+
+    // Check if the voter signed the transaction
+    if !voter_account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    // The voter record is written alongside the roll in the state account
+    if voter_account.key == system_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if voter_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let voter_data = &mut voter_account.data.borrow_mut();
+    let mut voter = Voter::from_bytes(voter_data)?;
+
+    // Check if the voter has the right to vote on this ballot
+    if !voter.may_vote_on(system_account.key) {
+        return Err(VotingError::NoRightToVote.into());
+    }
+
+    // Check if the voter carries enough weight to take part
+    let mut system = load_state(program_id, system_account)?;
+    if voter.weight < u64::from(system.min_participation_weight) {
+        return Err(VotingError::WeightBelowMinimum.into());
+    }
+
+    // Check if voting is open
+    system.check_voting_open()?;
+
+    // Check if the voter has already voted
+    if voter.voted {
+        return Err(VotingError::AlreadyVoted.into());
+    }
+
+    let proposals = load_proposal_accounts(program_id, system_account.key, proposal_accounts)?;
+    let weight = VoteCount::from(voter.weight);
+
+    let index = match proposals.iter().position(|proposal| proposal.name == name) {
+        Some(index) => {
+            let mut proposal = Proposal::from_bytes(&proposal_accounts[index].data.borrow())?;
+            proposal.vote_count = proposal
+                .vote_count
+                .checked_add(weight)
+                .ok_or(VotingError::VoteCountOverflow)?;
+            Proposal::check_expected_votes(
+                index as u32,
+                proposal.vote_count,
+                proposal.max_expected_votes,
+                proposal.reject_unexpected,
+            )?;
+            proposal.nonce += 1;
+            proposal_accounts[index]
+                .data
+                .borrow_mut()
+                .copy_from_slice(&proposal.to_bytes());
+
+            index as u32
+        }
+        None => {
+            if proposals.len() >= MAX_PROPOSALS {
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            let index = proposals.len() as u32;
+            let (expected_key, bump) = proposal_address(program_id, system_account.key, index);
+            if *write_in_account.key != expected_key {
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            invoke_signed(
+                &system_instruction::create_account(
+                    payer_account.key,
+                    write_in_account.key,
+                    Rent::get()?.minimum_balance(Proposal::LEN),
+                    Proposal::LEN as u64,
+                    program_id,
+                ),
+                &[
+                    payer_account.clone(),
+                    write_in_account.clone(),
+                    system_program_account.clone(),
+                ],
+                &[&[
+                    b"proposal",
+                    system_account.key.as_ref(),
+                    &index.to_le_bytes(),
+                    &[bump],
+                ]],
+            )?;
+
+            let proposal = Proposal {
+                name: name.to_vec(),
+                vote_count: weight,
+                nonce: 1,
+                max_expected_votes: 0,
+                reject_unexpected: false,
+                desc_hash,
+            };
+            write_in_account
+                .data
+                .borrow_mut()
+                .copy_from_slice(&proposal.to_bytes());
+
+            msg!("Write-in proposal {} created", index);
+            index
+        }
+    };
+
+    // Record the vote on the voter
+    voter.voted = true;
+    voter.vote = index;
+    voter_data[..Voter::LEN].copy_from_slice(&voter.to_bytes());
+    sync_roll(system_account, &mut system, voter_account.key, &voter)?;
+
+    Ok(())
+}
+
+/// Casts an approval ballot: the voter's weight is added to every proposal
+/// whose bit is set in the approval mask.
+///
+/// The voter records [`APPROVAL`] as their vote and the mask in `approvals`,
+/// which limits approval ballots to 32 proposals. An approval ballot cannot be
+/// changed, and no voter can delegate to its caster.
+fn approval_vote(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let voter_account = next_account_info(accounts_iter)?;
+    let system_account = next_account_info(accounts_iter)?;
+    let proposal_accounts = accounts_iter.as_slice();
+    let proposal_account = next_account_info(accounts_iter)?;
+
+    // Parse the approval mask from the instruction data
+    let approvals = instruction_data
+        .get(1..5)
+        .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+        .ok_or(ProgramError::InvalidInstructionData)?;
+
+    // Check if the voter signed the transaction
+    if !voter_account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    // The voter record, the proposals and the state are all borrowed mutably
+    // below, so they have to live in distinct accounts owned by this program.
+    // The state may only be passed as the sole proposal account, for the
+    // proposals it holds itself.
+    let in_state = proposal_account.key == system_account.key;
+    if voter_account.key == system_account.key
+        || proposal_accounts
+            .iter()
+            .any(|account| account.key == voter_account.key)
+        || (!in_state
+            && proposal_accounts
+                .iter()
+                .any(|account| account.key == system_account.key))
+        || (in_state && proposal_accounts.len() != 1)
+    {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if voter_account.owner != program_id
+        || proposal_accounts
+            .iter()
+            .any(|account| account.owner != program_id)
+    {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let voter_data = &mut voter_account.data.borrow_mut();
+    if voter_data.len() < Voter::LEN {
+        return Err(VotingError::AccountTooSmall.into());
+    }
+    let mut voter = Voter::from_bytes(voter_data)?;
+
+    // Check if the voter has the right to vote on this ballot
+    if !voter.may_vote_on(system_account.key) {
+        return Err(VotingError::NoRightToVote.into());
+    }
+
+    // Check if the voter carries enough weight to take part
+    let mut system = load_state(program_id, system_account)?;
+    if voter.weight < u64::from(system.min_participation_weight) {
+        return Err(VotingError::WeightBelowMinimum.into());
+    }
+
+    // Check if voting is open
+    system.check_voting_open()?;
+
+    // Check if the voter has already voted
+    if voter.voted {
+        return Err(VotingError::AlreadyVoted.into());
+    }
+
+    // An approval ballot has to approve something, and only existing proposals
+    let per_account = !in_state && proposal_account.data_len() == Proposal::LEN;
+    let proposal_count = if in_state {
+        system.proposals.len()
+    } else if per_account {
+        proposal_accounts.len()
+    } else {
+        proposal_account
+            .data_len()
+            .saturating_sub(PROPOSAL_LIST_TAG.len())
+            / Proposal::LEN
+    };
+    if approvals == 0 || (proposal_count < 32 && approvals >> proposal_count != 0) {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Proposals stored one per account are passed in index order
+    if per_account {
+        for (index, account) in proposal_accounts.iter().enumerate() {
+            let (expected_key, _) = proposal_address(program_id, system_account.key, index as u32);
+            if *account.key != expected_key {
+                return Err(ProgramError::InvalidArgument);
+            }
+        }
+    }
+
+    for index in (0..proposal_count.min(32)).filter(|index| approvals & (1 << index) != 0) {
+        let account = proposal_accounts
+            .get(index)
+            .filter(|_| per_account)
+            .unwrap_or(proposal_account);
+        add_votes(
+            program_id,
+            system_account,
+            &mut system,
+            account,
+            index as u32,
+            VoteCount::from(voter.weight),
+        )?;
+    }
+
+    // Record the approval mask on the voter
+    voter.voted = true;
+    voter.vote = APPROVAL;
+    voter.approvals = approvals;
+    voter_data[..Voter::LEN].copy_from_slice(&voter.to_bytes());
+    sync_roll(system_account, &mut system, voter_account.key, &voter)?;
+
+    Ok(())
+}
+
+/// Tag binding a vote receipt to this program, the voter account and the
+/// voter record it was issued for.
+///
+/// The program holds no secret, so this is a commitment rather than a keyed
+/// MAC: anyone can recompute it, but it only verifies while the voter account
+/// still records the vote it was issued for.
+fn receipt_mac(program_id: &Pubkey, voter_key: &Pubkey, voter_record: &[u8]) -> Hash {
+    hashv(&[
+        b"receipt",
+        program_id.as_ref(),
+        voter_key.as_ref(),
+        voter_record,
+    ])
+}
+
+fn verify_receipt(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let voter_account = next_account_info(accounts_iter)?;
+
+    // The receipt is the proposal index followed by the 32-byte tag
+    let receipt = instruction_data
+        .get(1..37)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    let proposal_index = u32::from_le_bytes(receipt[..4].try_into().unwrap());
+    let mac = &receipt[4..];
+
+    let voter_data = &voter_account.data.borrow();
+    let voter = Voter::from_bytes(voter_data)?;
+
+    let valid = voter.voted
+        && voter.vote == proposal_index
+        && receipt_mac(program_id, voter_account.key, &voter.to_bytes()).as_ref() == mac;
+
+    set_return_data(&[valid as u8]);
+
+    Ok(())
+}
+
+/// Address of the account holding the proposal at `index` of the ballot whose
+/// state account is `system_key`, when proposals are stored one per account
+/// rather than as a single list.
+fn proposal_address(program_id: &Pubkey, system_key: &Pubkey, index: u32) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"proposal", system_key.as_ref(), &index.to_le_bytes()],
+        program_id,
+    )
+}
+
+/// Offset of the proposal at `index` within `proposal_data`. A proposal either
+/// has an account of its own at the address derived from the state account
+/// `system_key` and its index, or is one record of a proposal list account.
+fn proposal_record_offset(
+    program_id: &Pubkey,
+    system_key: &Pubkey,
+    proposal_account: &AccountInfo,
+    proposal_data: &[u8],
+    index: u32,
+) -> Result<usize, ProgramError> {
+    if proposal_data.len() == Proposal::LEN {
+        let (expected_key, _) = proposal_address(program_id, system_key, index);
+        if *proposal_account.key != expected_key {
+            return Err(ProgramError::InvalidArgument);
+        }
+        return Ok(0);
+    }
+
+    if !proposal_data.starts_with(&PROPOSAL_LIST_TAG) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let offset = PROPOSAL_LIST_TAG.len() + index as usize * Proposal::LEN;
+    if offset + Proposal::LEN > proposal_data.len() {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    Ok(offset)
+}
+
+/// Adds `weight` votes to the proposal at `index` and bumps its nonce. The
+/// proposal is found as for [`update_proposal`].
+fn add_votes(
+    program_id: &Pubkey,
+    system_account: &AccountInfo,
+    system: &mut SimpleVotingSystem,
+    proposal_account: &AccountInfo,
+    index: u32,
+    weight: VoteCount,
+) -> ProgramResult {
+    update_proposal(
+        program_id,
+        system_account,
+        system,
+        proposal_account,
+        index,
+        |proposal| {
+            proposal.vote_count = proposal
+                .vote_count
+                .checked_add(weight)
+                .ok_or(VotingError::VoteCountOverflow)?;
+            Proposal::check_expected_votes(
+                index,
+                proposal.vote_count,
+                proposal.max_expected_votes,
+                proposal.reject_unexpected,
+            )?;
+            proposal.nonce += 1;
+
+            Ok(())
+        },
+    )
+}
+
+/// Takes back `weight` votes counted for the proposal at `index` and bumps its
+/// nonce. The proposal is found as for [`update_proposal`].
+fn remove_votes(
+    program_id: &Pubkey,
+    system_account: &AccountInfo,
+    system: &mut SimpleVotingSystem,
+    proposal_account: &AccountInfo,
+    index: u32,
+    weight: VoteCount,
+) -> ProgramResult {
+    update_proposal(
+        program_id,
+        system_account,
+        system,
+        proposal_account,
+        index,
+        |proposal| {
+            proposal.vote_count = proposal.vote_count.checked_sub(weight).ok_or_else(|| {
+                msg!("Proposal holds fewer votes than the weight taken back");
+                ProgramError::InvalidAccountData
+            })?;
+            proposal.nonce += 1;
+
+            Ok(())
+        },
+    )
+}
+
+/// Applies `update` to the proposal at `index`. The proposal is one of the
+/// state's own when `proposal_account` is the state account, updated in
+/// `system` for the caller to write back; otherwise it is a record of a
+/// proposal list or a per-proposal account.
+fn update_proposal(
+    program_id: &Pubkey,
+    system_account: &AccountInfo,
+    system: &mut SimpleVotingSystem,
+    proposal_account: &AccountInfo,
+    index: u32,
+    update: impl FnOnce(&mut Proposal) -> ProgramResult,
+) -> ProgramResult {
+    if proposal_account.key == system_account.key {
+        let proposal = system
+            .proposals
+            .get_mut(index as usize)
+            .ok_or(VotingError::InvalidProposalIndex)?;
+        return update(proposal);
+    }
+
+    if proposal_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let proposal_data = &mut proposal_account.data.borrow_mut();
+    let offset = proposal_record_offset(
+        program_id,
+        system_account.key,
+        proposal_account,
+        proposal_data,
+        index,
+    )
+    .map_err(|_| VotingError::InvalidProposalIndex)?;
+    let record = &mut proposal_data[offset..offset + Proposal::LEN];
+    let mut proposal = Proposal::from_bytes(record)?;
+    update(&mut proposal)?;
+    record.copy_from_slice(&proposal.to_bytes());
+
+    Ok(())
+}
+
+/// Reads proposals stored one per account, passed in index order, of the
+/// ballot whose state account is `system_key`.
+fn load_proposal_accounts(
+    program_id: &Pubkey,
+    system_key: &Pubkey,
+    accounts: &[AccountInfo],
+) -> Result<Vec<Proposal>, ProgramError> {
+    let mut proposals = Vec::new();
+
+    for (index, account) in accounts.iter().enumerate() {
+        let (expected_key, _) = proposal_address(program_id, system_key, index as u32);
+        if *account.key != expected_key {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        proposals.push(Proposal::from_bytes(&account.data.borrow())?);
+    }
+
+    Ok(proposals)
+}
+
+/// Whether `account` is a state account rather than proposals of their own: a
+/// proposal list opens with [`PROPOSAL_LIST_TAG`] and a per-proposal account is
+/// exactly one record long. Anything else is read as a state, which fails on a
+/// layout version other than the current one.
+fn holds_state(program_id: &Pubkey, account: &AccountInfo) -> bool {
+    account.owner == program_id
+        && account.data_len() != Proposal::LEN
+        && !account.data.borrow().starts_with(&PROPOSAL_LIST_TAG)
+}
+
+/// Reads the proposals of the ballot whose state account is `system_key` from
+/// the state account holding them, a single proposal list account or
+/// per-proposal accounts, depending on what the first account holds.
+fn load_ballot_proposals(
+    program_id: &Pubkey,
+    system_key: &Pubkey,
+    accounts: &[AccountInfo],
+) -> Result<Vec<Proposal>, ProgramError> {
+    let accounts_iter = &mut accounts.iter();
+    let proposal_account = next_account_info(accounts_iter)?;
+
+    if proposal_account.data_len() == Proposal::LEN {
+        load_proposal_accounts(program_id, system_key, accounts)
+    } else if holds_state(program_id, proposal_account) {
+        Ok(load_state(program_id, proposal_account)?.proposals)
+    } else {
+        Proposal::deserialize_list(&proposal_account.data.borrow())
+    }
+}
+
+/// Reads the proposals passed to an instruction that takes no state account of
+/// its own: the state account holding them, a proposal list, or per-proposal
+/// accounts following the state account of their ballot.
+fn load_proposals(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> Result<Vec<Proposal>, ProgramError> {
+    let accounts_iter = &mut accounts.iter();
+    let first_account = next_account_info(accounts_iter)?;
+
+    let proposal_accounts = accounts_iter.as_slice();
+    if !proposal_accounts.is_empty() && holds_state(program_id, first_account) {
+        return load_proposal_accounts(program_id, first_account.key, proposal_accounts);
+    }
+
+    load_ballot_proposals(program_id, first_account.key, accounts)
+}
+
+/// Reads the proposals held by a proposal list or a state account.
+fn held_proposals(
+    program_id: &Pubkey,
+    proposal_account: &AccountInfo,
+) -> Result<Vec<Proposal>, ProgramError> {
+    if holds_state(program_id, proposal_account) {
+        return Ok(load_state(program_id, proposal_account)?.proposals);
+    }
+
+    Proposal::deserialize_list(&proposal_account.data.borrow())
+}
+
+/// Index of the proposal with the most votes, favouring the earliest on ties.
+fn leading_proposal(proposals: &[Proposal]) -> u32 {
+    let mut winning_proposal = 0;
+    let mut winning_vote_count = 0;
+
+    for (index, proposal) in proposals.iter().enumerate() {
+        if proposal.vote_count > winning_vote_count {
+            winning_vote_count = proposal.vote_count;
+            winning_proposal = index as u32;
+        }
+    }
+
+    winning_proposal
+}
+
+fn winning_proposal(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let system_account = next_account_info(accounts_iter)?;
+
+    let system = load_state(program_id, system_account)?;
+    let proposals =
+        load_ballot_proposals(program_id, system_account.key, accounts_iter.as_slice())?;
+
+    // A winner backed by too few votes is not declared
+    system.check_ballot_quorum(&proposals)?;
+
+    // A tie is reported rather than settled by proposal order alone, so the
+    // client can decide how to break it
+    let winning_proposal = leading_proposal(&proposals);
+    let tied = proposals
+        .get(winning_proposal as usize)
+        .map_or(0, |leader| {
+            proposals
+                .iter()
+                .filter(|proposal| proposal.vote_count == leader.vote_count)
+                .count() as u32
+        });
+    if tied > 1 {
+        msg!("{} proposals are tied for the lead", tied);
+    }
+
+    // The winner is returned rather than written, leaving the proposal
+    // accounts untouched by the query
+    let mut result_data = Vec::new();
+    result_data.extend_from_slice(&winning_proposal.to_le_bytes());
+    result_data.extend_from_slice(&tied.to_le_bytes());
+    set_return_data(&result_data);
+
+    Ok(())
+}
+
+fn winner_name(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let proposal_account = next_account_info(accounts_iter)?;
+
+    let proposals = held_proposals(program_id, proposal_account)?;
+
+    // A ballot without proposals has no winner
+    let winner_name = &proposals
+        .get(leading_proposal(&proposals) as usize)
+        .ok_or(ProgramError::InvalidArgument)?
+        .name;
+
+    // The name is returned rather than written, as the account holds the
+    // whole proposal list
+    set_return_data(winner_name);
+
+    Ok(())
+}
+
+fn proposal_rank(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    // Parse the proposal index from the instruction data
+    let proposal_index = instruction_data
+        .get(1..5)
+        .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+        .ok_or(ProgramError::InvalidInstructionData)?;
+
+    let proposals = load_proposals(program_id, accounts)?;
+
+    let proposal = proposals
+        .get(proposal_index as usize)
+        .ok_or(ProgramError::InvalidArgument)?;
+
+    // Rank is one more than the number of proposals strictly ahead, so tied
+    // proposals share the better rank
+    let ahead = proposals
+        .iter()
+        .filter(|other| other.vote_count > proposal.vote_count)
+        .count() as u32;
+    let rank = ahead + 1;
+
+    set_return_data(&rank.to_le_bytes());
+
+    Ok(())
+}
+
+/// Returns the number of votes a proposal needs to move strictly ahead of
+/// every other proposal, which is 0 if it already is.
+fn overtake_gap(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    // Parse the proposal index from the instruction data
+    let proposal_index = u32::from_le_bytes(instruction_data[1..5].try_into().unwrap());
+
+    let proposals = load_proposals(program_id, accounts)?;
+
+    let proposal = proposals
+        .get(proposal_index as usize)
+        .ok_or(ProgramError::InvalidArgument)?;
+
+    // Best count among the other proposals; a tie still needs one more vote
+    let best_rival = proposals
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| *index != proposal_index as usize)
+        .map(|(_, other)| VoteTotal::from(other.vote_count))
+        .max();
+    let gap = match best_rival {
+        Some(best_rival) => best_rival
+            .saturating_add(1)
+            .saturating_sub(VoteTotal::from(proposal.vote_count)),
+        None => 0,
+    };
+
+    set_return_data(&gap.to_le_bytes());
+
+    Ok(())
+}
+
+/// Counts the votes in a velocity account cast within the last N slots. Only
+/// the most recent [`RECENT_VOTE_SLOTS`] votes are kept, so a burst larger than
+/// that saturates the count.
+fn recent_velocity(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let velocity_account = next_account_info(accounts_iter)?;
+
+    let window = u64::from_le_bytes(instruction_data[1..9].try_into().unwrap());
+    let current_slot = Clock::get()?.slot;
+
+    let data = velocity_account.data.borrow();
+    if data.len() < VELOCITY_ACCOUNT_LEN {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Unwritten entries are zero and fall outside any window ending past slot 0
+    let recent = data[4..VELOCITY_ACCOUNT_LEN]
+        .chunks_exact(8)
+        .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+        .filter(|&slot| slot != 0 && current_slot.saturating_sub(slot) < window)
+        .count() as u32;
+    set_return_data(&recent.to_le_bytes());
+
+    Ok(())
+}
+
+/// Returns whether the leader stays ahead even if every voter on the roll who
+/// has not voted yet backs the runner-up.
+fn is_decided(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let system_account = next_account_info(accounts_iter)?;
+
+    let system = load_state(program_id, system_account)?;
+    let proposals =
+        load_ballot_proposals(program_id, system_account.key, accounts_iter.as_slice())?;
+
+    let (leader, runner_up) = top_two_counts(&proposals);
+    let outstanding = system.outstanding_weight()?;
+
+    let decided = leader > runner_up.saturating_add(outstanding);
+    set_return_data(&[decided as u8]);
+
+    Ok(())
+}
+
+/// Vote counts of the leading proposal and the runner-up, 0 where the ballot
+/// has fewer proposals.
+fn top_two_counts(proposals: &[Proposal]) -> (VoteTotal, VoteTotal) {
+    let mut counts: Vec<VoteTotal> = proposals
+        .iter()
+        .map(|proposal| VoteTotal::from(proposal.vote_count))
+        .collect();
+    counts.sort_unstable_by_key(|&count| Reverse(count));
+
+    (
+        counts.first().copied().unwrap_or(0),
+        counts.get(1).copied().unwrap_or(0),
+    )
+}
+
+// States of the race reported by `race_status`
+const RACE_QUORUM_NOT_MET: u8 = 0;
+const RACE_UNDECIDED: u8 = 1;
+const RACE_DECIDED: u8 = 2;
+const RACE_TIED: u8 = 3;
+
+/// Sums up the state of the race in a single status byte. Quorum comes first:
+/// below it no result stands. Past it, the race is decided once the leader
+/// stays ahead even if all outstanding weight backs the runner-up, and tied
+/// once the top two are level with no weight left to break the tie.
+fn race_status(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let system_account = next_account_info(accounts_iter)?;
+
+    // Parse the weight and voter quorums from the instruction data
+    let weight_quorum = u64::from_le_bytes(instruction_data[1..9].try_into().unwrap());
+    let voter_quorum = u32::from_le_bytes(instruction_data[9..13].try_into().unwrap());
+
+    let system = load_state(program_id, system_account)?;
+    let proposals =
+        load_ballot_proposals(program_id, system_account.key, accounts_iter.as_slice())?;
+
+    let (weight_voted, voters_voted) = system.participation();
+    let (leader, runner_up) = top_two_counts(&proposals);
+    let outstanding = system.outstanding_weight()?;
+
+    let status = if weight_voted < VoteTotal::from(weight_quorum) || voters_voted < voter_quorum {
+        RACE_QUORUM_NOT_MET
+    } else if leader > runner_up.saturating_add(outstanding) {
+        RACE_DECIDED
+    } else if leader == runner_up && outstanding == 0 {
+        RACE_TIED
+    } else {
+        RACE_UNDECIDED
+    };
+    set_return_data(&[status]);
+
+    Ok(())
+}
+
+/// Progress of `current` toward `threshold` in basis points, capped at 10000.
+/// A zero threshold is met from the start.
+fn basis_points(current: VoteTotal, threshold: VoteTotal) -> u16 {
+    if threshold == 0 || current >= threshold {
+        return 10_000;
+    }
+
+    // current < threshold, so the quotient is below 10000. Scaling a very
+    // large count would overflow, in which case the threshold is scaled down
+    // instead, at a negligible loss of precision.
+    let bps = match current.checked_mul(10_000) {
+        Some(scaled) => scaled / threshold,
+        None => current / (threshold / 10_000),
+    };
+    bps as u16
+}
+
+/// Fails with `QuorumNotMet` unless both the weight and the voter quorum are
+/// met. A zero quorum counts as met.
+pub fn check_quorum(
+    weight_voted: VoteTotal,
+    voters_voted: u32,
+    weight_quorum: u64,
+    voter_quorum: u32,
+) -> ProgramResult {
+    if weight_voted < VoteTotal::from(weight_quorum) || voters_voted < voter_quorum {
+        msg!("Quorum not met");
+        msg!("Weight voted {} of {}", weight_voted, weight_quorum);
+        msg!("Voters voted {} of {}", voters_voted, voter_quorum);
+        return Err(VotingError::QuorumNotMet.into());
+    }
+
+    Ok(())
+}
+
+fn quorum_progress(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let system_account = next_account_info(accounts_iter)?;
+
+    // Parse the weight and voter quorums from the instruction data
+    let weight_quorum = u64::from_le_bytes(instruction_data[1..9].try_into().unwrap());
+    let voter_quorum = u32::from_le_bytes(instruction_data[9..13].try_into().unwrap());
+
+    let system = load_state(program_id, system_account)?;
+
+    let (weight_voted, voters_voted) = system.participation();
+
+    let mut result_data = Vec::new();
+    result_data.extend_from_slice(&weight_voted.to_le_bytes());
+    result_data.extend_from_slice(&voters_voted.to_le_bytes());
+    result_data.extend_from_slice(
+        &basis_points(weight_voted, VoteTotal::from(weight_quorum)).to_le_bytes(),
+    );
+    result_data.extend_from_slice(
+        &basis_points(VoteTotal::from(voters_voted), VoteTotal::from(voter_quorum)).to_le_bytes(),
+    );
+    set_return_data(&result_data);
+
+    Ok(())
+}
+
+fn weight_stats(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let system_account = next_account_info(accounts_iter)?;
+
+    let system = load_state(program_id, system_account)?;
+
+    let mut weights = system.voters.iter().map(|(_, voter)| voter.weight);
+    let min = weights.clone().min().unwrap_or(0);
+    let max = weights.clone().max().unwrap_or(0);
+    let sum = weights.try_fold(0u128, |sum, weight| {
+        sum.checked_add(u128::from(weight))
+            .ok_or(ProgramError::InvalidAccountData)
+    })?;
+
+    // The mean of u64 weights always fits in a u64
+    let mean = sum.checked_div(system.voters.len() as u128).unwrap_or(0) as u64;
+
+    let mut result_data = Vec::new();
+    result_data.extend_from_slice(&min.to_le_bytes());
+    result_data.extend_from_slice(&max.to_le_bytes());
+    result_data.extend_from_slice(&mean.to_le_bytes());
+    set_return_data(&result_data);
+
+    Ok(())
+}
+
+/// Returns the median weight of the roll. With an even number of voters the
+/// median is the mean of the two middle weights, which may end in a half.
+fn median_weight(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let system_account = next_account_info(accounts_iter)?;
+
+    let system = load_state(program_id, system_account)?;
+
+    let mut weights: Vec<u64> = system
+        .voters
+        .iter()
+        .map(|(_, voter)| voter.weight)
+        .collect();
+    weights.sort_unstable();
+
+    let middle = weights.len() / 2;
+    let (median, half) = match weights.len() {
+        0 => (0, false),
+        len if len % 2 == 1 => (weights[middle], false),
+        _ => {
+            let sum = u128::from(weights[middle - 1]) + u128::from(weights[middle]);
+            ((sum / 2) as u64, sum % 2 == 1)
+        }
+    };
+
+    let mut result_data = Vec::new();
+    result_data.extend_from_slice(&median.to_le_bytes());
+    result_data.push(half as u8);
+    set_return_data(&result_data);
+
+    Ok(())
+}
+
+fn unvoted_voters(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let system_account = next_account_info(accounts_iter)?;
+
+    // Parse the page offset and limit from the instruction data
+    let offset = u32::from_le_bytes(instruction_data[1..5].try_into().unwrap()) as usize;
+    let limit = u32::from_le_bytes(instruction_data[5..9].try_into().unwrap()) as usize;
+
+    let system = load_state(program_id, system_account)?;
+
+    // A page has to fit in the return data
+    let limit = limit.min(MAX_RETURN_DATA / 32);
+
+    let mut result_data = Vec::new();
+    for (voter_key, _) in system
+        .voters
+        .iter()
+        .filter(|(_, voter)| !voter.voted && voter.weight > 0)
+        .skip(offset)
+        .take(limit)
+    {
+        result_data.extend_from_slice(voter_key.as_ref());
+    }
+    set_return_data(&result_data);
+
+    Ok(())
+}
+
+/// Returns a page of `(index, vote_count)` pairs, most votes first and in
+/// index order among ties.
+fn leaderboard(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    // Parse the page offset and limit from the instruction data
+    let offset = u32::from_le_bytes(instruction_data[1..5].try_into().unwrap()) as usize;
+    let limit = u32::from_le_bytes(instruction_data[5..9].try_into().unwrap()) as usize;
+
+    let accounts_iter = &mut accounts.iter();
+    let system_account = next_account_info(accounts_iter)?;
+
+    let system = load_state(program_id, system_account)?;
+    let proposals =
+        load_ballot_proposals(program_id, system_account.key, accounts_iter.as_slice())?;
+
+    // The sort is stable, so ties keep their index order
+    let mut standings: Vec<(u32, VoteCount)> = proposals
+        .iter()
+        .enumerate()
+        .map(|(index, proposal)| (index as u32, proposal.vote_count))
+        .collect();
+    standings.sort_by_key(|&(_, vote_count)| Reverse(vote_count));
+
+    // A page has to fit in the return data after the decimals
+    let limit = limit.min((MAX_RETURN_DATA - 1) / (4 + COUNT_LEN));
+
+    let mut result_data = vec![system.decimals];
+    for (index, vote_count) in standings.iter().skip(offset).take(limit) {
+        result_data.extend_from_slice(&index.to_le_bytes());
+        result_data.extend_from_slice(&vote_count.to_le_bytes());
+    }
+    set_return_data(&result_data);
+
+    Ok(())
+}
+
+fn contested_count(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let proposals = load_proposals(program_id, accounts)?;
+
+    let contested = proposals
+        .iter()
+        .filter(|proposal| proposal.vote_count > 0)
+        .count() as u32;
+    set_return_data(&contested.to_le_bytes());
+
+    Ok(())
+}
+
+/// Sum of all proposals' vote counts.
+fn vote_total(proposals: &[Proposal]) -> Result<VoteTotal, ProgramError> {
+    proposals.iter().try_fold(0, |total: VoteTotal, proposal| {
+        total
+            .checked_add(VoteTotal::from(proposal.vote_count))
+            .ok_or(ProgramError::InvalidAccountData)
+    })
+}
+
+/// Share of `total` held by `count` in basis points, 0 out of a zero total.
+fn share_bps(count: VoteTotal, total: VoteTotal) -> u16 {
+    if total == 0 {
+        return 0;
+    }
+
+    basis_points(count, total)
+}
+
+fn winner_share(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let proposals = load_proposals(program_id, accounts)?;
+
+    let winner = leading_proposal(&proposals);
+    let winner_count = proposals
+        .get(winner as usize)
+        .map_or(0, |proposal| VoteTotal::from(proposal.vote_count));
+
+    let mut result_data = Vec::new();
+    result_data.extend_from_slice(&winner.to_le_bytes());
+    result_data.extend_from_slice(&share_bps(winner_count, vote_total(&proposals)?).to_le_bytes());
+    set_return_data(&result_data);
+
+    Ok(())
+}
+
+/// Threshold `needs_runoff` applies when none is given: a simple majority.
+const DEFAULT_RUNOFF_THRESHOLD_BPS: u16 = 5_000;
+
+/// Reports whether a second round is needed between the two leading
+/// proposals. A runoff needs votes to have been cast and at least two
+/// proposals to contest it.
+fn needs_runoff(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    // Parse the threshold from the instruction data
+    let threshold = match u16::from_le_bytes(instruction_data[1..3].try_into().unwrap()) {
+        0 => DEFAULT_RUNOFF_THRESHOLD_BPS,
+        threshold => threshold,
+    };
+
+    let proposals = load_proposals(program_id, accounts)?;
+
+    // The sort is stable, so ties keep their index order
+    let mut standings: Vec<(u32, VoteCount)> = proposals
+        .iter()
+        .enumerate()
+        .map(|(index, proposal)| (index as u32, proposal.vote_count))
+        .collect();
+    standings.sort_by_key(|&(_, vote_count)| Reverse(vote_count));
+
+    let first = standings.first().map_or(u32::MAX, |&(index, _)| index);
+    let second = standings.get(1).map_or(u32::MAX, |&(index, _)| index);
+
+    let total = vote_total(&proposals)?;
+    let leader_count = standings
+        .first()
+        .map_or(0, |&(_, vote_count)| VoteTotal::from(vote_count));
+    let runoff = total != 0 && second != u32::MAX && share_bps(leader_count, total) < threshold;
+
+    let mut result_data = vec![runoff as u8];
+    result_data.extend_from_slice(&first.to_le_bytes());
+    result_data.extend_from_slice(&second.to_le_bytes());
+    set_return_data(&result_data);
+
+    Ok(())
+}
+
+fn total_votes(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let system_account = next_account_info(accounts_iter)?;
+
+    let system = load_state(program_id, system_account)?;
+    let proposals =
+        load_ballot_proposals(program_id, system_account.key, accounts_iter.as_slice())?;
+
+    // Abstentions back no proposal but still count toward the total
+    let total = vote_total(&proposals)?
+        .checked_add(VoteTotal::from(system.abstain_weight))
+        .ok_or(ProgramError::InvalidAccountData)?;
+
+    let mut result_data = vec![system.decimals];
+    result_data.extend_from_slice(&total.to_le_bytes());
+    set_return_data(&result_data);
+
+    Ok(())
+}
+
+/// Address of the archive account of the ballot whose state account is
+/// `system_key`.
+fn archive_address(program_id: &Pubkey, system_key: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"archive", system_key.as_ref()], program_id)
+}
+
+/// Records the winner of a finished ballot, the vote total, the number of
+/// voters who took part and the current slot into the ballot's archive
+/// account.
+fn archive_results(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let chairperson_account = next_account_info(accounts_iter)?;
+    let system_account = next_account_info(accounts_iter)?;
+    let proposal_account = next_account_info(accounts_iter)?;
+    let archive_account = next_account_info(accounts_iter)?;
+    // The system program is only needed to create the archive account
+    let system_program_account = accounts_iter.next();
+
+    // Check if the sender is the chairperson
+    let system = require_chairperson(program_id, system_account, chairperson_account)?;
+
+    // Only the final results are archived
+    system.check_finished()?;
+
+    // Each ballot has the one archive derived from its state account, so
+    // another ballot's archive cannot be written over
+    let (expected_key, bump) = archive_address(program_id, system_account.key);
+    if *archive_account.key != expected_key {
+        msg!("The archive account is not this ballot's archive");
+        return Err(ProgramError::InvalidArgument);
+    }
+    if archive_account.data_is_empty() && *archive_account.owner == system_program::id() {
+        let system_program_account =
+            system_program_account.ok_or(ProgramError::NotEnoughAccountKeys)?;
+        invoke_signed(
+            &system_instruction::create_account(
+                chairperson_account.key,
+                archive_account.key,
+                Rent::get()?.minimum_balance(BallotArchive::LEN),
+                BallotArchive::LEN as u64,
+                program_id,
+            ),
+            &[
+                chairperson_account.clone(),
+                archive_account.clone(),
+                system_program_account.clone(),
+            ],
+            &[&[b"archive", system_account.key.as_ref(), &[bump]]],
+        )?;
+    }
+    if archive_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let proposals = held_proposals(program_id, proposal_account)?;
+    let winning_proposal = leading_proposal(&proposals);
+    let winner = proposals
+        .get(winning_proposal as usize)
+        .ok_or(ProgramError::InvalidAccountData)?;
 
-impl Voter {
-    fn deserialize(data: &[u8]) -> Result<Self, ProgramError> {
-        let weight = u32::from_le_bytes(data[..4].try_into().unwrap());
-        let voted = data[4] != 0;
-        let vote = u32::from_le_bytes(data[5..9].try_into().unwrap());
+    let archive = BallotArchive {
+        winning_proposal,
+        winner_name: winner.name.clone(),
+        // Abstentions count toward the total, as for `total_votes`
+        total_votes: vote_total(&proposals)?
+            .checked_add(VoteTotal::from(system.abstain_weight))
+            .ok_or(ProgramError::InvalidAccountData)?,
+        participants: system
+            .voters
+            .iter()
+            .filter(|(_, voter)| voter.voted)
+            .count() as u32,
+        end_slot: Clock::get()?.slot,
+        decimals: system.decimals,
+    };
 
-        Ok(Voter {
-            weight,
-            voted,
-            vote,
-        })
+    let archive_data = &mut archive_account.data.borrow_mut();
+    if archive_data.len() < BallotArchive::LEN {
+        return Err(ProgramError::AccountDataTooSmall);
     }
+    // The first archive of a ballot is the one kept
+    if archive_data.iter().any(|&byte| byte != 0) {
+        msg!("The ballot has been archived already");
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    archive_data[..BallotArchive::LEN].copy_from_slice(&archive.to_bytes());
 
-    fn serialize(&self) -> Vec<u8> {
-        let mut bytes = Vec::new();
-        bytes.extend_from_slice(&self.weight.to_le_bytes());
-        bytes.push(self.voted as u8);
-        bytes.extend_from_slice(&self.vote.to_le_bytes());
+    Ok(())
+}
 
-        bytes
+/// Sets the `chunk`-th piece of `data` as return data. Chunks hold as many
+/// whole `record_len`-byte records as fit in the return data, so a record is
+/// never split across two chunks.
+fn set_return_data_chunk(data: &[u8], record_len: usize, chunk: usize) -> ProgramResult {
+    let chunk_len = MAX_RETURN_DATA / record_len * record_len;
+    let start = chunk * chunk_len;
+    if start > data.len() {
+        return Err(ProgramError::InvalidArgument);
     }
+    let end = data.len().min(start + chunk_len);
 
-    fn from_bytes(bytes: &[u8]) -> Result<Self, ProgramError> {
-        let weight = u32::from_le_bytes(bytes[..4].try_into().unwrap());
-        let voted = bytes[4] != 0;
-        let vote = u32::from_le_bytes(bytes[5..9].try_into().unwrap());
+    set_return_data(&data[start..end]);
 
-        Ok(Voter {
-            weight,
-            voted,
-            vote,
-        })
-    }
+    Ok(())
+}
 
-    fn to_bytes(&self) -> Vec<u8> {
-        let mut bytes = Vec::new();
-        bytes.extend_from_slice(&self.weight.to_le_bytes());
-        bytes.push(self.voted as u8);
-        bytes.extend_from_slice(&self.vote.to_le_bytes());
+fn dump_proposals(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    // Parse the chunk index from the instruction data
+    let chunk = u32::from_le_bytes(instruction_data[1..5].try_into().unwrap()) as usize;
 
-        bytes
+    let proposals = load_proposals(program_id, accounts)?;
+
+    let mut records = Vec::new();
+    for proposal in &proposals {
+        records.extend_from_slice(&proposal.to_bytes());
     }
+
+    set_return_data_chunk(&records, Proposal::LEN, chunk)
 }
 
-#[derive(Debug)]
-struct Proposal {
-    name: [u8; 32],
-    vote_count: u32,
+/// Reads the slot a ballot was initialized in from its state account data.
+pub fn created_slot(system_data: &[u8]) -> Result<u64, ProgramError> {
+    Ok(SimpleVotingSystem::deserialize(system_data)?.created_slot)
 }
 
-impl Proposal {
-    fn from_bytes(bytes: &[u8]) -> Result<Self, ProgramError> {
-        let name = bytes[..32].try_into().unwrap();
-        let vote_count = u32::from_le_bytes(bytes[32..36].try_into().unwrap());
+fn created_at(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let system_account = next_account_info(accounts_iter)?;
 
-        Ok(Proposal { name, vote_count })
+    if system_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
     }
+    let created_slot = created_slot(&system_account.data.borrow())?;
+    set_return_data(&created_slot.to_le_bytes());
 
-    fn to_bytes(&self) -> Vec<u8> {
-        let mut bytes = Vec::new();
-        bytes.extend_from_slice(&self.name);
-        bytes.extend_from_slice(&self.vote_count.to_le_bytes());
+    Ok(())
+}
 
-        bytes
+/// Size of one voter roll entry: the voter's key and its record.
+pub const ROLL_ENTRY_LEN: usize = 32 + Voter::LEN;
+
+/// Serializes up to `limit` roll entries starting at the `offset`-th, fewer if
+/// they would not fit in the return data. An offset past the end of the roll
+/// is rejected; one at its end yields no entries.
+pub fn dump_roll_entries(
+    voters: &[(Pubkey, Voter)],
+    offset: usize,
+    limit: usize,
+) -> Result<Vec<u8>, ProgramError> {
+    let entries = voters.get(offset..).ok_or(ProgramError::InvalidArgument)?;
+
+    let mut bytes = Vec::new();
+    for (voter_key, voter) in entries
+        .iter()
+        .take(limit.min(MAX_RETURN_DATA / ROLL_ENTRY_LEN))
+    {
+        bytes.extend_from_slice(&voter_key.to_bytes());
+        bytes.extend_from_slice(&voter.to_bytes());
     }
 
-    fn deserialize_list(data: &[u8]) -> Result<Vec<Self>, ProgramError> {
-        let mut proposals = Vec::new();
-        let mut offset = 4; // Skip the winning proposal index
+    Ok(bytes)
+}
 
-        while offset < data.len() {
-            let name = data[offset..offset + 32].try_into().unwrap();
-            let vote_count = u32::from_le_bytes(data[offset + 32..offset + 36].try_into().unwrap());
+fn dump_roll(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let system_account = next_account_info(accounts_iter)?;
 
-            proposals.push(Proposal { name, vote_count });
+    // Parse the offset and limit from the instruction data
+    let offset = u32::from_le_bytes(instruction_data[1..5].try_into().unwrap()) as usize;
+    let limit = u32::from_le_bytes(instruction_data[5..9].try_into().unwrap()) as usize;
 
-            offset += 36;
-        }
+    let system = load_state(program_id, system_account)?;
+    set_return_data(&dump_roll_entries(&system.voters, offset, limit)?);
 
-        Ok(proposals)
-    }
+    Ok(())
 }
 
-#[derive(Debug)]
-struct SimpleVotingSystem {
-    chairperson: Pubkey,
-    voters: Vec<(Pubkey, Voter)>,
-    proposals: Vec<Proposal>,
-}
+fn config(program_id: &Pubkey, accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let system_account = next_account_info(accounts_iter)?;
 
-entrypoint!(process_instruction);
+    // Parse the chunk index from the instruction data
+    let chunk = u32::from_le_bytes(instruction_data[1..5].try_into().unwrap()) as usize;
+
+    let system = load_state(program_id, system_account)?;
+
+    let mut summary = vec![STATE_VERSION];
+    summary.extend_from_slice(&system.chairperson.to_bytes());
+    summary.extend_from_slice(&system.min_participation_weight.to_le_bytes());
+    summary.extend_from_slice(&system.title);
+    summary.push(system.decimals);
+    summary.push(system.results_sealed as u8);
+    summary.extend_from_slice(&system.snapshot_slot.to_le_bytes());
+    summary.push(system.voting_started as u8);
+    summary.extend_from_slice(&system.end_timestamp.to_le_bytes());
+    summary.extend_from_slice(&system.quorum.to_le_bytes());
+    summary.extend_from_slice(&system.gov_mint.to_bytes());
+    summary.extend_from_slice(&(system.proposals.len() as u32).to_le_bytes());
+    summary.extend_from_slice(&(system.voters.len() as u32).to_le_bytes());
 
-fn process_instruction(
+    set_return_data_chunk(&summary, 1, chunk)
+}
+
+fn get_proposal(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     instruction_data: &[u8],
 ) -> ProgramResult {
-    msg!("Simple Voting System Rust program entrypoint");
+    // Parse the proposal index from the instruction data
+    let proposal_index = u32::from_le_bytes(instruction_data[1..5].try_into().unwrap());
 
-    // Parse the instruction data and call the appropriate function based on its value
-    match instruction_data[0] {
-        0 => {
-            // Give right to vote instruction
-            let result = give_right_to_vote(program_id, accounts, instruction_data);
-
-            match result {
-                Ok(()) => {
-                    // Handle success case
-                    println!("Voting rights granted successfully");
-                    return Ok(());
-                }
-                Err(error) => match error {
-                    ProgramError::InvalidAccountData => {
-                        // Handle specific error case
-                        println!("Encountered InvalidAccountData: {:?}", error);
-                    }
-                    _ => {
-                        // Handle any other error case
-                        println!("Encountered an unknown error: {:?}", error);
-                        return Err(ProgramError::Custom(0));
-                    }
-                },
-            }
-        }
-        1 => {
-            // Vote instruction
-            let result = vote(program_id, accounts, instruction_data);
+    let proposal = read_proposal(program_id, accounts, proposal_index)?;
+    set_return_data(&proposal.to_bytes());
 
-            match result {
-                Ok(()) => {
-                    // Handle success case
-                    println!("Voting rights granted successfully");
-                    return Ok(());
-                }
-                Err(error) => match error {
-                    ProgramError::InvalidAccountData => {
-                        // Handle specific error case
-                        println!("Encountered InvalidAccountData: {:?}", error);
-                    }
-                    _ => {
-                        // Handle any other error case
-                        println!("Encountered an unknown error: {:?}", error);
-                        return Err(ProgramError::Custom(0));
-                    }
-                },
-            }
-        }
-        _ => {
-            println!("Invalid Instruction");
-            return Err(ProgramError::Custom(0));
-        }
-    }
     Ok(())
 }
 
-fn give_right_to_vote(
+/// Reads the proposal at `index` from the state account, a proposal list or
+/// a per-proposal account following the state account of its ballot.
+fn read_proposal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    index: u32,
+) -> Result<Proposal, ProgramError> {
+    let accounts_iter = &mut accounts.iter();
+    let first_account = next_account_info(accounts_iter)?;
+
+    let proposal_account = match accounts_iter.next() {
+        Some(proposal_account) if holds_state(program_id, first_account) => proposal_account,
+        _ => first_account,
+    };
+
+    // The state's own proposals follow its roll rather than a list header
+    if holds_state(program_id, proposal_account) {
+        return load_state(program_id, proposal_account)?
+            .proposals
+            .into_iter()
+            .nth(index as usize)
+            .ok_or(ProgramError::InvalidArgument);
+    }
+
+    let proposal_data = &proposal_account.data.borrow();
+    let offset = proposal_record_offset(
+        program_id,
+        first_account.key,
+        proposal_account,
+        proposal_data,
+        index,
+    )?;
+    Proposal::from_bytes(&proposal_data[offset..offset + Proposal::LEN])
+}
+
+fn remaining_choices(
     _program_id: &Pubkey,
     accounts: &[AccountInfo],
     _instruction_data: &[u8],
 ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
-    //let chairperson_account = next_account_info(accounts_iter)?;
     let voter_account = next_account_info(accounts_iter)?;
 
-    // // Check if the sender is the chairperson
-    // if *chairperson_account.key != chairperson_public_key {
-    //     return Err(ProgramError::InvalidAccountData);
-    // }
+    let voter = Voter::from_bytes(&voter_account.data.borrow())?;
+    set_return_data(&voter.remaining_choices().to_le_bytes());
 
-    // Check if the voter has already voted
-    let mut voter_data = voter_account.data.borrow_mut();
-    let voter = Voter::deserialize(&voter_data)?;
+    Ok(())
+}
 
-    if voter.voted {
-        return Err(ProgramError::InvalidAccountData);
-    }
+fn verify_description(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    // Parse the proposal index and the description from the instruction data
+    let proposal_index = u32::from_le_bytes(instruction_data[1..5].try_into().unwrap());
+    let description = &instruction_data[5..];
 
-    // Give the voter the right to vote
-    let voter = Voter {
-        weight: 1,
-        voted: false,
-        vote: 0,
-    };
-    voter_data.copy_from_slice(&voter.to_bytes());
+    let proposal = read_proposal(program_id, accounts, proposal_index)?;
+
+    let matches = proposal.matches_description(description);
+    msg!(
+        "Proposal {} description matches: {}",
+        proposal_index,
+        matches
+    );
+    set_return_data(&[matches as u8]);
 
     Ok(())
 }
 
-fn vote(_program_id: &Pubkey, accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResult {
+fn set_expected_votes(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
-    let voter_account = next_account_info(accounts_iter)?;
+    let chairperson_account = next_account_info(accounts_iter)?;
+    let system_account = next_account_info(accounts_iter)?;
     let proposal_account = next_account_info(accounts_iter)?;
 
-    // Parse the proposal index from the instruction data
-    let proposal_index = u32::from_le_bytes(instruction_data[1..].try_into().unwrap());
+    // Check if the sender is the chairperson
+    let mut system = require_chairperson(program_id, system_account, chairperson_account)?;
 
-    // Retrieve the voter and proposal data
-    let voter_data = &mut voter_account.data.borrow_mut();
-    let proposal_data = &mut proposal_account.data.borrow_mut();
-    let mut voter = Voter::from_bytes(voter_data)?;
-    let mut proposal = Proposal::from_bytes(proposal_data)?;
+    // Check if the results are still open
+    if system.results_sealed {
+        return Err(ProgramError::InvalidArgument);
+    }
 
-    // Check if the voter has the right to vote
-    if voter.weight == 0 {
-        return Err(ProgramError::InvalidAccountData);
+    // Parse the proposal index, expected maximum and reject flag
+    let proposal_index = u32::from_le_bytes(instruction_data[1..5].try_into().unwrap());
+    let max_expected_votes =
+        VoteCount::from_le_bytes(instruction_data[5..5 + COUNT_LEN].try_into().unwrap());
+    let reject_unexpected = instruction_data[5 + COUNT_LEN] != 0;
+
+    update_proposal(
+        program_id,
+        system_account,
+        &mut system,
+        proposal_account,
+        proposal_index,
+        |proposal| {
+            proposal.max_expected_votes = max_expected_votes;
+            proposal.reject_unexpected = reject_unexpected;
+            proposal.nonce += 1;
+
+            Ok(())
+        },
+    )?;
+
+    // A proposal held by the state is written back with it
+    if proposal_account.key == system_account.key {
+        system.write_into(&mut system_account.try_borrow_mut_data()?)?;
     }
 
-    // Check if the voter has already voted
-    if voter.voted {
-        return Err(ProgramError::InvalidAccountData);
+    Ok(())
+}
+
+fn set_title(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let chairperson_account = next_account_info(accounts_iter)?;
+    let system_account = next_account_info(accounts_iter)?;
+
+    // Check if the sender is the chairperson
+    let mut system = require_chairperson(program_id, system_account, chairperson_account)?;
+
+    system.title = instruction_data[1..65].try_into().unwrap();
+    system.write_into(&mut system_account.try_borrow_mut_data()?)
+}
+
+fn set_decimals(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let chairperson_account = next_account_info(accounts_iter)?;
+    let system_account = next_account_info(accounts_iter)?;
+
+    // Check if the sender is the chairperson
+    let mut system = require_chairperson(program_id, system_account, chairperson_account)?;
+
+    system.decimals = instruction_data[1];
+    system.write_into(&mut system_account.try_borrow_mut_data()?)
+}
+
+/// Sets the governance token mint. The mint cannot change once voting has
+/// opened, as ballots already cast were weighed by the old one.
+fn set_gov_mint(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let chairperson_account = next_account_info(accounts_iter)?;
+    let system_account = next_account_info(accounts_iter)?;
+
+    // Check if the sender is the chairperson
+    let mut system = require_chairperson(program_id, system_account, chairperson_account)?;
+
+    if system.voting_started {
+        msg!("The governance mint cannot change once voting has started");
+        return Err(ProgramError::InvalidArgument);
     }
 
-    // Update the voter and proposal data
-    voter.voted = true;
-    voter.vote = proposal_index;
-    proposal.vote_count += voter.weight;
+    system.gov_mint = read_pubkey(instruction_data, 1)?;
+    system.write_into(&mut system_account.try_borrow_mut_data()?)
+}
+
+fn start_voting(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let chairperson_account = next_account_info(accounts_iter)?;
+    let system_account = next_account_info(accounts_iter)?;
 
-    // Save the updated data back to the accounts
-    voter_data.copy_from_slice(&voter.to_bytes());
-    proposal_data.copy_from_slice(&proposal.to_bytes());
+    // Check if the sender is the chairperson
+    let mut system = require_chairperson(program_id, system_account, chairperson_account)?;
 
-    Ok(())
+    // A ballot without proposals would only collect unusable votes
+    let proposal_accounts = accounts_iter.as_slice();
+    let has_proposals = if proposal_accounts.is_empty() {
+        !system.proposals.is_empty()
+    } else {
+        !load_ballot_proposals(program_id, system_account.key, proposal_accounts)?.is_empty()
+    };
+    if !has_proposals {
+        msg!("Voting cannot start on a ballot without proposals");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    system.voting_started = true;
+    system.write_into(&mut system_account.try_borrow_mut_data()?)
 }
 
-fn winning_proposal(
-    _program_id: &Pubkey,
+/// Makes the results final. Sealing cannot be undone.
+fn seal_results(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let chairperson_account = next_account_info(accounts_iter)?;
+    let system_account = next_account_info(accounts_iter)?;
+
+    // Check if the sender is the chairperson
+    let mut system = require_chairperson(program_id, system_account, chairperson_account)?;
+
+    // Refuse to lock in a winner the ballot's quorum does not back, counting
+    // the proposals held by the state unless proposal accounts are passed
+    let proposal_accounts = accounts_iter.as_slice();
+    if proposal_accounts.is_empty() {
+        system.check_ballot_quorum(&system.proposals)?;
+    } else {
+        system.check_ballot_quorum(&load_ballot_proposals(
+            program_id,
+            system_account.key,
+            proposal_accounts,
+        )?)?;
+    }
+
+    // and the participation quorums, if given
+    if let Some(quorums) = instruction_data.get(1..13) {
+        let weight_quorum = u64::from_le_bytes(quorums[..8].try_into().unwrap());
+        let voter_quorum = u32::from_le_bytes(quorums[8..].try_into().unwrap());
+        let (weight_voted, voters_voted) = system.participation();
+        check_quorum(weight_voted, voters_voted, weight_quorum, voter_quorum)?;
+    }
+
+    system.results_sealed = true;
+    system.write_into(&mut system_account.try_borrow_mut_data()?)
+}
+
+/// Reads a version 0 state account and writes its contents in the current
+/// layout into a new state account. The old account is left as it was.
+fn migrate(
+    program_id: &Pubkey,
     accounts: &[AccountInfo],
     _instruction_data: &[u8],
 ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
-    let proposal_account = next_account_info(accounts_iter)?;
+    let chairperson_account = next_account_info(accounts_iter)?;
+    let old_system_account = next_account_info(accounts_iter)?;
+    let new_system_account = next_account_info(accounts_iter)?;
 
-    let proposal_data = &proposal_account.data.borrow();
-    let proposals = Proposal::deserialize_list(proposal_data)?;
+    if old_system_account.owner != program_id || new_system_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let system = SimpleVotingSystem::deserialize_v0(&old_system_account.data.borrow())?;
 
-    let mut winning_proposal = 0;
-    let mut winning_vote_count = 0;
+    // Check if the sender is the chairperson
+    system.check_chairperson(chairperson_account)?;
 
-    for (index, proposal) in proposals.iter().enumerate() {
-        if proposal.vote_count > winning_vote_count {
-            winning_vote_count = proposal.vote_count;
-            winning_proposal = index as u32;
-        }
+    // Refuse to overwrite a state account already carrying a version
+    let new_system_data = &mut new_system_account.data.borrow_mut();
+    if new_system_data.first() == Some(&STATE_VERSION) {
+        return Err(ProgramError::AccountAlreadyInitialized);
     }
 
-    let mut result_data = vec![0u8; 4];
-    result_data.copy_from_slice(&winning_proposal.to_le_bytes());
-    proposal_account
-        .data
-        .borrow_mut()
-        .copy_from_slice(&result_data);
+    system.write_into(new_system_data)
+}
+
+fn chairperson_voter(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let system_account = next_account_info(accounts_iter)?;
+
+    let system = load_state(program_id, system_account)?;
+
+    let voter = system
+        .voters
+        .iter()
+        .find(|(voter_key, _)| *voter_key == system.chairperson)
+        .map(|(_, voter)| voter.to_bytes());
+    set_return_data(voter.as_deref().unwrap_or_default());
 
     Ok(())
 }
 
-fn winner_name(
-    _program_id: &Pubkey,
+/// Schedules the weight snapshot. A snapshot that has already been taken
+/// cannot be moved.
+fn set_snapshot_slot(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let chairperson_account = next_account_info(accounts_iter)?;
+    let system_account = next_account_info(accounts_iter)?;
+
+    // Check if the sender is the chairperson
+    let mut system = require_chairperson(program_id, system_account, chairperson_account)?;
+
+    system.check_weights_unfrozen()?;
+
+    system.snapshot_slot = u64::from_le_bytes(instruction_data[1..9].try_into().unwrap());
+    system.write_into(&mut system_account.try_borrow_mut_data()?)
+}
+
+fn get_title(
+    program_id: &Pubkey,
     accounts: &[AccountInfo],
     _instruction_data: &[u8],
 ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
-    let proposal_account = next_account_info(accounts_iter)?;
+    let system_account = next_account_info(accounts_iter)?;
 
-    let proposal_data = &proposal_account.data.borrow();
-    let proposals = Proposal::deserialize_list(proposal_data)?;
+    let system = load_state(program_id, system_account)?;
+
+    // Strip the zero padding after the title
+    set_return_data(trim_name(&system.title));
+
+    Ok(())
+}
 
-    let winning_proposal = u32::from_le_bytes(proposal_data[..4].try_into().unwrap());
+/// Reads the pubkey stored at `offset`, failing rather than panicking if the
+/// data ends before it.
+fn read_pubkey(data: &[u8], offset: usize) -> Result<Pubkey, ProgramError> {
+    let bytes = offset
+        .checked_add(32)
+        .and_then(|end| data.get(offset..end))
+        .ok_or(ProgramError::InvalidAccountData)?;
 
-    let winner_name = proposals[winning_proposal as usize].name;
+    Ok(Pubkey::new_from_array(bytes.try_into().unwrap()))
+}
 
-    let mut result_data = vec![0u8; 32];
-    result_data.copy_from_slice(&winner_name);
-    proposal_account
-        .data
-        .borrow_mut()
-        .copy_from_slice(&result_data);
+/// Fails with `InvalidArgument` for the default key, which a client that left
+/// a key unset passes in its place.
+fn check_key_set(key: &Pubkey) -> ProgramResult {
+    if *key == Pubkey::default() {
+        msg!("The default key is not a valid account here");
+        return Err(ProgramError::InvalidArgument);
+    }
 
     Ok(())
 }
 
+/// Splits the next `len` bytes off `data`, failing on a truncated account
+/// rather than panicking on a short read.
+fn take<'a>(data: &mut &'a [u8], len: usize) -> Result<&'a [u8], ProgramError> {
+    if data.len() < len {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let (head, rest) = data.split_at(len);
+    *data = rest;
+
+    Ok(head)
+}
+
 impl SimpleVotingSystem {
-    fn deserialize(data: &[u8]) -> Result<Self, ProgramError> {
-        let chairperson = Pubkey::new_from_array(data[..32].try_into().unwrap());
+    /// Reads the state at the start of `data`. Bytes past the proposals, such
+    /// as slack left in the account for rent exemption, are ignored.
+    pub fn deserialize(data: &[u8]) -> Result<Self, ProgramError> {
+        <Self as BorshDeserialize>::deserialize(&mut &data[..])
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
 
-        let mut offset = 32;
-        let mut voters = Vec::new();
+    /// Reads a state account written before the layout carried a version. Its
+    /// header ends at `decimals`, its weights and vote counts are 32 bits wide,
+    /// unless built with `u128-counts`, and its proposals, 32-byte names
+    /// without a description hash, fill the rest of the account.
+    fn deserialize_v0(data: &[u8]) -> Result<Self, ProgramError> {
+        let data = &mut &data[..];
+        // Weight, `voted` and vote of each voter
+        const VOTER_LEN: usize = 9;
+        const COUNT_LEN_V0: usize = if cfg!(feature = "u128-counts") { 16 } else { 4 };
+        const PROPOSAL_LEN: usize = 32 + COUNT_LEN_V0 + 8 + COUNT_LEN_V0 + 1;
 
-        while offset < data.len() {
-            let voter_key = Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap());
-            let weight = u32::from_le_bytes(data[offset + 32..offset + 36].try_into().unwrap());
-            let voted = data[offset + 36] != 0;
-            let vote = u32::from_le_bytes(data[offset + 37..offset + 41].try_into().unwrap());
+        let chairperson = read_pubkey(take(data, 32)?, 0)?;
+        let min_participation_weight = u32::from_le_bytes(take(data, 4)?.try_into().unwrap());
+        let title = take(data, 64)?.try_into().unwrap();
+        let decimals = take(data, 1)?[0];
 
-            voters.push((
-                voter_key,
-                Voter {
-                    weight,
-                    voted,
-                    vote,
-                },
-            ));
+        let voter_count = u32::from_le_bytes(take(data, 4)?.try_into().unwrap());
+        let mut voters = Vec::new();
+        for _ in 0..voter_count {
+            let voter_key = read_pubkey(take(data, 32)?, 0)?;
+            let (weight, rest) = take(data, VOTER_LEN)?.split_at(4);
+            let mut bytes = weight.to_vec();
+            bytes.resize(8, 0);
+            bytes.extend_from_slice(rest);
+            bytes.resize(Voter::LEN, 0);
+            voters.push((voter_key, Voter::from_bytes(&bytes)?));
+        }
 
-            offset += 41;
+        if take(data, 4)? != PROPOSALS_SEPARATOR {
+            return Err(ProgramError::InvalidAccountData);
         }
 
+        // Trailing bytes too few for a whole record, such as slack left in the
+        // account for rent exemption, are ignored
         let mut proposals = Vec::new();
-        offset += 4; // Skip the winning proposal index
-
-        while offset < data.len() {
-            let name = data[offset..offset + 32].try_into().unwrap();
-            let vote_count = u32::from_le_bytes(data[offset + 32..offset + 36].try_into().unwrap());
+        while data.len() >= PROPOSAL_LEN {
+            let widen = |count: &[u8]| {
+                let mut bytes = [0; COUNT_LEN];
+                bytes[..count.len()].copy_from_slice(count);
+                bytes
+            };
+            let record = take(data, PROPOSAL_LEN)?;
+            let (name, rest) = record.split_at(32);
+            let (vote_count, rest) = rest.split_at(COUNT_LEN_V0);
+            let (nonce, rest) = rest.split_at(8);
+            let (max_expected_votes, reject_unexpected) = rest.split_at(COUNT_LEN_V0);
 
-            proposals.push(Proposal { name, vote_count });
-
-            offset += 36;
+            let mut bytes = name.to_vec();
+            bytes.extend_from_slice(&widen(vote_count));
+            bytes.extend_from_slice(nonce);
+            bytes.extend_from_slice(&widen(max_expected_votes));
+            bytes.extend_from_slice(reject_unexpected);
+            bytes.resize(Proposal::LEGACY_LEN, 0);
+            proposals.push(Proposal::from_legacy_bytes(&bytes)?);
         }
 
+        // Ballots of this layout had no way to hold voting back, so they are
+        // read as started
         Ok(SimpleVotingSystem {
             chairperson,
+            min_participation_weight,
+            title,
+            decimals,
+            results_sealed: false,
+            snapshot_slot: 0,
+            voting_started: true,
+            created_slot: 0,
+            end_timestamp: 0,
+            quorum: 0,
+            abstain_weight: 0,
+            gov_mint: Pubkey::default(),
             voters,
             proposals,
         })
     }
 
-    fn to_bytes(&self) -> Vec<u8> {
-        let mut bytes = Vec::new();
-        bytes.extend_from_slice(&self.chairperson.to_bytes());
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.try_to_vec().unwrap()
+    }
 
-        for (voter_key, voter) in &self.voters {
-            bytes.extend_from_slice(&voter_key.to_bytes());
-            bytes.extend_from_slice(&voter.weight.to_le_bytes());
-            bytes.push(voter.voted as u8);
-            bytes.extend_from_slice(&voter.vote.to_le_bytes());
+    /// Total weight of the voters on the roll who have voted, abstentions
+    /// included, and their number. Delegated weight is counted through the
+    /// delegate, and abstained weight through `abstain_weight`.
+    fn participation(&self) -> (VoteTotal, u32) {
+        let mut weight_voted = VoteTotal::from(self.abstain_weight);
+        let mut voters_voted: u32 = 0;
+        for (_, voter) in self.voters.iter().filter(|(_, voter)| voter.voted) {
+            if !voter.has_delegated() && voter.vote != ABSTAIN {
+                weight_voted += VoteTotal::from(voter.weight);
+            }
+            voters_voted += 1;
         }
 
-        bytes.extend_from_slice(&(self.proposals.len() as u32).to_le_bytes());
+        (weight_voted, voters_voted)
+    }
+
+    /// Total weight of the voters on the roll who have not voted yet.
+    fn outstanding_weight(&self) -> Result<VoteTotal, ProgramError> {
+        self.voters
+            .iter()
+            .filter(|(_, voter)| !voter.voted)
+            .try_fold(0, |total: VoteTotal, (_, voter)| {
+                total
+                    .checked_add(VoteTotal::from(voter.weight))
+                    .ok_or(ProgramError::InvalidAccountData)
+            })
+    }
 
-        for proposal in &self.proposals {
-            bytes.extend_from_slice(&proposal.to_bytes());
+    /// Mirrors `voter` in the roll under `key`, adding an entry for a voter the
+    /// roll does not list yet.
+    pub fn record_voter(&mut self, key: &Pubkey, voter: &Voter) {
+        match self
+            .voters
+            .iter_mut()
+            .find(|(voter_key, _)| voter_key == key)
+        {
+            Some((_, entry)) => *entry = voter.clone(),
+            None => self.voters.push((*key, voter.clone())),
         }
+    }
 
-        bytes
+    /// Fails with `QuorumNotMet` while the votes for `proposals` fall short of
+    /// the ballot's quorum. Abstentions count toward the quorum.
+    fn check_ballot_quorum(&self, proposals: &[Proposal]) -> ProgramResult {
+        let total_votes = vote_total(proposals)?
+            .checked_add(VoteTotal::from(self.abstain_weight))
+            .ok_or(ProgramError::InvalidAccountData)?;
+        if total_votes < VoteTotal::from(self.quorum) {
+            msg!("Quorum not met: {} of {} votes", total_votes, self.quorum);
+            return Err(VotingError::QuorumNotMet.into());
+        }
+
+        Ok(())
+    }
+
+    /// Fails unless `signer` is the chairperson and signed the transaction.
+    fn check_chairperson(&self, signer: &AccountInfo) -> ProgramResult {
+        if !signer.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if *signer.key != self.chairperson {
+            return Err(VotingError::NotChairperson.into());
+        }
+
+        Ok(())
+    }
+
+    /// Fails unless votes can currently be cast: voting has to have started,
+    /// the results must not be sealed and the deadline must not have passed.
+    fn check_voting_open(&self) -> ProgramResult {
+        if !self.voting_started {
+            return Err(VotingError::VotingNotStarted.into());
+        }
+        if self.results_sealed {
+            return Err(ProgramError::InvalidArgument);
+        }
+        if self.end_timestamp != 0 && Clock::get()?.unix_timestamp > self.end_timestamp {
+            msg!("Voting closed at {}", self.end_timestamp);
+            return Err(VotingError::VotingClosed.into());
+        }
+
+        Ok(())
+    }
+
+    /// Fails with `PollNotFinished` until the results are sealed or the
+    /// deadline has passed.
+    fn check_finished(&self) -> ProgramResult {
+        let deadline_passed =
+            self.end_timestamp != 0 && Clock::get()?.unix_timestamp > self.end_timestamp;
+        if !deadline_passed && !self.results_sealed {
+            return Err(VotingError::PollNotFinished.into());
+        }
+
+        Ok(())
+    }
+
+    /// Fails once the snapshot slot has passed, for instructions that change
+    /// voting weights.
+    fn check_weights_unfrozen(&self) -> ProgramResult {
+        if self.snapshot_slot != 0 && Clock::get()?.slot > self.snapshot_slot {
+            msg!("Weights are frozen since slot {}", self.snapshot_slot);
+            return Err(VotingError::WeightsFrozen.into());
+        }
+
+        Ok(())
+    }
+
+    /// Serializes the state into the front of an account's data, leaving any
+    /// spare capacity after it untouched.
+    fn write_into(&self, data: &mut [u8]) -> ProgramResult {
+        let bytes = self.to_bytes();
+        if data.len() < bytes.len() {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+        data[..bytes.len()].copy_from_slice(&bytes);
+
+        Ok(())
     }
 }
@@ -0,0 +1,114 @@
+//! Abstained weight counts toward the vote total and the participation, and
+//! delegated weight is counted once, through the delegate.
+
+mod common;
+
+use common::{process, return_data, Ballot, TestAccount};
+use solana_voting_program::{
+    Proposal, VoteTotal, VotingError, ABSTAIN, IX_DELEGATE, IX_QUORUM_PROGRESS, IX_TOTAL_VOTES,
+};
+
+fn named(name: u8) -> Proposal {
+    Proposal {
+        name: vec![name; 32],
+        vote_count: 0,
+        nonce: 0,
+        max_expected_votes: 0,
+        reject_unexpected: false,
+        desc_hash: [0; 32],
+    }
+}
+
+/// An open ballot holding proposals `a` and `b`, with room for five voters on
+/// the roll.
+fn ballot() -> Ballot {
+    Ballot::new(vec![named(b'a'), named(b'b')], 5)
+}
+
+impl Ballot {
+    fn delegate(&mut self, from: &mut TestAccount, to: &mut TestAccount) {
+        let mut proposals = self.state_alias();
+        process(
+            &self.program_id,
+            &mut [from, to, &mut self.system, &mut proposals],
+            &[IX_DELEGATE],
+        )
+        .unwrap();
+    }
+
+    fn total_votes(&mut self) -> VoteTotal {
+        let mut proposals = self.state_alias();
+        process(
+            &self.program_id,
+            &mut [&mut self.system, &mut proposals],
+            &[IX_TOTAL_VOTES],
+        )
+        .unwrap();
+
+        let data = return_data();
+        VoteTotal::from_le_bytes(data[1..].try_into().unwrap())
+    }
+
+    fn participation(&mut self) -> (VoteTotal, u32) {
+        let mut progress = vec![IX_QUORUM_PROGRESS];
+        progress.extend_from_slice(&0u64.to_le_bytes());
+        progress.extend_from_slice(&0u32.to_le_bytes());
+        process(&self.program_id, &mut [&mut self.system], &progress).unwrap();
+
+        let data = return_data();
+        let (weight, rest) = data.split_at(std::mem::size_of::<VoteTotal>());
+        (
+            VoteTotal::from_le_bytes(weight.try_into().unwrap()),
+            u32::from_le_bytes(rest[..4].try_into().unwrap()),
+        )
+    }
+}
+
+#[test]
+fn abstentions_count_toward_the_total_and_the_participation() {
+    let mut ballot = ballot();
+    let mut backer = ballot.voter(3);
+    let mut abstainer = ballot.voter(4);
+    ballot.vote(&mut backer, 0).unwrap();
+    ballot.vote(&mut abstainer, ABSTAIN).unwrap();
+
+    assert_eq!(ballot.total_votes(), 7);
+    assert_eq!(ballot.participation(), (7, 2));
+}
+
+#[test]
+fn only_the_abstain_index_lies_past_the_proposals() {
+    let mut ballot = ballot();
+    let mut voter = ballot.voter(3);
+
+    // The ballot holds two proposals, so index 3 is out of range
+    assert_eq!(
+        ballot.vote(&mut voter, 3),
+        Err(VotingError::InvalidProposalIndex.into())
+    );
+    assert_eq!(ballot.total_votes(), 0);
+
+    ballot.vote(&mut voter, ABSTAIN).unwrap();
+    assert_eq!(ballot.total_votes(), 3);
+}
+
+#[test]
+fn delegated_weight_is_counted_once() {
+    let mut ballot = ballot();
+    let mut early = ballot.voter(3);
+    let mut late = ballot.voter(1);
+    let mut to_early = ballot.voter(2);
+    let mut to_late = ballot.voter(5);
+    let mut abstainer = ballot.voter(4);
+
+    // One delegate has voted already, the other votes after receiving the
+    // weight
+    ballot.vote(&mut early, 0).unwrap();
+    ballot.delegate(&mut to_early, &mut early);
+    ballot.delegate(&mut to_late, &mut late);
+    ballot.vote(&mut late, 1).unwrap();
+    ballot.vote(&mut abstainer, ABSTAIN).unwrap();
+
+    assert_eq!(ballot.total_votes(), 15);
+    assert_eq!(ballot.participation(), (15, 5));
+}
@@ -0,0 +1,116 @@
+//! The dispatcher checks each instruction's minimum account count before
+//! running its handler, and logs which instruction was short.
+
+mod common;
+
+use std::mem::size_of;
+
+use common::{logs, process, TestAccount};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use solana_voting_program::*;
+
+/// Every instruction tag, with the length of a payload it accepts and the
+/// fewest accounts it runs with.
+const MIN_ACCOUNTS: [(u8, usize, usize); 49] = [
+    (IX_GIVE_RIGHT, 0, 3),
+    (IX_VOTE, 4, 3),
+    (IX_WINNING_PROPOSAL, 0, 2),
+    (IX_WINNER_NAME, 0, 1),
+    (IX_INITIALIZE, 16, 2),
+    (IX_DELEGATE, 0, 3),
+    (IX_CHANGE_VOTE, 8, 4),
+    (IX_ADD_PROPOSAL, 0, 2),
+    (IX_CLOSE_POLL, 0, 3),
+    (IX_PROPOSAL_RANK, 4, 1),
+    (IX_VERIFY_RECEIPT, 36, 1),
+    (IX_APPROVAL_VOTE, 4, 3),
+    (IX_LEADERBOARD, 8, 2),
+    (IX_TOTAL_VOTES, 0, 2),
+    (IX_ARCHIVE_RESULTS, 0, 4),
+    (IX_OVERTAKE_GAP, 4, 1),
+    (IX_DUMP_PROPOSALS, 4, 1),
+    (IX_SET_TITLE, 64, 2),
+    (IX_GET_TITLE, 0, 1),
+    (IX_IS_DECIDED, 0, 2),
+    (IX_UNVOTED_VOTERS, 8, 1),
+    (IX_SET_EXPECTED_VOTES, 5 + size_of::<VoteCount>(), 3),
+    (IX_RECENT_VELOCITY, 8, 1),
+    (IX_BATCH_REVOKE, 0, 2),
+    (IX_SET_DECIMALS, 1, 2),
+    (IX_MIGRATE, 0, 3),
+    (IX_SEAL_RESULTS, 0, 2),
+    (IX_CHAIRPERSON_VOTER, 0, 1),
+    (IX_QUORUM_PROGRESS, 12, 1),
+    (IX_WRITE_IN_VOTE, 0, 5),
+    (IX_CONFIG, 4, 1),
+    (IX_WEIGHT_STATS, 0, 1),
+    (IX_SET_SNAPSHOT_SLOT, 8, 2),
+    (IX_CONTESTED_COUNT, 0, 1),
+    (IX_START_VOTING, 0, 2),
+    (IX_MEDIAN_WEIGHT, 0, 1),
+    (IX_RACE_STATUS, 12, 2),
+    (IX_COMPACT_ROLL, 0, 2),
+    (IX_WINNER_SHARE, 0, 1),
+    (IX_NEEDS_RUNOFF, 2, 1),
+    (IX_GET_PROPOSAL, 4, 1),
+    (IX_VERIFY_DESCRIPTION, 4, 1),
+    (IX_REMAINING_CHOICES, 0, 1),
+    (IX_DUMP_ROLL, 8, 1),
+    (IX_CREATED_AT, 0, 1),
+    (IX_SET_GOV_MINT, 32, 2),
+    (IX_VOTE_WITH_TOKEN, 4, 5),
+    (IX_REDELEGATE, 0, 4),
+    (IX_DELEGATE_TO_CHAIR, 0, 3),
+];
+
+fn run(tag: u8, payload_len: usize, accounts: usize) -> Result<(), ProgramError> {
+    let program_id = Pubkey::new_unique();
+    let mut accounts: Vec<TestAccount> = (0..accounts).map(|_| TestAccount::signer()).collect();
+    let mut accounts: Vec<&mut TestAccount> = accounts.iter_mut().collect();
+    let mut instruction_data = vec![tag];
+    instruction_data.resize(1 + payload_len, 0);
+    process(&program_id, &mut accounts, &instruction_data)
+}
+
+#[test]
+fn zero_accounts_are_rejected_for_every_instruction() {
+    for (tag, payload_len, min) in MIN_ACCOUNTS {
+        assert_eq!(
+            run(tag, payload_len, 0),
+            Err(ProgramError::NotEnoughAccountKeys),
+            "instruction {}",
+            tag
+        );
+        let expected = format!(
+            "Instruction {} expects at least {} accounts, got 0",
+            tag, min
+        );
+        assert!(logs().contains(&expected), "instruction {}", tag);
+    }
+}
+
+#[test]
+fn one_account_short_is_rejected() {
+    for (tag, payload_len, min) in MIN_ACCOUNTS {
+        assert_eq!(
+            run(tag, payload_len, min - 1),
+            Err(ProgramError::NotEnoughAccountKeys),
+            "instruction {}",
+            tag
+        );
+    }
+}
+
+#[test]
+fn minimum_accounts_reach_the_handler() {
+    for (tag, payload_len, min) in MIN_ACCOUNTS {
+        // The accounts are blank, so the handler fails on their contents
+        // rather than their count
+        assert_ne!(
+            run(tag, payload_len, min),
+            Err(ProgramError::NotEnoughAccountKeys),
+            "instruction {}",
+            tag
+        );
+    }
+}
@@ -0,0 +1,47 @@
+//! Instructions given the state account again in place of the proposal list
+//! read and write it without holding a borrow across each other.
+
+mod common;
+
+use common::{open_ballot, process_infos, proposal, return_data, TestAccount};
+use solana_program::pubkey::Pubkey;
+use solana_voting_program::{SimpleVotingSystem, IX_SEAL_RESULTS, IX_WINNING_PROPOSAL};
+
+/// A state account holding proposals with 1 and 4 votes.
+fn state(program_id: Pubkey, chairperson: Pubkey) -> TestAccount {
+    let mut ballot = open_ballot(chairperson);
+    ballot.proposals = vec![proposal(b"Parks", 1), proposal(b"Roads", 4)];
+    TestAccount::state(program_id, &ballot, 0)
+}
+
+#[test]
+fn winning_proposal_reads_the_state_passed_twice() {
+    let program_id = Pubkey::new_unique();
+    let mut system = state(program_id, Pubkey::new_unique());
+    let info = system.info();
+
+    process_infos(&program_id, &[info.clone(), info], &[IX_WINNING_PROPOSAL]).unwrap();
+
+    assert_eq!(return_data()[..4], 1u32.to_le_bytes());
+}
+
+#[test]
+fn seal_results_writes_the_state_passed_twice() {
+    let program_id = Pubkey::new_unique();
+    let mut chairperson = TestAccount::signer();
+    let mut system = state(program_id, chairperson.key);
+    let info = system.info();
+
+    process_infos(
+        &program_id,
+        &[chairperson.info(), info.clone(), info],
+        &[IX_SEAL_RESULTS],
+    )
+    .unwrap();
+
+    assert!(
+        SimpleVotingSystem::deserialize(&system.data)
+            .unwrap()
+            .results_sealed
+    );
+}
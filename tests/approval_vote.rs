@@ -0,0 +1,137 @@
+//! Approval ballots back several proposals at once, and are refused by the
+//! instructions built for single votes.
+
+mod common;
+
+use common::{process, proposal_list, Ballot, TestAccount};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use solana_voting_program::{
+    Proposal, VoteCount, Voter, APPROVAL, IX_APPROVAL_VOTE, IX_CHANGE_VOTE, IX_DELEGATE,
+};
+
+/// An open ballot with room for two voters on the roll, and the list account
+/// holding its three proposals.
+fn ballot_with_list() -> (Ballot, TestAccount) {
+    let ballot = Ballot::new(Vec::new(), 2);
+    let list = proposal_list(&[b'a', b'b', b'c'].map(|name| Proposal {
+        name: vec![name; 32],
+        vote_count: 0,
+        nonce: 0,
+        max_expected_votes: 0,
+        reject_unexpected: false,
+        desc_hash: [0; 32],
+    }));
+
+    let list = TestAccount::new(ballot.program_id, list);
+    (ballot, list)
+}
+
+impl Ballot {
+    fn approve(
+        &mut self,
+        voter: &mut TestAccount,
+        list: &mut TestAccount,
+        approvals: u32,
+    ) -> Result<(), ProgramError> {
+        let mut data = vec![IX_APPROVAL_VOTE];
+        data.extend_from_slice(&approvals.to_le_bytes());
+        process(
+            &self.program_id,
+            &mut [voter, &mut self.system, list],
+            &data,
+        )
+    }
+}
+
+fn counts(list: &TestAccount) -> Vec<VoteCount> {
+    list.data[4..]
+        .chunks(Proposal::LEN)
+        .map(|record| Proposal::from_bytes(record).unwrap().vote_count)
+        .collect()
+}
+
+#[test]
+fn approvals_are_recorded_apart_from_the_vote() {
+    let (mut ballot, mut list) = ballot_with_list();
+    let mut voter = ballot.voter(2);
+
+    ballot.approve(&mut voter, &mut list, 0b101).unwrap();
+
+    assert_eq!(counts(&list), vec![2, 0, 2]);
+    let record = Voter::from_bytes(&voter.data).unwrap();
+    assert!(record.voted);
+    assert_eq!(record.vote, APPROVAL);
+    assert_eq!(record.approvals, 0b101);
+}
+
+#[test]
+fn approval_ballot_cannot_be_changed() {
+    let (mut ballot, mut list) = ballot_with_list();
+    let mut voter = ballot.voter(1);
+    ballot.approve(&mut voter, &mut list, 0b011).unwrap();
+
+    let mut data = vec![IX_CHANGE_VOTE];
+    data.extend_from_slice(&APPROVAL.to_le_bytes());
+    data.extend_from_slice(&2u32.to_le_bytes());
+    let mut other_list = TestAccount::new(ballot.program_id, list.data.clone()).with_key(list.key);
+    assert_eq!(
+        process(
+            &ballot.program_id,
+            &mut [&mut voter, &mut list, &mut other_list, &mut ballot.system,],
+            &data,
+        ),
+        Err(ProgramError::InvalidArgument)
+    );
+    assert_eq!(counts(&list), vec![1, 1, 0]);
+}
+
+#[test]
+fn delegating_to_an_approval_voter_is_rejected() {
+    let (mut ballot, mut list) = ballot_with_list();
+    let mut delegate = ballot.voter(1);
+    let mut from = ballot.voter(4);
+    ballot.approve(&mut delegate, &mut list, 0b001).unwrap();
+
+    assert_eq!(
+        process(
+            &ballot.program_id,
+            &mut [&mut from, &mut delegate, &mut ballot.system, &mut list,],
+            &[IX_DELEGATE],
+        ),
+        Err(ProgramError::InvalidArgument)
+    );
+    assert_eq!(counts(&list), vec![1, 0, 0]);
+    assert!(!Voter::from_bytes(&from.data).unwrap().voted);
+}
+
+#[test]
+fn voter_passed_as_a_proposal_is_rejected() {
+    let mut ballot = Ballot::new(Vec::new(), 2);
+    let mut voter = ballot.voter(1);
+    let mut alias = TestAccount::new(ballot.program_id, voter.data.clone()).with_key(voter.key);
+
+    let mut data = vec![IX_APPROVAL_VOTE];
+    data.extend_from_slice(&1u32.to_le_bytes());
+    assert_eq!(
+        process(
+            &ballot.program_id,
+            &mut [&mut voter, &mut ballot.system, &mut alias],
+            &data,
+        ),
+        Err(ProgramError::InvalidAccountData)
+    );
+    assert!(!Voter::from_bytes(&voter.data).unwrap().voted);
+}
+
+#[test]
+fn proposals_owned_by_another_program_are_rejected() {
+    let (mut ballot, mut list) = ballot_with_list();
+    let mut voter = ballot.voter(1);
+    list.owner = Pubkey::new_unique();
+
+    assert_eq!(
+        ballot.approve(&mut voter, &mut list, 0b001),
+        Err(ProgramError::IncorrectProgramId)
+    );
+    assert!(!Voter::from_bytes(&voter.data).unwrap().voted);
+}
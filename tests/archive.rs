@@ -0,0 +1,164 @@
+//! Archives the results of a finished ballot into a compact record, once and
+//! only into the ballot's own archive account.
+
+mod common;
+
+use common::{archive_address, process, proposal, warp_to_slot, Ballot, TestAccount, NOW, SLOT};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use solana_voting_program::{
+    BallotArchive, SimpleVotingSystem, Voter, VotingError, IX_ARCHIVE_RESULTS,
+};
+
+/// A ballot where two of three voters voted and another abstained with a
+/// weight of 2, finished if `sealed`.
+fn tallied(sealed: bool) -> Ballot {
+    Ballot::with_state(0, |ballot| {
+        ballot.decimals = 3;
+        ballot.results_sealed = sealed;
+        ballot.abstain_weight = 2;
+        ballot.proposals = vec![proposal(b"Parks", 4), proposal(b"Roads", 6)];
+        let voted = Voter {
+            weight: 5,
+            voted: true,
+            ..Voter::default()
+        };
+        ballot.voters = vec![
+            (Pubkey::new_unique(), voted.clone()),
+            (Pubkey::new_unique(), Voter::default()),
+            (Pubkey::new_unique(), voted),
+        ];
+    })
+}
+
+impl Ballot {
+    fn update(&mut self, update: impl FnOnce(&mut SimpleVotingSystem)) {
+        let mut state = self.state();
+        update(&mut state);
+        let key = self.system.key;
+        self.system = TestAccount::state(self.program_id, &state, 0).with_key(key);
+    }
+
+    /// An archive account of `len` bytes at this ballot's archive address.
+    fn archive_account(&self, len: usize) -> TestAccount {
+        TestAccount::new(self.program_id, vec![0; len])
+            .with_key(archive_address(&self.program_id, &self.system.key))
+    }
+
+    fn archive(&mut self, archive: &mut TestAccount) -> Result<(), ProgramError> {
+        let mut proposals = self.state_alias();
+        process(
+            &self.program_id,
+            &mut [
+                &mut self.chairperson,
+                &mut self.system,
+                &mut proposals,
+                archive,
+            ],
+            &[IX_ARCHIVE_RESULTS],
+        )
+    }
+}
+
+fn expected_archive() -> BallotArchive {
+    BallotArchive {
+        winning_proposal: 1,
+        winner_name: b"Roads".to_vec(),
+        total_votes: 12,
+        participants: 2,
+        end_slot: SLOT,
+        decimals: 3,
+    }
+}
+
+#[test]
+fn archive_records_the_final_results() {
+    let mut ballot = tallied(true);
+    let mut archive = ballot.archive_account(BallotArchive::LEN);
+
+    ballot.archive(&mut archive).unwrap();
+
+    assert_eq!(
+        BallotArchive::from_bytes(&archive.data).unwrap(),
+        expected_archive()
+    );
+}
+
+#[test]
+fn ballot_past_its_deadline_is_archived() {
+    let mut ballot = tallied(false);
+    ballot.update(|state| state.end_timestamp = NOW - 1);
+    let mut archive = ballot.archive_account(BallotArchive::LEN);
+
+    ballot.archive(&mut archive).unwrap();
+
+    assert_eq!(
+        BallotArchive::from_bytes(&archive.data).unwrap(),
+        expected_archive()
+    );
+}
+
+#[test]
+fn unfinished_ballot_is_not_archived() {
+    let mut ballot = tallied(false);
+    ballot.update(|state| state.end_timestamp = NOW + 1);
+    let mut archive = ballot.archive_account(BallotArchive::LEN);
+
+    assert_eq!(
+        ballot.archive(&mut archive),
+        Err(VotingError::PollNotFinished.into())
+    );
+    assert!(archive.data.iter().all(|&byte| byte == 0));
+}
+
+#[test]
+fn archive_is_never_overwritten() {
+    let mut ballot = tallied(true);
+    let mut archive = ballot.archive_account(BallotArchive::LEN);
+    ballot.archive(&mut archive).unwrap();
+    let first = archive.data.clone();
+
+    warp_to_slot(SLOT + 10);
+    let rerun = ballot.archive(&mut archive);
+    warp_to_slot(SLOT);
+
+    assert_eq!(rerun, Err(ProgramError::AccountAlreadyInitialized));
+    assert_eq!(archive.data, first);
+}
+
+#[test]
+fn archive_of_another_ballot_is_rejected() {
+    let mut ballot = tallied(true);
+    let other = tallied(true);
+    let mut archive = other.archive_account(BallotArchive::LEN);
+
+    assert_eq!(
+        ballot.archive(&mut archive),
+        Err(ProgramError::InvalidArgument)
+    );
+    assert!(archive.data.iter().all(|&byte| byte == 0));
+}
+
+#[test]
+fn archive_owned_by_another_program_is_rejected() {
+    let mut ballot = tallied(true);
+    let mut archive = ballot.archive_account(BallotArchive::LEN);
+    archive.owner = Pubkey::new_unique();
+
+    assert_eq!(
+        ballot.archive(&mut archive),
+        Err(ProgramError::IncorrectProgramId)
+    );
+    assert!(archive.data.iter().all(|&byte| byte == 0));
+}
+
+#[test]
+fn archive_account_has_to_fit_the_record() {
+    let mut ballot = tallied(true);
+    let mut archive = ballot.archive_account(BallotArchive::LEN - 1);
+
+    assert_eq!(
+        ballot.archive(&mut archive),
+        Err(ProgramError::AccountDataTooSmall)
+    );
+    assert!(archive.data.iter().all(|&byte| byte == 0));
+}
@@ -0,0 +1,62 @@
+//! Revokes several voters at once, skipping those who have voted.
+
+mod common;
+
+use common::{process, proposal, return_data, Ballot, TestAccount};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use solana_voting_program::{Voter, IX_BATCH_REVOKE};
+
+/// An open ballot holding one proposal, with room for three voters on the
+/// roll.
+fn ballot() -> Ballot {
+    Ballot::new(vec![proposal(b"Parks", 0)], 3)
+}
+
+impl Ballot {
+    fn revoke(&mut self, voters: &mut [&mut TestAccount]) -> Result<(), ProgramError> {
+        let mut accounts: Vec<&mut TestAccount> = vec![&mut self.chairperson, &mut self.system];
+        accounts.extend(voters.iter_mut().map(|voter| &mut **voter));
+        process(&self.program_id, &mut accounts, &[IX_BATCH_REVOKE])
+    }
+}
+
+fn weight(voter: &TestAccount) -> u64 {
+    Voter::from_bytes(&voter.data).unwrap().weight
+}
+
+#[test]
+fn voters_who_voted_are_skipped_and_reported() {
+    let mut ballot = ballot();
+    let mut first = ballot.voter(1);
+    let mut second = ballot.voter(2);
+    let mut third = ballot.voter(3);
+    ballot.vote(&mut second, 0).unwrap();
+
+    ballot
+        .revoke(&mut [&mut first, &mut second, &mut third])
+        .unwrap();
+
+    assert_eq!(return_data(), 1u32.to_le_bytes());
+    assert_eq!([weight(&first), weight(&second), weight(&third)], [0, 2, 0]);
+    let roll = ballot.state().voters;
+    let weights: Vec<u64> = roll.iter().map(|(_, voter)| voter.weight).collect();
+    assert_eq!(weights, vec![0, 2, 0]);
+}
+
+#[test]
+fn voter_of_another_ballot_is_rejected() {
+    let mut ballot = ballot();
+    let mut own = ballot.voter(1);
+    let record = Voter {
+        weight: 4,
+        ballot: Pubkey::new_unique(),
+        ..Voter::default()
+    };
+    let mut foreign = TestAccount::new(ballot.program_id, record.to_bytes());
+
+    assert_eq!(
+        ballot.revoke(&mut [&mut own, &mut foreign]),
+        Err(ProgramError::InvalidArgument)
+    );
+    assert_eq!(weight(&foreign), 4);
+}
@@ -0,0 +1,57 @@
+//! A freshly initialized ballot puts its chairperson on the roll with a weight
+//! of one.
+
+mod common;
+
+use common::{process, return_data, Ballot, TestAccount};
+use solana_voting_program::{Voter, IX_CHAIRPERSON_VOTER, IX_INITIALIZE};
+
+/// A ballot initialized without a deadline, quorum or proposals, with room
+/// for one voter.
+fn initialized_ballot() -> Ballot {
+    let mut ballot = Ballot::new(Vec::new(), 1);
+    ballot.system.data.fill(0);
+
+    let mut initialize = vec![IX_INITIALIZE];
+    initialize.extend_from_slice(&[0; 16]);
+    process(
+        &ballot.program_id,
+        &mut [&mut ballot.chairperson, &mut ballot.system],
+        &initialize,
+    )
+    .unwrap();
+
+    ballot
+}
+
+impl Ballot {
+    fn chairperson_voter(&mut self) -> Vec<u8> {
+        process(
+            &self.program_id,
+            &mut [&mut self.system],
+            &[IX_CHAIRPERSON_VOTER],
+        )
+        .unwrap();
+        return_data()
+    }
+}
+
+#[test]
+fn chairperson_missing_from_the_roll_returns_nothing() {
+    let mut ballot = initialized_ballot();
+
+    assert!(ballot.chairperson_voter().is_empty());
+}
+
+#[test]
+fn chairperson_record_is_returned_once_granted() {
+    let mut ballot = initialized_ballot();
+    let mut record =
+        TestAccount::new(ballot.program_id, vec![0; Voter::LEN]).with_key(ballot.chairperson.key);
+    ballot.grant(&mut record, 3).unwrap();
+
+    let voter = Voter::from_bytes(&ballot.chairperson_voter()).unwrap();
+    assert_eq!(voter.weight, 3);
+    assert_eq!(voter.ballot, ballot.system.key);
+    assert!(!voter.voted);
+}
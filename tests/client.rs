@@ -0,0 +1,85 @@
+//! Checks that the client instruction builders produce instructions the
+//! program accepts.
+
+#![cfg(feature = "client")]
+
+mod common;
+
+use common::{open_ballot, process, proposal_address, TestAccount};
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+use solana_voting_program::{
+    client::{
+        change_vote_ix, close_poll_ix, delegate_ix, give_right_to_vote_ix, initialize_ix, vote_ix,
+    },
+    Proposal, Voter, IX_CHANGE_VOTE, IX_DELEGATE, IX_INITIALIZE, ROLL_ENTRY_LEN,
+};
+
+/// Runs `instruction` against `accounts`, which have to match its account
+/// metas in order.
+fn run(instruction: &Instruction, accounts: &mut [&mut TestAccount]) {
+    assert_eq!(instruction.accounts.len(), accounts.len());
+    for (meta, account) in instruction.accounts.iter().zip(accounts.iter()) {
+        assert_eq!(meta.pubkey, account.key);
+        assert_eq!(meta.is_signer, account.is_signer);
+    }
+
+    process(&instruction.program_id, accounts, &instruction.data).unwrap();
+}
+
+#[test]
+fn built_grant_and_vote_are_accepted() {
+    let program_id = Pubkey::new_unique();
+    let mut chairperson = TestAccount::signer();
+    let mut system = TestAccount::state(program_id, &open_ballot(chairperson.key), ROLL_ENTRY_LEN);
+    let mut voter = TestAccount::voter(program_id);
+    let record = Proposal {
+        name: vec![b'x'; 32],
+        vote_count: 0,
+        nonce: 0,
+        max_expected_votes: 0,
+        reject_unexpected: false,
+        desc_hash: [0; 32],
+    };
+    let mut proposal = TestAccount::new(program_id, record.to_bytes()).with_key(proposal_address(
+        &program_id,
+        &system.key,
+        0,
+    ));
+
+    // The grant does not need the voter's signature, only the vote does
+    voter.is_signer = false;
+    let grant = give_right_to_vote_ix(&program_id, &chairperson.key, &system.key, &voter.key, 3);
+    run(&grant, &mut [&mut chairperson, &mut system, &mut voter]);
+
+    voter.is_signer = true;
+    let vote = vote_ix(&program_id, &voter.key, &proposal.key, &system.key, 0);
+    run(&vote, &mut [&mut voter, &mut proposal, &mut system]);
+
+    assert_eq!(Proposal::from_bytes(&proposal.data).unwrap().vote_count, 3);
+    assert!(Voter::from_bytes(&voter.data).unwrap().voted);
+}
+
+#[test]
+fn payloads_follow_the_wire_format() {
+    let program_id = Pubkey::new_unique();
+    let key = Pubkey::new_unique();
+
+    let initialize = initialize_ix(&program_id, &key, &key, -1, 7, &[b"Parks", &[2; 40]]);
+    assert_eq!(initialize.data[0], IX_INITIALIZE);
+    assert_eq!(initialize.data[1..9], (-1i64).to_le_bytes());
+    assert_eq!(initialize.data[9..17], 7u64.to_le_bytes());
+    assert_eq!(initialize.data[17..24], *b"\x05\x00Parks");
+    assert_eq!(initialize.data[24..26], 40u16.to_le_bytes());
+    assert_eq!(initialize.data.len(), 1 + 16 + 7 + 42);
+
+    let delegate = delegate_ix(&program_id, &key, &key, &key, None, Some(2));
+    assert_eq!(delegate.data, [IX_DELEGATE, 2, 0, 0, 0, 0, 0, 0, 0]);
+
+    let change = change_vote_ix(&program_id, &key, &key, &key, &key, 2, 5);
+    assert_eq!(change.data, [IX_CHANGE_VOTE, 2, 0, 0, 0, 5, 0, 0, 0]);
+
+    let closed = [Pubkey::new_unique(), Pubkey::new_unique()];
+    let close = close_poll_ix(&program_id, &key, &key, &key, &closed);
+    assert_eq!(close.accounts.len(), 5);
+    assert!(close.accounts[3..].iter().all(|meta| meta.is_writable));
+}
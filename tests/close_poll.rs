@@ -0,0 +1,161 @@
+//! Closing a finished poll reclaims its state and voter records, and refuses
+//! accounts belonging to any other ballot.
+
+mod common;
+
+use common::{open_ballot, process, proposal_address, token_voter_address, TestAccount};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use solana_voting_program::{Proposal, Voter, IX_CLOSE_POLL, TOKEN_VOTER_LEN};
+
+struct Poll {
+    program_id: Pubkey,
+    chairperson: TestAccount,
+    system: TestAccount,
+    destination: TestAccount,
+}
+
+impl Poll {
+    /// A poll whose results are sealed.
+    fn sealed(program_id: Pubkey) -> Self {
+        let chairperson = TestAccount::signer();
+        let mut ballot = open_ballot(chairperson.key);
+        ballot.results_sealed = true;
+
+        Poll {
+            program_id,
+            system: TestAccount::state(program_id, &ballot, 0),
+            chairperson,
+            destination: TestAccount::new(Pubkey::default(), Vec::new()),
+        }
+    }
+
+    /// A voter record granted on this poll.
+    fn voter(&self) -> TestAccount {
+        let voter = Voter {
+            weight: 1,
+            ballot: self.system.key,
+            ..Voter::default()
+        };
+        TestAccount::new(self.program_id, voter.to_bytes())
+    }
+
+    fn close(&mut self, closed: &mut [&mut TestAccount]) -> Result<(), ProgramError> {
+        let mut accounts: Vec<&mut TestAccount> = vec![
+            &mut self.chairperson,
+            &mut self.system,
+            &mut self.destination,
+        ];
+        accounts.extend(closed.iter_mut().map(|account| &mut **account));
+        process(&self.program_id, &mut accounts, &[IX_CLOSE_POLL])
+    }
+}
+
+#[test]
+fn own_state_and_voter_records_are_closed() {
+    let program_id = Pubkey::new_unique();
+    let mut poll = Poll::sealed(program_id);
+    let mut voter = poll.voter();
+    let holder = Pubkey::new_unique();
+    let mut record = vec![0; TOKEN_VOTER_LEN];
+    record[Voter::LEN..].copy_from_slice(holder.as_ref());
+    let mut token_voter = TestAccount::new(program_id, record).with_key(token_voter_address(
+        &program_id,
+        &poll.system.key,
+        &holder,
+    ));
+    let mut state =
+        TestAccount::new(program_id, poll.system.data.clone()).with_key(poll.system.key);
+    let expected =
+        poll.destination.lamports + voter.lamports + token_voter.lamports + state.lamports;
+
+    poll.close(&mut [&mut voter, &mut token_voter, &mut state])
+        .unwrap();
+
+    assert_eq!(poll.destination.lamports, expected);
+    assert_eq!(voter.lamports, 0);
+    assert!(voter.data.iter().all(|&byte| byte == 0));
+    assert_eq!(token_voter.lamports, 0);
+    assert_eq!(state.lamports, 0);
+}
+
+#[test]
+fn oversized_voter_records_are_closed() {
+    let program_id = Pubkey::new_unique();
+    let mut poll = Poll::sealed(program_id);
+    let mut voter = poll.voter();
+    voter.data.resize(Voter::LEN + 64, 0xAB);
+    let expected = poll.destination.lamports + voter.lamports;
+
+    poll.close(&mut [&mut voter]).unwrap();
+
+    assert_eq!(poll.destination.lamports, expected);
+    assert_eq!(voter.lamports, 0);
+    assert!(voter.data.iter().all(|&byte| byte == 0));
+}
+
+#[test]
+fn foreign_ballot_accounts_are_rejected() {
+    let program_id = Pubkey::new_unique();
+    let victim = Poll::sealed(program_id);
+    let mut attacker = Poll::sealed(program_id);
+
+    let mut victim_state =
+        TestAccount::new(program_id, victim.system.data.clone()).with_key(victim.system.key);
+    let mut victim_voter = victim.voter();
+    let lamports = (victim_state.lamports, victim_voter.lamports);
+    let destination_lamports = attacker.destination.lamports;
+
+    assert_eq!(
+        attacker.close(&mut [&mut victim_state]),
+        Err(ProgramError::InvalidArgument)
+    );
+    assert_eq!(
+        attacker.close(&mut [&mut victim_voter]),
+        Err(ProgramError::InvalidArgument)
+    );
+    assert_eq!((victim_state.lamports, victim_voter.lamports), lamports);
+    assert_eq!(attacker.destination.lamports, destination_lamports);
+}
+
+#[test]
+fn token_voter_record_of_another_ballot_is_rejected() {
+    let program_id = Pubkey::new_unique();
+    let mut poll = Poll::sealed(program_id);
+    let holder = Pubkey::new_unique();
+    let mut record = vec![0; TOKEN_VOTER_LEN];
+    record[Voter::LEN..].copy_from_slice(holder.as_ref());
+    let mut token_voter = TestAccount::new(program_id, record).with_key(token_voter_address(
+        &program_id,
+        &Pubkey::new_unique(),
+        &holder,
+    ));
+
+    assert_eq!(
+        poll.close(&mut [&mut token_voter]),
+        Err(ProgramError::InvalidArgument)
+    );
+}
+
+#[test]
+fn proposal_accounts_are_rejected() {
+    let program_id = Pubkey::new_unique();
+    let mut poll = Poll::sealed(program_id);
+    let record = Proposal {
+        name: vec![b'x'; 32],
+        vote_count: 0,
+        nonce: 0,
+        max_expected_votes: 0,
+        reject_unexpected: false,
+        desc_hash: [0; 32],
+    };
+    let mut proposal = TestAccount::new(program_id, record.to_bytes()).with_key(proposal_address(
+        &program_id,
+        &poll.system.key,
+        0,
+    ));
+
+    assert_eq!(
+        poll.close(&mut [&mut proposal]),
+        Err(ProgramError::InvalidArgument)
+    );
+}
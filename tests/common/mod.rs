@@ -0,0 +1,335 @@
+//! In-process harness for running instructions through the program
+//! entrypoint with hand-built accounts. `Clock` and `Rent` are served by
+//! syscall stubs: the clock stands at [`NOW`] and at [`SLOT`] unless a test
+//! moves it with [`warp_to_slot`], and rent uses the default parameters. The
+//! return data and the log messages of the last instruction run on the
+//! current thread are kept for [`return_data`] and [`logs`].
+
+#![allow(dead_code)]
+
+use std::{
+    cell::{Cell, RefCell},
+    sync::Once,
+};
+
+use solana_program::{
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::{ProgramResult, SUCCESS},
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+    pubkey::Pubkey,
+    rent::Rent,
+};
+use solana_voting_program::{
+    process_instruction, Proposal, SimpleVotingSystem, VoteCount, Voter, IX_GIVE_RIGHT, IX_VOTE,
+    PROPOSAL_LIST_TAG, ROLL_ENTRY_LEN,
+};
+
+/// Slot reported by the stubbed clock.
+pub const SLOT: u64 = 1_000;
+/// Unix timestamp reported by the stubbed clock.
+pub const NOW: i64 = 1_700_000_000;
+
+thread_local! {
+    // Tests run on threads of their own, so each sees only its own results
+    static RETURN_DATA: RefCell<Vec<u8>> = const { RefCell::new(Vec::new()) };
+    static LOGS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    static CLOCK_SLOT: Cell<u64> = const { Cell::new(SLOT) };
+}
+
+struct TestStubs;
+
+impl SyscallStubs for TestStubs {
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = Clock {
+            slot: CLOCK_SLOT.with(Cell::get),
+            unix_timestamp: NOW,
+            ..Clock::default()
+        };
+        unsafe { *(var_addr as *mut Clock) = clock };
+        SUCCESS
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { *(var_addr as *mut Rent) = Rent::default() };
+        SUCCESS
+    }
+
+    fn sol_log(&self, message: &str) {
+        LOGS.with(|logs| logs.borrow_mut().push(message.to_string()));
+    }
+
+    fn sol_set_return_data(&mut self, data: &[u8]) {
+        RETURN_DATA.with(|return_data| *return_data.borrow_mut() = data.to_vec());
+    }
+}
+
+/// An account owned by the test, lent to the program as an `AccountInfo`.
+pub struct TestAccount {
+    pub key: Pubkey,
+    pub is_signer: bool,
+    pub lamports: u64,
+    pub data: Vec<u8>,
+    pub owner: Pubkey,
+}
+
+impl TestAccount {
+    /// A rent-exempt account holding `data`.
+    pub fn new(owner: Pubkey, data: Vec<u8>) -> Self {
+        TestAccount {
+            key: Pubkey::new_unique(),
+            is_signer: false,
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner,
+        }
+    }
+
+    /// A blank voter account owned by the program, signed for by its holder.
+    pub fn voter(program_id: Pubkey) -> Self {
+        TestAccount {
+            is_signer: true,
+            ..Self::new(program_id, vec![0; Voter::LEN])
+        }
+    }
+
+    /// A state account owned by the program with room for `spare` more bytes.
+    pub fn state(program_id: Pubkey, system: &SimpleVotingSystem, spare: usize) -> Self {
+        let mut data = system.to_bytes();
+        data.resize(data.len() + spare, 0);
+        Self::new(program_id, data)
+    }
+
+    /// A wallet owned by the system program that signs the transaction.
+    pub fn signer() -> Self {
+        TestAccount {
+            is_signer: true,
+            ..Self::new(Pubkey::default(), Vec::new())
+        }
+    }
+
+    pub fn with_key(self, key: Pubkey) -> Self {
+        TestAccount { key, ..self }
+    }
+
+    pub fn info(&mut self) -> AccountInfo<'_> {
+        AccountInfo::new(
+            &self.key,
+            self.is_signer,
+            true,
+            &mut self.lamports,
+            &mut self.data,
+            &self.owner,
+            false,
+            0,
+        )
+    }
+}
+
+/// An open ballot chaired by `chairperson`, with no voters or proposals.
+pub fn open_ballot(chairperson: Pubkey) -> SimpleVotingSystem {
+    SimpleVotingSystem {
+        chairperson,
+        min_participation_weight: 0,
+        title: [0; 64],
+        decimals: 0,
+        results_sealed: false,
+        snapshot_slot: 0,
+        voting_started: true,
+        created_slot: 0,
+        end_timestamp: 0,
+        quorum: 0,
+        abstain_weight: 0,
+        gov_mint: Pubkey::default(),
+        voters: Vec::new(),
+        proposals: Vec::new(),
+    }
+}
+
+/// A proposal named `name` that has received `vote_count` votes.
+pub fn proposal(name: &[u8], vote_count: VoteCount) -> Proposal {
+    Proposal {
+        name: name.to_vec(),
+        vote_count,
+        nonce: 0,
+        max_expected_votes: 0,
+        reject_unexpected: false,
+        desc_hash: [0; 32],
+    }
+}
+
+/// The data of a proposal list account holding `proposals`.
+pub fn proposal_list(proposals: &[Proposal]) -> Vec<u8> {
+    let mut data = PROPOSAL_LIST_TAG.to_vec();
+    for proposal in proposals {
+        data.extend_from_slice(&proposal.to_bytes());
+    }
+    data
+}
+
+/// A roll entry for a new voter of `weight`, who has voted for proposal 0 if
+/// `voted`.
+pub fn roll_entry(weight: u64, voted: bool) -> (Pubkey, Voter) {
+    let voter = Voter {
+        weight,
+        voted,
+        ..Voter::default()
+    };
+    (Pubkey::new_unique(), voter)
+}
+
+/// A ballot set up for a test: the program, the chairperson, who signs, and
+/// the state account. Tests needing more accounts keep them alongside.
+pub struct Ballot {
+    pub program_id: Pubkey,
+    pub chairperson: TestAccount,
+    pub system: TestAccount,
+}
+
+impl Ballot {
+    /// An open ballot holding `proposals`, with room for `voters` voters on
+    /// the roll.
+    pub fn new(proposals: Vec<Proposal>, voters: usize) -> Self {
+        Self::with_state(voters, |state| state.proposals = proposals)
+    }
+
+    /// An open ballot without proposals whose state is first adjusted by
+    /// `setup`, with room for `voters` voters on the roll.
+    pub fn with_state(voters: usize, setup: impl FnOnce(&mut SimpleVotingSystem)) -> Self {
+        let program_id = Pubkey::new_unique();
+        let chairperson = TestAccount::signer();
+        let mut state = open_ballot(chairperson.key);
+        setup(&mut state);
+
+        Ballot {
+            program_id,
+            system: TestAccount::state(program_id, &state, voters * ROLL_ENTRY_LEN),
+            chairperson,
+        }
+    }
+
+    /// Grants `voter` the right to vote with `weight`.
+    pub fn grant(&mut self, voter: &mut TestAccount, weight: u64) -> ProgramResult {
+        let mut grant = vec![IX_GIVE_RIGHT];
+        grant.extend_from_slice(&weight.to_le_bytes());
+        process(
+            &self.program_id,
+            &mut [&mut self.chairperson, &mut self.system, voter],
+            &grant,
+        )
+    }
+
+    /// A new voter granted the right to vote with `weight`.
+    pub fn voter(&mut self, weight: u64) -> TestAccount {
+        let mut voter = TestAccount::voter(self.program_id);
+        self.grant(&mut voter, weight).unwrap();
+        voter
+    }
+
+    /// Votes for proposal `index`, with the state account passed as the
+    /// proposal account.
+    pub fn vote(&mut self, voter: &mut TestAccount, index: u32) -> ProgramResult {
+        let mut vote = vec![IX_VOTE];
+        vote.extend_from_slice(&index.to_le_bytes());
+        let mut proposals = self.state_alias();
+        process(
+            &self.program_id,
+            &mut [voter, &mut proposals, &mut self.system],
+            &vote,
+        )
+    }
+
+    /// A second handle on the state account, for the instructions that take
+    /// it in place of a proposal account.
+    pub fn state_alias(&self) -> TestAccount {
+        TestAccount::new(self.program_id, self.system.data.clone()).with_key(self.system.key)
+    }
+
+    /// The state as the ballot's state account now holds it.
+    pub fn state(&self) -> SimpleVotingSystem {
+        SimpleVotingSystem::deserialize(&self.system.data).unwrap()
+    }
+
+    /// The vote counts of the proposals the state holds.
+    pub fn counts(&self) -> Vec<VoteCount> {
+        self.state()
+            .proposals
+            .iter()
+            .map(|proposal| proposal.vote_count)
+            .collect()
+    }
+}
+
+/// Address of the per-proposal account for `index` on the ballot whose state
+/// account is `system`.
+pub fn proposal_address(program_id: &Pubkey, system: &Pubkey, index: u32) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"proposal", system.as_ref(), &index.to_le_bytes()],
+        program_id,
+    )
+    .0
+}
+
+/// Address of the voter record of token holder `owner` on the ballot whose
+/// state account is `system`.
+pub fn token_voter_address(program_id: &Pubkey, system: &Pubkey, owner: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"token_voter", system.as_ref(), owner.as_ref()],
+        program_id,
+    )
+    .0
+}
+
+/// Address of the archive account of the ballot whose state account is
+/// `system`.
+pub fn archive_address(program_id: &Pubkey, system: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"archive", system.as_ref()], program_id).0
+}
+
+/// Address of the velocity account of the ballot whose state account is
+/// `system`.
+pub fn velocity_address(program_id: &Pubkey, system: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"velocity", system.as_ref()], program_id).0
+}
+
+/// Runs one instruction with the given accounts, in order.
+pub fn process(
+    program_id: &Pubkey,
+    accounts: &mut [&mut TestAccount],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let infos: Vec<AccountInfo> = accounts.iter_mut().map(|account| account.info()).collect();
+    process_infos(program_id, &infos, instruction_data)
+}
+
+/// Runs one instruction with accounts already lent out, so that one account
+/// can be passed in more than one position.
+pub fn process_infos(
+    program_id: &Pubkey,
+    infos: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    static STUBS: Once = Once::new();
+    STUBS.call_once(|| {
+        set_syscall_stubs(Box::new(TestStubs));
+    });
+
+    RETURN_DATA.with(|return_data| return_data.borrow_mut().clear());
+    LOGS.with(|logs| logs.borrow_mut().clear());
+    process_instruction(program_id, infos, instruction_data)
+}
+
+/// Moves the clock seen by the instructions run on this thread to `slot`.
+pub fn warp_to_slot(slot: u64) {
+    CLOCK_SLOT.with(|clock_slot| clock_slot.set(slot));
+}
+
+/// Return data set by the last instruction run on this thread.
+pub fn return_data() -> Vec<u8> {
+    RETURN_DATA.with(|return_data| return_data.borrow().clone())
+}
+
+/// Messages logged by the last instruction run on this thread.
+pub fn logs() -> Vec<String> {
+    LOGS.with(|logs| logs.borrow().clone())
+}
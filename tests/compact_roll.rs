@@ -0,0 +1,55 @@
+//! Compacting the voter roll drops the voters whose right to vote was revoked
+//! and clears the space they took.
+
+mod common;
+
+use common::{process, Ballot, TestAccount};
+use solana_program::pubkey::Pubkey;
+use solana_voting_program::{IX_BATCH_REVOKE, IX_COMPACT_ROLL, ROLL_ENTRY_LEN};
+
+impl Ballot {
+    fn run(&mut self, instruction: u8, voters: &mut [&mut TestAccount]) {
+        let mut accounts: Vec<&mut TestAccount> = vec![&mut self.chairperson, &mut self.system];
+        accounts.extend(voters.iter_mut().map(|voter| &mut **voter));
+        process(&self.program_id, &mut accounts, &[instruction]).unwrap();
+    }
+
+    fn roll(&self) -> Vec<Pubkey> {
+        self.state()
+            .voters
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect()
+    }
+}
+
+#[test]
+fn revoked_voters_are_removed() {
+    let mut ballot = Ballot::new(Vec::new(), 3);
+    let mut first = ballot.voter(1);
+    let kept = ballot.voter(1);
+    let mut last = ballot.voter(1);
+    ballot.run(IX_BATCH_REVOKE, &mut [&mut first, &mut last]);
+    let full_len = ballot.state().to_bytes().len();
+
+    ballot.run(IX_COMPACT_ROLL, &mut []);
+
+    assert_eq!(ballot.roll(), vec![kept.key]);
+    // The space the dropped entries took is cleared
+    let compact_len = full_len - 2 * ROLL_ENTRY_LEN;
+    assert!(ballot.system.data[compact_len..]
+        .iter()
+        .all(|&byte| byte == 0));
+}
+
+#[test]
+fn roll_without_revoked_voters_is_unchanged() {
+    let mut ballot = Ballot::new(Vec::new(), 3);
+    let voters = [ballot.voter(1).key, ballot.voter(1).key];
+    let data = ballot.system.data.clone();
+
+    ballot.run(IX_COMPACT_ROLL, &mut []);
+
+    assert_eq!(ballot.roll(), voters);
+    assert_eq!(ballot.system.data, data);
+}
@@ -0,0 +1,65 @@
+//! The configuration summary carries every setting of the ballot and is read in
+//! chunks.
+
+mod common;
+
+use common::{open_ballot, process, return_data, TestAccount};
+use solana_program::pubkey::Pubkey;
+use solana_voting_program::{Proposal, Voter, IX_CONFIG};
+
+#[test]
+fn each_field_round_trips() {
+    let program_id = Pubkey::new_unique();
+    let chairperson = Pubkey::new_unique();
+    let gov_mint = Pubkey::new_unique();
+    let mut ballot = open_ballot(chairperson);
+    ballot.min_participation_weight = 2;
+    ballot.title = [b't'; 64];
+    ballot.decimals = 6;
+    ballot.results_sealed = true;
+    ballot.snapshot_slot = 77;
+    ballot.end_timestamp = 1_800_000_000;
+    ballot.quorum = 40;
+    ballot.gov_mint = gov_mint;
+    let proposal = Proposal {
+        name: vec![b'p'; 32],
+        vote_count: 0,
+        nonce: 0,
+        max_expected_votes: 0,
+        reject_unexpected: false,
+        desc_hash: [0; 32],
+    };
+    ballot.proposals = vec![proposal; 3];
+    ballot.voters = vec![(Pubkey::new_unique(), Voter::default()); 2];
+    let mut system = TestAccount::state(program_id, &ballot, 0);
+
+    let mut config = vec![IX_CONFIG];
+    config.extend_from_slice(&0u32.to_le_bytes());
+    process(&program_id, &mut [&mut system], &config).unwrap();
+
+    // The summary opens with the layout version, as the state account does
+    let mut expected = vec![system.data[0]];
+    expected.extend_from_slice(chairperson.as_ref());
+    expected.extend_from_slice(&2u32.to_le_bytes());
+    expected.extend_from_slice(&[b't'; 64]);
+    expected.push(6);
+    expected.push(1);
+    expected.extend_from_slice(&77u64.to_le_bytes());
+    expected.push(1);
+    expected.extend_from_slice(&1_800_000_000i64.to_le_bytes());
+    expected.extend_from_slice(&40u64.to_le_bytes());
+    expected.extend_from_slice(gov_mint.as_ref());
+    expected.extend_from_slice(&3u32.to_le_bytes());
+    expected.extend_from_slice(&2u32.to_le_bytes());
+    assert_eq!(return_data(), expected);
+}
+
+#[test]
+fn chunk_past_the_summary_is_rejected() {
+    let program_id = Pubkey::new_unique();
+    let mut system = TestAccount::state(program_id, &open_ballot(Pubkey::new_unique()), 0);
+
+    let mut config = vec![IX_CONFIG];
+    config.extend_from_slice(&1u32.to_le_bytes());
+    assert!(process(&program_id, &mut [&mut system], &config).is_err());
+}
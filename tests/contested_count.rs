@@ -0,0 +1,33 @@
+//! Only proposals that have received at least one vote count as contested.
+
+mod common;
+
+use common::{open_ballot, process, proposal, return_data, TestAccount};
+use solana_program::pubkey::Pubkey;
+use solana_voting_program::{VoteCount, IX_CONTESTED_COUNT};
+
+/// The contested count of a ballot whose proposals hold `counts`.
+fn contested_count(counts: Vec<VoteCount>) -> u32 {
+    let program_id = Pubkey::new_unique();
+    let mut ballot = open_ballot(Pubkey::new_unique());
+    ballot.proposals = counts
+        .into_iter()
+        .map(|count| proposal(b"Parks", count))
+        .collect();
+    let mut system = TestAccount::state(program_id, &ballot, 0);
+
+    process(&program_id, &mut [&mut system], &[IX_CONTESTED_COUNT]).unwrap();
+
+    u32::from_le_bytes(return_data().try_into().unwrap())
+}
+
+#[test]
+fn only_proposals_with_votes_are_counted() {
+    assert_eq!(contested_count(vec![0, 4, 0, 1, 7]), 3);
+}
+
+#[test]
+fn uncontested_ballots_count_zero() {
+    assert_eq!(contested_count(vec![0, 0]), 0);
+    assert_eq!(contested_count(Vec::new()), 0);
+}
@@ -0,0 +1,19 @@
+mod common;
+
+use common::open_ballot;
+use solana_program::pubkey::Pubkey;
+use solana_voting_program::{created_slot, SimpleVotingSystem};
+
+/// An empty ballot created in slot `created`.
+fn ballot(created: u64) -> SimpleVotingSystem {
+    let mut ballot = open_ballot(Pubkey::new_unique());
+    ballot.created_slot = created;
+    ballot
+}
+
+#[test]
+fn created_slot_is_preserved() {
+    let ballot = ballot(123_456);
+
+    assert_eq!(created_slot(&ballot.to_bytes()), Ok(123_456));
+}
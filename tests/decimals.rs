@@ -0,0 +1,54 @@
+//! The display decimals set by the chairperson are stored with the ballot and
+//! reported by the result queries.
+
+mod common;
+
+use common::{open_ballot, process, proposal, return_data, TestAccount};
+use solana_program::pubkey::Pubkey;
+use solana_voting_program::{SimpleVotingSystem, IX_LEADERBOARD, IX_SET_DECIMALS, IX_TOTAL_VOTES};
+
+#[test]
+fn decimals_survive_serialization() {
+    let mut ballot = open_ballot(Pubkey::new_unique());
+    ballot.decimals = 9;
+
+    let read = SimpleVotingSystem::deserialize(&ballot.to_bytes()).unwrap();
+    assert_eq!(read.decimals, 9);
+}
+
+#[test]
+fn set_decimals_are_reported_with_the_results() {
+    let program_id = Pubkey::new_unique();
+    let mut chairperson = TestAccount::signer();
+    let mut ballot = open_ballot(chairperson.key);
+    ballot.proposals = vec![proposal(b"Parks", 1_500)];
+    let mut system = TestAccount::state(program_id, &ballot, 0);
+
+    process(
+        &program_id,
+        &mut [&mut chairperson, &mut system],
+        &[IX_SET_DECIMALS, 3],
+    )
+    .unwrap();
+    assert_eq!(
+        SimpleVotingSystem::deserialize(&system.data)
+            .unwrap()
+            .decimals,
+        3
+    );
+
+    let mut proposals = TestAccount::new(program_id, system.data.clone()).with_key(system.key);
+    process(
+        &program_id,
+        &mut [&mut system, &mut proposals],
+        &[IX_TOTAL_VOTES],
+    )
+    .unwrap();
+    assert_eq!(return_data()[0], 3);
+
+    let mut page = vec![IX_LEADERBOARD];
+    page.extend_from_slice(&0u32.to_le_bytes());
+    page.extend_from_slice(&1u32.to_le_bytes());
+    process(&program_id, &mut [&mut system, &mut proposals], &page).unwrap();
+    assert_eq!(return_data()[0], 3);
+}
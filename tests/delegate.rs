@@ -0,0 +1,356 @@
+//! Voters hand all or part of their weight to another voter, or to the
+//! chairperson, and may move it on to a new delegate.
+
+mod common;
+
+use common::{logs, process, Ballot, TestAccount};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use solana_voting_program::{
+    Proposal, Voter, VotingError, IX_DELEGATE, IX_DELEGATE_TO_CHAIR, IX_REDELEGATE,
+};
+
+fn named(name: u8) -> Proposal {
+    Proposal {
+        name: vec![name; 32],
+        vote_count: 0,
+        nonce: 0,
+        max_expected_votes: 0,
+        reject_unexpected: false,
+        desc_hash: [0; 32],
+    }
+}
+
+/// An open ballot holding proposals `a` and `b`, with room for four voters on
+/// the roll.
+fn ballot() -> Ballot {
+    Ballot::new(vec![named(b'a'), named(b'b')], 4)
+}
+
+impl Ballot {
+    /// The chairperson's voter record, at the chairperson's address.
+    fn chairperson_voter(&mut self, weight: u64) -> TestAccount {
+        let mut voter = TestAccount::voter(self.program_id).with_key(self.chairperson.key);
+        voter.is_signer = false;
+        self.grant(&mut voter, weight).unwrap();
+        voter
+    }
+
+    fn delegate_to_chair(
+        &mut self,
+        from: &mut TestAccount,
+        chairperson: &mut TestAccount,
+    ) -> Result<(), ProgramError> {
+        let mut proposals = self.state_alias();
+        process(
+            &self.program_id,
+            &mut [from, chairperson, &mut self.system, &mut proposals],
+            &[IX_DELEGATE_TO_CHAIR],
+        )
+    }
+
+    fn delegate(
+        &mut self,
+        from: &mut TestAccount,
+        to: &mut TestAccount,
+    ) -> Result<(), ProgramError> {
+        let mut proposals = self.state_alias();
+        process(
+            &self.program_id,
+            &mut [from, to, &mut self.system, &mut proposals],
+            &[IX_DELEGATE],
+        )
+    }
+
+    fn delegate_part(
+        &mut self,
+        from: &mut TestAccount,
+        to: &mut TestAccount,
+        amount: u64,
+    ) -> Result<(), ProgramError> {
+        let mut delegate = vec![IX_DELEGATE];
+        delegate.extend_from_slice(&amount.to_le_bytes());
+        let mut proposals = self.state_alias();
+        process(
+            &self.program_id,
+            &mut [from, to, &mut self.system, &mut proposals],
+            &delegate,
+        )
+    }
+
+    /// Redelegates with the state account passed as the proposal account of
+    /// each delegate who voted.
+    fn redelegate(
+        &mut self,
+        from: &mut TestAccount,
+        old: &mut TestAccount,
+        to: &mut TestAccount,
+    ) -> Result<(), ProgramError> {
+        let mut old_proposals = self.state_alias();
+        let mut new_proposals = self.state_alias();
+        process(
+            &self.program_id,
+            &mut [
+                from,
+                old,
+                to,
+                &mut self.system,
+                &mut old_proposals,
+                &mut new_proposals,
+            ],
+            &[IX_REDELEGATE],
+        )
+    }
+}
+
+fn weight(voter: &TestAccount) -> u64 {
+    Voter::from_bytes(&voter.data).unwrap().weight
+}
+
+#[test]
+fn delegation_is_logged() {
+    let mut ballot = ballot();
+    let mut from = ballot.voter(2);
+    let mut to = ballot.voter(3);
+
+    ballot.delegate(&mut from, &mut to).unwrap();
+
+    let expected = format!("DELEGATE from={} to={} weight=2", from.key, to.key);
+    assert!(logs().contains(&expected), "{:?}", logs());
+    assert_eq!(weight(&to), 5);
+}
+
+#[test]
+fn unsigned_delegation_is_rejected() {
+    let mut ballot = ballot();
+    let mut from = ballot.voter(2);
+    let mut to = ballot.voter(3);
+    from.is_signer = false;
+
+    assert_eq!(
+        ballot.delegate(&mut from, &mut to),
+        Err(ProgramError::MissingRequiredSignature)
+    );
+    assert!(!Voter::from_bytes(&from.data).unwrap().voted);
+    assert_eq!(weight(&to), 3);
+}
+
+#[test]
+fn delegating_to_the_default_key_is_rejected() {
+    let mut ballot = ballot();
+    let mut from = ballot.voter(2);
+    let mut to = ballot.voter(3).with_key(Pubkey::default());
+
+    assert_eq!(
+        ballot.delegate(&mut from, &mut to),
+        Err(ProgramError::InvalidArgument)
+    );
+    assert!(!Voter::from_bytes(&from.data).unwrap().voted);
+}
+
+#[test]
+fn weight_delegated_to_a_voter_who_voted_follows_their_vote() {
+    let mut ballot = ballot();
+    let mut from = ballot.voter(2);
+    let mut to = ballot.voter(3);
+    ballot.vote(&mut to, 1).unwrap();
+
+    ballot.delegate(&mut from, &mut to).unwrap();
+
+    let system = ballot.state();
+    assert_eq!(system.proposals[1].vote_count, 5);
+    assert!(logs()
+        .iter()
+        .any(|log| log.contains("DELEGATE") && log.ends_with("weight=2")));
+}
+
+#[test]
+fn redelegating_moves_the_weight_to_the_new_delegate() {
+    let mut ballot = ballot();
+    let mut from = ballot.voter(2);
+    let mut first = ballot.voter(3);
+    let mut second = ballot.voter(4);
+    ballot.delegate(&mut from, &mut first).unwrap();
+
+    ballot
+        .redelegate(&mut from, &mut first, &mut second)
+        .unwrap();
+
+    assert_eq!(weight(&first), 3);
+    assert_eq!(weight(&second), 6);
+    assert_eq!(Voter::from_bytes(&from.data).unwrap().delegate, second.key);
+    let expected = format!(
+        "REDELEGATE from={} old={} to={} weight=2",
+        from.key, first.key, second.key
+    );
+    assert!(logs().contains(&expected), "{:?}", logs());
+}
+
+#[test]
+fn redelegating_takes_the_weight_out_of_the_old_delegates_vote() {
+    let mut ballot = ballot();
+    let mut from = ballot.voter(2);
+    let mut first = ballot.voter(3);
+    let mut second = ballot.voter(4);
+    ballot.delegate(&mut from, &mut first).unwrap();
+    ballot.vote(&mut first, 0).unwrap();
+    ballot.vote(&mut second, 1).unwrap();
+    assert_eq!(ballot.counts(), vec![5, 4]);
+
+    ballot
+        .redelegate(&mut from, &mut first, &mut second)
+        .unwrap();
+
+    assert_eq!(ballot.counts(), vec![3, 6]);
+}
+
+#[test]
+fn redelegating_needs_the_current_delegate() {
+    let mut ballot = ballot();
+    let mut from = ballot.voter(2);
+    let mut first = ballot.voter(3);
+    let mut other = ballot.voter(1);
+    let mut second = ballot.voter(4);
+    ballot.delegate(&mut from, &mut first).unwrap();
+
+    assert_eq!(
+        ballot.redelegate(&mut from, &mut other, &mut second),
+        Err(ProgramError::InvalidArgument)
+    );
+    assert_eq!(weight(&first), 5);
+}
+
+#[test]
+fn redelegating_cannot_form_a_loop() {
+    let mut ballot = ballot();
+    let mut from = ballot.voter(2);
+    let mut first = ballot.voter(3);
+    let mut second = ballot.voter(4);
+    let mut third = ballot.voter(1);
+    ballot.delegate(&mut from, &mut first).unwrap();
+    ballot.delegate(&mut second, &mut third).unwrap();
+
+    // The new delegate handed their own weight on
+    assert_eq!(
+        ballot.redelegate(&mut from, &mut first, &mut second),
+        Err(ProgramError::InvalidArgument)
+    );
+}
+
+#[test]
+fn redelegating_is_refused_once_voting_closed() {
+    let mut ballot = ballot();
+    let mut from = ballot.voter(2);
+    let mut first = ballot.voter(3);
+    let mut second = ballot.voter(4);
+    ballot.delegate(&mut from, &mut first).unwrap();
+
+    let mut system = ballot.state();
+    system.results_sealed = true;
+    let mut data = system.to_bytes();
+    data.resize(ballot.system.data.len(), 0);
+    ballot.system.data = data;
+
+    assert!(ballot
+        .redelegate(&mut from, &mut first, &mut second)
+        .is_err());
+    assert_eq!(weight(&first), 5);
+}
+
+#[test]
+fn delegating_to_the_chairperson_adds_to_their_weight() {
+    let mut ballot = ballot();
+    let mut from = ballot.voter(2);
+    let mut chairperson = ballot.chairperson_voter(3);
+
+    ballot
+        .delegate_to_chair(&mut from, &mut chairperson)
+        .unwrap();
+
+    assert_eq!(weight(&chairperson), 5);
+    let system = ballot.state();
+    let (_, entry) = system
+        .voters
+        .iter()
+        .find(|(key, _)| *key == ballot.chairperson.key)
+        .unwrap();
+    assert_eq!(entry.weight, 5);
+    assert_eq!(
+        Voter::from_bytes(&from.data).unwrap().delegate,
+        ballot.chairperson.key
+    );
+}
+
+#[test]
+fn delegating_to_the_chair_needs_the_chairpersons_record() {
+    let mut ballot = ballot();
+    let mut from = ballot.voter(2);
+    let mut other = ballot.voter(3);
+
+    assert_eq!(
+        ballot.delegate_to_chair(&mut from, &mut other),
+        Err(VotingError::NotChairperson.into())
+    );
+    assert_eq!(weight(&other), 3);
+}
+
+#[test]
+fn delegating_to_the_chair_cannot_form_a_loop() {
+    let mut ballot = ballot();
+    let mut from = ballot.voter(2);
+    let mut chairperson = ballot.chairperson_voter(3);
+    chairperson.is_signer = true;
+    ballot.delegate(&mut chairperson, &mut from).unwrap();
+
+    assert_eq!(
+        ballot.delegate_to_chair(&mut from, &mut chairperson),
+        Err(ProgramError::InvalidArgument)
+    );
+}
+
+#[test]
+fn part_of_the_weight_can_be_delegated() {
+    let mut ballot = ballot();
+    let mut from = ballot.voter(5);
+    let mut to = ballot.voter(1);
+
+    ballot.delegate_part(&mut from, &mut to, 2).unwrap();
+    assert_eq!(weight(&from), 3);
+    assert_eq!(weight(&to), 3);
+    assert!(!Voter::from_bytes(&from.data).unwrap().has_delegated());
+
+    // The delegator votes with the weight they kept
+    ballot.vote(&mut from, 0).unwrap();
+    ballot.vote(&mut to, 1).unwrap();
+    assert_eq!(ballot.counts(), vec![3, 3]);
+}
+
+#[test]
+fn delegating_more_than_the_weight_is_rejected() {
+    let mut ballot = ballot();
+    let mut from = ballot.voter(5);
+    let mut to = ballot.voter(1);
+
+    assert_eq!(
+        ballot.delegate_part(&mut from, &mut to, 6),
+        Err(ProgramError::InvalidArgument)
+    );
+    assert_eq!(
+        ballot.delegate_part(&mut from, &mut to, 0),
+        Err(ProgramError::InvalidArgument)
+    );
+    assert_eq!((weight(&from), weight(&to)), (5, 1));
+}
+
+#[test]
+fn delegating_the_whole_weight_as_an_amount_is_a_full_delegation() {
+    let mut ballot = ballot();
+    let mut from = ballot.voter(5);
+    let mut to = ballot.voter(1);
+
+    ballot.delegate_part(&mut from, &mut to, 5).unwrap();
+
+    let from = Voter::from_bytes(&from.data).unwrap();
+    assert!(from.voted);
+    assert_eq!(from.delegate, to.key);
+    assert_eq!(weight(&to), 6);
+}
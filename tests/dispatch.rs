@@ -0,0 +1,119 @@
+//! Each instruction tag constant is dispatched to its own handler, and tags
+//! without a constant are rejected.
+
+mod common;
+
+use std::mem::size_of;
+
+use common::{process, TestAccount};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use solana_voting_program::*;
+
+/// Every instruction tag, with the handler it runs and the length of a
+/// payload it accepts.
+const HANDLERS: [(u8, &str, usize); 51] = [
+    (IX_GIVE_RIGHT, "give_right_to_vote", 0),
+    (IX_VOTE, "vote", 4),
+    (IX_WINNING_PROPOSAL, "winning_proposal", 0),
+    (IX_WINNER_NAME, "winner_name", 0),
+    (IX_INITIALIZE, "initialize", 16),
+    (IX_DELEGATE, "delegate", 0),
+    (IX_CHANGE_VOTE, "change_vote", 8),
+    (IX_ADD_PROPOSAL, "add_proposal", 0),
+    (IX_CLOSE_POLL, "close_poll", 0),
+    (IX_PROPOSAL_RANK, "proposal_rank", 4),
+    (IX_VERIFY_RECEIPT, "verify_receipt", 36),
+    (IX_APPROVAL_VOTE, "approval_vote", 4),
+    (IX_LEADERBOARD, "leaderboard", 8),
+    (IX_TOTAL_VOTES, "total_votes", 0),
+    (IX_ARCHIVE_RESULTS, "archive_results", 0),
+    (IX_OVERTAKE_GAP, "overtake_gap", 4),
+    (IX_DUMP_PROPOSALS, "dump_proposals", 4),
+    (IX_SET_TITLE, "set_title", 64),
+    (IX_GET_TITLE, "get_title", 0),
+    (IX_IS_DECIDED, "is_decided", 0),
+    (IX_UNVOTED_VOTERS, "unvoted_voters", 8),
+    (
+        IX_SET_EXPECTED_VOTES,
+        "set_expected_votes",
+        5 + size_of::<VoteCount>(),
+    ),
+    (IX_RECENT_VELOCITY, "recent_velocity", 8),
+    (IX_BATCH_REVOKE, "batch_revoke", 0),
+    (IX_SET_DECIMALS, "set_decimals", 1),
+    (IX_MIGRATE, "migrate", 0),
+    (IX_SEAL_RESULTS, "seal_results", 0),
+    (IX_CHAIRPERSON_VOTER, "chairperson_voter", 0),
+    (IX_QUORUM_PROGRESS, "quorum_progress", 12),
+    (IX_WRITE_IN_VOTE, "write_in_vote", 0),
+    (IX_CONFIG, "config", 4),
+    (IX_WEIGHT_STATS, "weight_stats", 0),
+    (IX_SET_SNAPSHOT_SLOT, "set_snapshot_slot", 8),
+    (IX_CONTESTED_COUNT, "contested_count", 0),
+    (IX_START_VOTING, "start_voting", 0),
+    (IX_MEDIAN_WEIGHT, "median_weight", 0),
+    (IX_RACE_STATUS, "race_status", 12),
+    (IX_COMPACT_ROLL, "compact_roll", 0),
+    (IX_WINNER_SHARE, "winner_share", 0),
+    (IX_NEEDS_RUNOFF, "needs_runoff", 2),
+    (IX_GET_PROPOSAL, "get_proposal", 4),
+    (IX_VERIFY_DESCRIPTION, "verify_description", 4),
+    (IX_REMAINING_CHOICES, "remaining_choices", 0),
+    (IX_DUMP_ROLL, "dump_roll", 8),
+    (IX_CREATED_AT, "created_at", 0),
+    (IX_SET_GOV_MINT, "set_gov_mint", 32),
+    (IX_VOTE_WITH_TOKEN, "vote_with_token", 4),
+    (IX_REDELEGATE, "redelegate", 0),
+    (IX_DELEGATE_TO_CHAIR, "delegate_to_chair", 0),
+    // Listed twice, with the optional payload
+    (IX_VOTE, "vote", 12),
+    (IX_DELEGATE, "delegate", 8),
+];
+
+fn run(tag: u8, payload_len: usize, accounts: usize) -> Result<(), ProgramError> {
+    let program_id = Pubkey::new_unique();
+    let mut accounts: Vec<TestAccount> = (0..accounts).map(|_| TestAccount::signer()).collect();
+    let mut accounts: Vec<&mut TestAccount> = accounts.iter_mut().collect();
+    let mut instruction_data = vec![tag];
+    instruction_data.resize(1 + payload_len, 0);
+    process(&program_id, &mut accounts, &instruction_data)
+}
+
+#[test]
+fn tags_are_distinct() {
+    let mut tags: Vec<u8> = HANDLERS.iter().map(|&(tag, _, _)| tag).collect();
+    tags.sort_unstable();
+    tags.dedup();
+    assert_eq!(tags.len(), HANDLERS.len() - 2);
+}
+
+#[test]
+fn every_tag_is_dispatched() {
+    // Known instructions get as far as counting their accounts
+    for (tag, handler, payload_len) in HANDLERS {
+        assert_eq!(
+            run(tag, payload_len, 0),
+            Err(ProgramError::NotEnoughAccountKeys),
+            "{}",
+            handler
+        );
+    }
+}
+
+#[test]
+fn tags_without_a_constant_are_rejected() {
+    for tag in [9, 15, 20, 57, 255] {
+        assert_eq!(run(tag, 0, 5), Err(ProgramError::InvalidInstructionData));
+    }
+}
+
+#[cfg(feature = "profiling")]
+#[test]
+fn every_tag_runs_its_handler() {
+    for (tag, handler, payload_len) in HANDLERS {
+        // The accounts are blank, so most handlers fail once they run
+        let _ = run(tag, payload_len, 5);
+        let marker = format!("PROFILE {} start", handler);
+        assert!(common::logs().contains(&marker), "{}", handler);
+    }
+}
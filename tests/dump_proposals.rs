@@ -0,0 +1,70 @@
+//! Raw proposal records come back in chunks that decode into the proposals and
+//! never split a record.
+
+mod common;
+
+use common::{open_ballot, process, proposal, return_data, TestAccount};
+use solana_program::{program::MAX_RETURN_DATA, pubkey::Pubkey};
+use solana_voting_program::{Proposal, IX_DUMP_PROPOSALS};
+
+struct Dump {
+    program_id: Pubkey,
+    system: TestAccount,
+}
+
+impl Dump {
+    /// A ballot holding `count` proposals, the i-th with i votes.
+    fn new(count: usize) -> Self {
+        let program_id = Pubkey::new_unique();
+        let mut ballot = open_ballot(Pubkey::new_unique());
+        ballot.proposals = (0..count)
+            .map(|index| proposal(format!("Proposal {}", index).as_bytes(), index as _))
+            .collect();
+
+        Dump {
+            program_id,
+            system: TestAccount::state(program_id, &ballot, 0),
+        }
+    }
+
+    fn chunk(&mut self, chunk: u32) -> Option<Vec<Proposal>> {
+        let mut dump = vec![IX_DUMP_PROPOSALS];
+        dump.extend_from_slice(&chunk.to_le_bytes());
+        process(&self.program_id, &mut [&mut self.system], &dump).ok()?;
+
+        let records = return_data();
+        assert_eq!(records.len() % Proposal::LEN, 0);
+        Some(
+            records
+                .chunks(Proposal::LEN)
+                .map(|record| Proposal::from_bytes(record).unwrap())
+                .collect(),
+        )
+    }
+}
+
+#[test]
+fn dump_decodes_back_into_the_proposals() {
+    let mut dump = Dump::new(3);
+    let expected = (0..3)
+        .map(|index| proposal(format!("Proposal {}", index).as_bytes(), index))
+        .collect::<Vec<_>>();
+
+    assert_eq!(dump.chunk(0), Some(expected));
+}
+
+#[test]
+fn records_are_never_split_across_chunks() {
+    let per_chunk = MAX_RETURN_DATA / Proposal::LEN;
+    let mut dump = Dump::new(per_chunk + 2);
+
+    let first = dump.chunk(0).unwrap();
+    let second = dump.chunk(1).unwrap();
+    assert_eq!(first.len(), per_chunk);
+    assert_eq!(second.len(), 2);
+    assert_eq!(
+        second[1].name,
+        format!("Proposal {}", per_chunk + 1).into_bytes()
+    );
+    assert_eq!(dump.chunk(3), None);
+}
@@ -0,0 +1,62 @@
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use solana_voting_program::{dump_roll_entries, Voter, ROLL_ENTRY_LEN};
+
+fn roll() -> Vec<(Pubkey, Voter)> {
+    (0..3)
+        .map(|position| {
+            let voter = Voter {
+                weight: 10 + position,
+                voted: position == 1,
+                vote: position as u32,
+                ..Voter::default()
+            };
+            (Pubkey::new_unique(), voter)
+        })
+        .collect()
+}
+
+fn decode(bytes: &[u8]) -> Vec<(Pubkey, Voter)> {
+    assert_eq!(bytes.len() % ROLL_ENTRY_LEN, 0);
+    bytes
+        .chunks_exact(ROLL_ENTRY_LEN)
+        .map(|entry| {
+            let (voter_key, record) = entry.split_at(32);
+            (Pubkey::new(voter_key), Voter::from_bytes(record).unwrap())
+        })
+        .collect()
+}
+
+#[test]
+fn dump_decodes_back_into_the_roll() {
+    let voters = roll();
+    let decoded = decode(&dump_roll_entries(&voters, 0, 10).unwrap());
+
+    assert_eq!(decoded.len(), voters.len());
+    for ((key, voter), (decoded_key, decoded_voter)) in voters.iter().zip(&decoded) {
+        assert_eq!(key, decoded_key);
+        assert_eq!(voter.weight, decoded_voter.weight);
+        assert_eq!(voter.voted, decoded_voter.voted);
+        assert_eq!(voter.vote, decoded_voter.vote);
+    }
+}
+
+#[test]
+fn dump_honours_offset_and_limit() {
+    let voters = roll();
+    let decoded = decode(&dump_roll_entries(&voters, 1, 1).unwrap());
+
+    assert_eq!(decoded.len(), 1);
+    assert_eq!(decoded[0].0, voters[1].0);
+    assert_eq!(decoded[0].1.weight, 11);
+}
+
+#[test]
+fn dump_rejects_an_offset_past_the_roll() {
+    let voters = roll();
+
+    assert!(dump_roll_entries(&voters, 3, 1).unwrap().is_empty());
+    assert_eq!(
+        dump_roll_entries(&voters, 4, 1),
+        Err(ProgramError::InvalidArgument)
+    );
+}
@@ -0,0 +1,61 @@
+//! Records re-encoded in either byte order, with little-endian the default.
+
+mod common;
+
+use common::proposal;
+use solana_program::pubkey::Pubkey;
+use solana_voting_program::{Endian, Proposal, Voter};
+
+fn voter() -> Voter {
+    Voter {
+        weight: 0x0102_0304_0506_0708,
+        voted: true,
+        vote: 0x0a0b_0c0d,
+        delegate: Pubkey::new_unique(),
+        ballot: Pubkey::new_unique(),
+        approvals: 0b101,
+    }
+}
+
+#[test]
+fn little_endian_is_the_default() {
+    let voter = voter();
+    assert_eq!(voter.to_bytes(), voter.to_bytes_endian(Endian::default()));
+    assert_eq!(voter.to_bytes()[..8], voter.weight.to_le_bytes());
+
+    let proposal = proposal(b"Parks", 7);
+    assert_eq!(
+        proposal.to_bytes(),
+        proposal.to_bytes_endian(Endian::Little)
+    );
+}
+
+#[test]
+fn voters_round_trip_in_both_byte_orders() {
+    let voter = voter();
+    for endian in [Endian::Little, Endian::Big] {
+        let bytes = voter.to_bytes_endian(endian);
+        assert_eq!(Voter::from_bytes_endian(&bytes, endian).unwrap(), voter);
+    }
+    assert_eq!(
+        voter.to_bytes_endian(Endian::Big)[..8],
+        voter.weight.to_be_bytes()
+    );
+}
+
+#[test]
+fn proposals_round_trip_in_both_byte_orders() {
+    let mut proposal = proposal(b"Parks", 0x0102_0304);
+    proposal.nonce = 9;
+    for endian in [Endian::Little, Endian::Big] {
+        let bytes = proposal.to_bytes_endian(endian);
+        assert_eq!(
+            Proposal::from_bytes_endian(&bytes, endian).unwrap(),
+            proposal
+        );
+    }
+
+    // Read in the other byte order, the name length runs past its slot
+    let bytes = proposal.to_bytes_endian(Endian::Big);
+    assert!(Proposal::from_bytes_endian(&bytes, Endian::Little).is_err());
+}
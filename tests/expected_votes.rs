@@ -0,0 +1,101 @@
+//! Votes taking a proposal past its registered maximum are logged, or
+//! rejected when the chairperson asked for that.
+
+mod common;
+
+use common::{logs, open_ballot, process, proposal, proposal_address, TestAccount};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use solana_voting_program::{
+    Proposal, SimpleVotingSystem, VoteCount, VotingError, IX_GIVE_RIGHT, IX_SET_EXPECTED_VOTES,
+    IX_VOTE, ROLL_ENTRY_LEN,
+};
+
+/// Registers a maximum of 5 votes on a proposal holding 3, then votes for it
+/// with a weight of 3.
+fn vote_past_the_maximum(reject: bool) -> (Result<(), ProgramError>, Proposal) {
+    let program_id = Pubkey::new_unique();
+    let mut chairperson = TestAccount::signer();
+    let mut system = TestAccount::state(program_id, &open_ballot(chairperson.key), ROLL_ENTRY_LEN);
+    let mut proposal = TestAccount::new(program_id, proposal(b"Parks", 3).to_bytes())
+        .with_key(proposal_address(&program_id, &system.key, 0));
+    let mut voter = TestAccount::voter(program_id);
+
+    let mut expect = vec![IX_SET_EXPECTED_VOTES];
+    expect.extend_from_slice(&0u32.to_le_bytes());
+    expect.extend_from_slice(&(5 as VoteCount).to_le_bytes());
+    expect.push(reject as u8);
+    process(
+        &program_id,
+        &mut [&mut chairperson, &mut system, &mut proposal],
+        &expect,
+    )
+    .unwrap();
+
+    let mut grant = vec![IX_GIVE_RIGHT];
+    grant.extend_from_slice(&3u64.to_le_bytes());
+    process(
+        &program_id,
+        &mut [&mut chairperson, &mut system, &mut voter],
+        &grant,
+    )
+    .unwrap();
+
+    let mut vote = vec![IX_VOTE];
+    vote.extend_from_slice(&0u32.to_le_bytes());
+    let result = process(
+        &program_id,
+        &mut [&mut voter, &mut proposal, &mut system],
+        &vote,
+    );
+
+    (result, Proposal::from_bytes(&proposal.data).unwrap())
+}
+
+#[test]
+fn strict_maximum_rejects_the_vote() {
+    let (result, proposal) = vote_past_the_maximum(true);
+
+    assert_eq!(result, Err(VotingError::ExpectedVotesExceeded.into()));
+    assert_eq!(proposal.vote_count, 3);
+    assert_eq!(proposal.max_expected_votes, 5);
+    assert!(proposal.reject_unexpected);
+}
+
+#[test]
+fn lenient_maximum_only_logs() {
+    let (result, proposal) = vote_past_the_maximum(false);
+
+    assert_eq!(result, Ok(()));
+    assert_eq!(proposal.vote_count, 6);
+    assert!(logs()
+        .iter()
+        .any(|message| message.contains("above its expected maximum of 5")));
+}
+
+#[test]
+fn maximum_is_registered_on_a_proposal_held_by_the_state() {
+    let program_id = Pubkey::new_unique();
+    let mut chairperson = TestAccount::signer();
+    let mut ballot = open_ballot(chairperson.key);
+    ballot.proposals = vec![proposal(b"Parks", 0), proposal(b"Roads", 0)];
+    let mut system = TestAccount::state(program_id, &ballot, 0);
+    let mut state = TestAccount::new(program_id, system.data.clone()).with_key(system.key);
+
+    let mut expect = vec![IX_SET_EXPECTED_VOTES];
+    expect.extend_from_slice(&1u32.to_le_bytes());
+    expect.extend_from_slice(&(5 as VoteCount).to_le_bytes());
+    expect.push(1);
+    process(
+        &program_id,
+        &mut [&mut chairperson, &mut system, &mut state],
+        &expect,
+    )
+    .unwrap();
+
+    let proposals = SimpleVotingSystem::deserialize(&system.data)
+        .unwrap()
+        .proposals;
+    assert_eq!(proposals[0], proposal(b"Parks", 0));
+    assert_eq!(proposals[1].max_expected_votes, 5);
+    assert!(proposals[1].reject_unexpected);
+}
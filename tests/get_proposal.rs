@@ -0,0 +1,111 @@
+//! Proposal records, description hash included, read from the state account or
+//! from a per-proposal account.
+
+mod common;
+
+use common::{open_ballot, process, proposal, proposal_address, return_data, TestAccount};
+use solana_program::{hash::hash, program_error::ProgramError, pubkey::Pubkey};
+use solana_voting_program::{Proposal, IX_ADD_PROPOSAL, IX_GET_PROPOSAL};
+
+/// Queries the record of proposal `index` held by `accounts`.
+fn get_proposal(
+    program_id: &Pubkey,
+    accounts: &mut [&mut TestAccount],
+    index: u32,
+) -> Result<Proposal, ProgramError> {
+    let mut query = vec![IX_GET_PROPOSAL];
+    query.extend_from_slice(&index.to_le_bytes());
+    process(program_id, accounts, &query)?;
+
+    Ok(Proposal::from_bytes(&return_data()).unwrap())
+}
+
+/// Adds `name` to a ballot still in setup, with `desc_hash` when given, and
+/// reads the new proposal back.
+fn add_and_read(name: &[u8], desc_hash: Option<[u8; 32]>) -> Proposal {
+    let program_id = Pubkey::new_unique();
+    let mut chairperson = TestAccount::signer();
+    let mut ballot = open_ballot(chairperson.key);
+    ballot.voting_started = false;
+    ballot.proposals = vec![proposal(b"Parks", 0)];
+    let mut system = TestAccount::state(program_id, &ballot, Proposal::LEN);
+
+    let mut add = vec![IX_ADD_PROPOSAL];
+    add.extend_from_slice(&(name.len() as u16).to_le_bytes());
+    add.extend_from_slice(name);
+    if let Some(desc_hash) = desc_hash {
+        add.extend_from_slice(&desc_hash);
+    }
+    process(&program_id, &mut [&mut chairperson, &mut system], &add).unwrap();
+
+    get_proposal(&program_id, &mut [&mut system], 1).unwrap()
+}
+
+#[test]
+fn description_hash_set_at_creation_round_trips() {
+    let desc_hash = hash(b"Resurface the ring road").to_bytes();
+
+    let proposal = add_and_read(b"Roads", Some(desc_hash));
+
+    assert_eq!(proposal.name, b"Roads");
+    assert_eq!(proposal.desc_hash, desc_hash);
+}
+
+#[test]
+fn proposals_added_without_a_description_hash_hold_zeros() {
+    assert_eq!(add_and_read(b"Roads", None).desc_hash, [0; 32]);
+}
+
+#[test]
+fn per_proposal_account_returns_its_record() {
+    let program_id = Pubkey::new_unique();
+    let record = Proposal {
+        desc_hash: hash(b"Open two new libraries").to_bytes(),
+        ..proposal(b"Libraries", 7)
+    };
+    let mut system = TestAccount::state(program_id, &open_ballot(Pubkey::new_unique()), 0);
+    let mut account = TestAccount::new(program_id, record.to_bytes()).with_key(proposal_address(
+        &program_id,
+        &system.key,
+        0,
+    ));
+
+    assert_eq!(
+        get_proposal(&program_id, &mut [&mut system, &mut account], 0),
+        Ok(record)
+    );
+}
+
+#[test]
+fn per_proposal_account_needs_the_state_of_its_ballot() {
+    let program_id = Pubkey::new_unique();
+    let mut system = TestAccount::state(program_id, &open_ballot(Pubkey::new_unique()), 0);
+    let mut other = TestAccount::state(program_id, &open_ballot(Pubkey::new_unique()), 0);
+    let mut account = TestAccount::new(program_id, proposal(b"Libraries", 7).to_bytes())
+        .with_key(proposal_address(&program_id, &system.key, 0));
+
+    assert_eq!(
+        get_proposal(&program_id, &mut [&mut other, &mut account], 0),
+        Err(ProgramError::InvalidArgument)
+    );
+    assert_eq!(
+        get_proposal(&program_id, &mut [&mut account], 0),
+        Err(ProgramError::InvalidArgument)
+    );
+    assert!(get_proposal(&program_id, &mut [&mut system, &mut account], 0).is_ok());
+}
+
+#[test]
+fn index_past_the_state_proposals_is_rejected() {
+    let program_id = Pubkey::new_unique();
+    let mut ballot = open_ballot(Pubkey::new_unique());
+    ballot.proposals = vec![proposal(b"Parks", 0)];
+    let mut system = TestAccount::state(program_id, &ballot, 0);
+
+    let mut query = vec![IX_GET_PROPOSAL];
+    query.extend_from_slice(&1u32.to_le_bytes());
+    assert_eq!(
+        process(&program_id, &mut [&mut system], &query),
+        Err(ProgramError::InvalidArgument)
+    );
+}
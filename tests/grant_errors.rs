@@ -0,0 +1,70 @@
+//! Grants to voters who already hold a right or have voted fail with
+//! distinct errors and leave the voter record as it was.
+
+mod common;
+
+use common::{Ballot, TestAccount};
+use solana_program::pubkey::Pubkey;
+use solana_voting_program::{Voter, VotingError};
+
+/// A voter account already holding `voter`.
+fn voter_holding(program_id: Pubkey, voter: Voter) -> TestAccount {
+    TestAccount {
+        is_signer: true,
+        ..TestAccount::new(program_id, voter.to_bytes())
+    }
+}
+
+#[test]
+fn voter_who_has_voted_is_reported_as_such() {
+    let mut ballot = Ballot::new(Vec::new(), 1);
+    let record = Voter {
+        weight: 2,
+        voted: true,
+        vote: 1,
+        ballot: ballot.system.key,
+        ..Voter::default()
+    };
+    let mut voter = voter_holding(ballot.program_id, record.clone());
+
+    assert_eq!(
+        ballot.grant(&mut voter, 3),
+        Err(VotingError::AlreadyVoted.into())
+    );
+    assert_eq!(Voter::from_bytes(&voter.data).unwrap(), record);
+}
+
+#[test]
+fn grant_of_another_weight_is_reported_as_already_granted() {
+    let mut ballot = Ballot::new(Vec::new(), 1);
+    let mut voter = voter_holding(ballot.program_id, Voter::default());
+    ballot.grant(&mut voter, 2).unwrap();
+    let granted = voter.data.clone();
+
+    assert_eq!(
+        ballot.grant(&mut voter, 3),
+        Err(VotingError::AlreadyGranted.into())
+    );
+    assert_eq!(voter.data, granted);
+
+    // Repeating the same grant changes nothing and succeeds
+    assert_eq!(ballot.grant(&mut voter, 2), Ok(()));
+    assert_eq!(voter.data, granted);
+}
+
+#[test]
+fn voter_of_another_ballot_is_reported_as_already_granted() {
+    let mut ballot = Ballot::new(Vec::new(), 1);
+    let record = Voter {
+        weight: 2,
+        ballot: Pubkey::new_unique(),
+        ..Voter::default()
+    };
+    let mut voter = voter_holding(ballot.program_id, record.clone());
+
+    assert_eq!(
+        ballot.grant(&mut voter, 2),
+        Err(VotingError::AlreadyGranted.into())
+    );
+    assert_eq!(Voter::from_bytes(&voter.data).unwrap(), record);
+}
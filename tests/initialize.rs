@@ -0,0 +1,57 @@
+//! A blank state account is set up once, closed to votes and holding its
+//! proposals.
+
+mod common;
+
+use common::{open_ballot, process, proposal, TestAccount, NOW, SLOT};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use solana_voting_program::{SimpleVotingSystem, IX_INITIALIZE};
+
+fn initialize_data(end_timestamp: i64, quorum: u64, names: &[&[u8]]) -> Vec<u8> {
+    let mut data = vec![IX_INITIALIZE];
+    data.extend_from_slice(&end_timestamp.to_le_bytes());
+    data.extend_from_slice(&quorum.to_le_bytes());
+    for name in names {
+        data.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        data.extend_from_slice(name);
+    }
+    data
+}
+
+#[test]
+fn blank_account_is_set_up_closed_with_its_proposals() {
+    let program_id = Pubkey::new_unique();
+    let mut chairperson = TestAccount::signer();
+    let mut expected = open_ballot(chairperson.key);
+    expected.voting_started = false;
+    expected.created_slot = SLOT;
+    expected.end_timestamp = NOW + 60;
+    expected.quorum = 4;
+    expected.proposals = vec![proposal(b"Parks", 0), proposal(b"Roads", 0)];
+    let mut system = TestAccount::new(program_id, vec![0; expected.to_bytes().len()]);
+
+    process(
+        &program_id,
+        &mut [&mut chairperson, &mut system],
+        &initialize_data(NOW + 60, 4, &[b"Parks", b"Roads"]),
+    )
+    .unwrap();
+
+    assert_eq!(SimpleVotingSystem::deserialize(&system.data), Ok(expected));
+}
+
+#[test]
+fn initialized_account_is_not_set_up_again() {
+    let program_id = Pubkey::new_unique();
+    let mut chairperson = TestAccount::signer();
+    let mut system = TestAccount::state(program_id, &open_ballot(chairperson.key), 0);
+
+    assert_eq!(
+        process(
+            &program_id,
+            &mut [&mut chairperson, &mut system],
+            &initialize_data(0, 0, &[]),
+        ),
+        Err(ProgramError::AccountAlreadyInitialized)
+    );
+}
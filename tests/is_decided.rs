@@ -0,0 +1,46 @@
+//! A ballot is decided once the weight still outstanding on the voter roll
+//! could no longer change the leader.
+
+mod common;
+
+use common::{open_ballot, process, proposal, return_data, roll_entry, TestAccount};
+use solana_program::pubkey::Pubkey;
+use solana_voting_program::{VoteCount, IX_IS_DECIDED};
+
+/// Whether a race between proposals holding `counts` is decided, with voters
+/// of `outstanding` weights yet to vote.
+fn is_decided(counts: &[VoteCount], outstanding: &[u64]) -> bool {
+    let program_id = Pubkey::new_unique();
+    let mut ballot = open_ballot(Pubkey::new_unique());
+    ballot.proposals = counts.iter().map(|&count| proposal(b"p", count)).collect();
+    ballot.voters = vec![roll_entry(12, true)];
+    ballot
+        .voters
+        .extend(outstanding.iter().map(|&weight| roll_entry(weight, false)));
+    let mut system = TestAccount::state(program_id, &ballot, 0);
+    let mut proposals = TestAccount::new(program_id, system.data.clone()).with_key(system.key);
+
+    process(
+        &program_id,
+        &mut [&mut system, &mut proposals],
+        &[IX_IS_DECIDED],
+    )
+    .unwrap();
+
+    match return_data()[..] {
+        [decided] => decided == 1,
+        ref data => panic!("unexpected return data {:?}", data),
+    }
+}
+
+#[test]
+fn lead_beyond_the_outstanding_weight_is_decided() {
+    assert!(is_decided(&[10, 4], &[2, 3]));
+    assert!(is_decided(&[10, 4], &[]));
+}
+
+#[test]
+fn outstanding_weight_that_could_tie_leaves_it_undecided() {
+    assert!(!is_decided(&[10, 4], &[2, 4]));
+    assert!(!is_decided(&[10, 4, 1], &[7]));
+}
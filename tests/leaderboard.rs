@@ -0,0 +1,53 @@
+//! The standings sort proposals by vote count, ties in index order, and come
+//! back a page at a time.
+
+mod common;
+
+use std::mem::size_of;
+
+use common::{open_ballot, process, proposal, return_data, TestAccount};
+use solana_program::pubkey::Pubkey;
+use solana_voting_program::{VoteCount, IX_LEADERBOARD};
+
+/// Runs the leaderboard query over proposals holding `counts`, with two
+/// decimals, and returns the decimals and the page.
+fn leaderboard(counts: &[VoteCount], offset: u32, limit: u32) -> (u8, Vec<(u32, VoteCount)>) {
+    let program_id = Pubkey::new_unique();
+    let mut ballot = open_ballot(Pubkey::new_unique());
+    ballot.decimals = 2;
+    ballot.proposals = counts.iter().map(|&count| proposal(b"p", count)).collect();
+    let mut system = TestAccount::state(program_id, &ballot, 0);
+    let mut proposals = TestAccount::new(program_id, system.data.clone()).with_key(system.key);
+
+    let mut query = vec![IX_LEADERBOARD];
+    query.extend_from_slice(&offset.to_le_bytes());
+    query.extend_from_slice(&limit.to_le_bytes());
+    process(&program_id, &mut [&mut system, &mut proposals], &query).unwrap();
+
+    let data = return_data();
+    let page = data[1..]
+        .chunks(4 + size_of::<VoteCount>())
+        .map(|entry| {
+            let (index, count) = entry.split_at(4);
+            (
+                u32::from_le_bytes(index.try_into().unwrap()),
+                VoteCount::from_le_bytes(count.try_into().unwrap()),
+            )
+        })
+        .collect();
+    (data[0], page)
+}
+
+#[test]
+fn four_proposals_sort_by_count_with_ties_in_index_order() {
+    let (decimals, page) = leaderboard(&[3, 8, 3, 5], 0, 10);
+
+    assert_eq!(decimals, 2);
+    assert_eq!(page, vec![(1, 8), (3, 5), (0, 3), (2, 3)]);
+}
+
+#[test]
+fn pages_follow_the_offset_and_limit() {
+    assert_eq!(leaderboard(&[3, 8, 3, 5], 1, 2).1, vec![(3, 5), (0, 3)]);
+    assert!(leaderboard(&[3, 8, 3, 5], 4, 2).1.is_empty());
+}
@@ -0,0 +1,45 @@
+//! The median weight of the voter roll, with even rolls reporting the half unit
+//! between the middle two.
+
+mod common;
+
+use common::{open_ballot, process, return_data, roll_entry, TestAccount};
+use solana_program::pubkey::Pubkey;
+use solana_voting_program::IX_MEDIAN_WEIGHT;
+
+/// The median weight of a roll of voters of `weights`, rounded down, and
+/// whether the exact median is half a unit above it.
+fn median_weight(weights: &[u64]) -> (u64, u8) {
+    let program_id = Pubkey::new_unique();
+    let mut ballot = open_ballot(Pubkey::new_unique());
+    ballot.voters = weights
+        .iter()
+        .map(|&weight| roll_entry(weight, false))
+        .collect();
+    let mut system = TestAccount::state(program_id, &ballot, 0);
+
+    process(&program_id, &mut [&mut system], &[IX_MEDIAN_WEIGHT]).unwrap();
+
+    let data = return_data();
+    (u64::from_le_bytes(data[..8].try_into().unwrap()), data[8])
+}
+
+#[test]
+fn even_roll_reports_the_half_unit() {
+    assert_eq!(median_weight(&[4, 1, 3, 2]), (2, 1));
+}
+
+#[test]
+fn odd_roll_reports_the_middle_weight() {
+    assert_eq!(median_weight(&[5, 1, 3]), (3, 0));
+}
+
+#[test]
+fn empty_roll_reports_zeros() {
+    assert_eq!(median_weight(&[]), (0, 0));
+}
+
+#[test]
+fn heaviest_weights_do_not_overflow() {
+    assert_eq!(median_weight(&[u64::MAX, u64::MAX]), (u64::MAX, 0));
+}
@@ -0,0 +1,129 @@
+//! The chairperson rewrites a state account from before the layout carried a
+//! version in the current layout.
+
+mod common;
+
+use common::{open_ballot, process, proposal, TestAccount};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use solana_voting_program::{SimpleVotingSystem, Voter, IX_MIGRATE};
+
+/// Width of a vote count in the version 0 layout.
+#[cfg(not(feature = "u128-counts"))]
+const V0_COUNT_LEN: usize = 4;
+#[cfg(feature = "u128-counts")]
+const V0_COUNT_LEN: usize = 16;
+
+/// The version 0 state of a ballot chaired by `chairperson`, with one voter
+/// who voted for the second of two proposals.
+fn v0_state(chairperson: &Pubkey, voter: &Pubkey) -> Vec<u8> {
+    let mut data = chairperson.to_bytes().to_vec();
+    data.extend_from_slice(&2u32.to_le_bytes());
+    let mut title = [0; 64];
+    title[..6].copy_from_slice(b"Budget");
+    data.extend_from_slice(&title);
+    data.push(1);
+
+    data.extend_from_slice(&1u32.to_le_bytes());
+    data.extend_from_slice(voter.as_ref());
+    data.extend_from_slice(&7u32.to_le_bytes());
+    data.push(1);
+    data.extend_from_slice(&1u32.to_le_bytes());
+
+    data.extend_from_slice(b"PRPS");
+    for (name, count) in [(&b"Parks"[..], 0u8), (b"Roads", 7)] {
+        let mut padded = [0; 32];
+        padded[..name.len()].copy_from_slice(name);
+        data.extend_from_slice(&padded);
+        let mut count_bytes = [0; V0_COUNT_LEN];
+        count_bytes[0] = count;
+        data.extend_from_slice(&count_bytes);
+        data.extend_from_slice(&0u64.to_le_bytes());
+        data.extend_from_slice(&[0; V0_COUNT_LEN]);
+        data.push(0);
+    }
+    data
+}
+
+/// What the ballot of [`v0_state`] reads as in the current layout.
+fn migrated(chairperson: Pubkey, voter: Pubkey) -> SimpleVotingSystem {
+    let mut system = open_ballot(chairperson);
+    system.min_participation_weight = 2;
+    system.title[..6].copy_from_slice(b"Budget");
+    system.decimals = 1;
+    let record = Voter {
+        weight: 7,
+        voted: true,
+        vote: 1,
+        ..Voter::default()
+    };
+    system.voters = vec![(voter, record)];
+    system.proposals = vec![proposal(b"Parks", 0), proposal(b"Roads", 7)];
+    system
+}
+
+struct Migration {
+    program_id: Pubkey,
+    chairperson: TestAccount,
+    old: TestAccount,
+    new: TestAccount,
+    expected: SimpleVotingSystem,
+}
+
+impl Migration {
+    fn new() -> Self {
+        let program_id = Pubkey::new_unique();
+        let chairperson = TestAccount::signer();
+        let voter = Pubkey::new_unique();
+        let expected = migrated(chairperson.key, voter);
+
+        Migration {
+            program_id,
+            old: TestAccount::new(program_id, v0_state(&chairperson.key, &voter)),
+            new: TestAccount::new(program_id, vec![0; expected.to_bytes().len()]),
+            chairperson,
+            expected,
+        }
+    }
+
+    fn run(&mut self) -> Result<(), ProgramError> {
+        process(
+            &self.program_id,
+            &mut [&mut self.chairperson, &mut self.old, &mut self.new],
+            &[IX_MIGRATE],
+        )
+    }
+}
+
+#[test]
+fn v0_account_is_rewritten_in_the_current_layout() {
+    let mut migration = Migration::new();
+    let old = migration.old.data.clone();
+
+    migration.run().unwrap();
+
+    assert_eq!(
+        SimpleVotingSystem::deserialize(&migration.new.data).unwrap(),
+        migration.expected
+    );
+    assert_eq!(migration.old.data, old);
+}
+
+#[test]
+fn only_the_chairperson_migrates() {
+    let mut migration = Migration::new();
+    migration.chairperson = TestAccount::signer();
+
+    assert!(migration.run().is_err());
+    assert!(migration.new.data.iter().all(|&byte| byte == 0));
+}
+
+#[test]
+fn initialized_account_is_not_overwritten() {
+    let mut migration = Migration::new();
+    migration.run().unwrap();
+
+    assert_eq!(
+        migration.run(),
+        Err(ProgramError::AccountAlreadyInitialized)
+    );
+}
@@ -0,0 +1,61 @@
+//! Voters below the ballot's minimum participation weight cannot vote.
+
+mod common;
+
+use common::{open_ballot, process, proposal, TestAccount};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use solana_voting_program::{
+    SimpleVotingSystem, VoteCount, VotingError, IX_GIVE_RIGHT, IX_VOTE, ROLL_ENTRY_LEN,
+};
+
+/// Grants a voter `weight` on a ballot requiring a weight of 3, then votes.
+fn vote_with_weight(weight: u64) -> (Result<(), ProgramError>, SimpleVotingSystem) {
+    let program_id = Pubkey::new_unique();
+    let mut chairperson = TestAccount::signer();
+    let mut ballot = open_ballot(chairperson.key);
+    ballot.min_participation_weight = 3;
+    ballot.proposals = vec![proposal(b"Parks", 0)];
+    let mut system = TestAccount::state(program_id, &ballot, ROLL_ENTRY_LEN);
+    let mut voter = TestAccount::voter(program_id);
+
+    let mut grant = vec![IX_GIVE_RIGHT];
+    grant.extend_from_slice(&weight.to_le_bytes());
+    process(
+        &program_id,
+        &mut [&mut chairperson, &mut system, &mut voter],
+        &grant,
+    )
+    .unwrap();
+
+    let mut vote = vec![IX_VOTE];
+    vote.extend_from_slice(&0u32.to_le_bytes());
+    let mut proposals = TestAccount::new(program_id, system.data.clone()).with_key(system.key);
+    let result = process(
+        &program_id,
+        &mut [&mut voter, &mut proposals, &mut system],
+        &vote,
+    );
+
+    (
+        result,
+        SimpleVotingSystem::deserialize(&system.data).unwrap(),
+    )
+}
+
+#[test]
+fn weight_below_the_minimum_is_rejected() {
+    let (result, system) = vote_with_weight(2);
+
+    assert_eq!(result, Err(VotingError::WeightBelowMinimum.into()));
+    assert_eq!(system.proposals[0].vote_count, 0);
+}
+
+#[test]
+fn weight_at_or_above_the_minimum_votes() {
+    for weight in [3, 4] {
+        let (result, system) = vote_with_weight(weight);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(system.proposals[0].vote_count, VoteCount::from(weight));
+    }
+}
@@ -0,0 +1,52 @@
+//! Ballots where no proposal reaches the majority threshold go to a runoff
+//! between the two leaders.
+
+mod common;
+
+use common::{open_ballot, process, proposal, return_data, TestAccount};
+use solana_program::pubkey::Pubkey;
+use solana_voting_program::{VoteCount, IX_NEEDS_RUNOFF};
+
+/// Whether a runoff is needed at `threshold` basis points, and the two
+/// leading proposals, on a ballot whose proposals hold `counts`.
+fn needs_runoff(counts: Vec<VoteCount>, threshold: u16) -> (u8, u32, u32) {
+    let program_id = Pubkey::new_unique();
+    let mut ballot = open_ballot(Pubkey::new_unique());
+    ballot.proposals = counts
+        .into_iter()
+        .map(|count| proposal(b"Parks", count))
+        .collect();
+    let mut system = TestAccount::state(program_id, &ballot, 0);
+
+    let mut query = vec![IX_NEEDS_RUNOFF];
+    query.extend_from_slice(&threshold.to_le_bytes());
+    process(&program_id, &mut [&mut system], &query).unwrap();
+
+    let data = return_data();
+    (
+        data[0],
+        u32::from_le_bytes(data[1..5].try_into().unwrap()),
+        u32::from_le_bytes(data[5..9].try_into().unwrap()),
+    )
+}
+
+#[test]
+fn ballot_without_a_majority_goes_to_a_runoff() {
+    assert_eq!(needs_runoff(vec![3, 4, 2, 1], 0), (1, 1, 0));
+}
+
+#[test]
+fn majority_winner_needs_no_runoff() {
+    assert_eq!(needs_runoff(vec![6, 4], 0), (0, 0, 1));
+}
+
+#[test]
+fn threshold_is_configurable() {
+    assert_eq!(needs_runoff(vec![6, 4], 6001), (1, 0, 1));
+    assert_eq!(needs_runoff(vec![6, 4], 6000), (0, 0, 1));
+}
+
+#[test]
+fn single_proposal_needs_no_runoff() {
+    assert_eq!(needs_runoff(vec![1], 0), (0, 0, u32::MAX));
+}
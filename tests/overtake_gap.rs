@@ -0,0 +1,40 @@
+//! The votes a proposal still needs to pass the leader: one past the gap, and
+//! one to break a tie.
+
+mod common;
+
+use common::{open_ballot, process, proposal, return_data, TestAccount};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use solana_voting_program::{VoteCount, VoteTotal, IX_OVERTAKE_GAP};
+
+fn overtake_gap(counts: &[VoteCount], index: u32) -> Result<VoteTotal, ProgramError> {
+    let program_id = Pubkey::new_unique();
+    let mut ballot = open_ballot(Pubkey::new_unique());
+    ballot.proposals = counts.iter().map(|&count| proposal(b"p", count)).collect();
+    let mut system = TestAccount::state(program_id, &ballot, 0);
+
+    let mut query = vec![IX_OVERTAKE_GAP];
+    query.extend_from_slice(&index.to_le_bytes());
+    process(&program_id, &mut [&mut system], &query)?;
+
+    Ok(VoteTotal::from_le_bytes(return_data().try_into().unwrap()))
+}
+
+#[test]
+fn trailing_proposal_needs_one_vote_past_the_leader() {
+    assert_eq!(overtake_gap(&[7, 3, 10], 1), Ok(8));
+}
+
+#[test]
+fn leader_needs_nothing_and_a_tie_needs_one() {
+    assert_eq!(overtake_gap(&[7, 3, 10], 2), Ok(0));
+    assert_eq!(overtake_gap(&[7, 7], 0), Ok(1));
+}
+
+#[test]
+fn index_past_the_end_is_rejected() {
+    assert_eq!(
+        overtake_gap(&[7, 3, 10], 3),
+        Err(ProgramError::InvalidArgument)
+    );
+}
@@ -0,0 +1,47 @@
+//! Compute-unit markers logged around each handler by `profiling` builds.
+
+mod common;
+
+use common::{logs, open_ballot, process, TestAccount};
+use solana_program::pubkey::Pubkey;
+use solana_voting_program::{IX_GIVE_RIGHT, ROLL_ENTRY_LEN};
+
+/// Grants a voter the right to vote and returns the messages logged.
+fn grant_logs() -> Vec<String> {
+    let program_id = Pubkey::new_unique();
+    let mut chairperson = TestAccount::signer();
+    let mut system = TestAccount::state(program_id, &open_ballot(chairperson.key), ROLL_ENTRY_LEN);
+    let mut voter = TestAccount::voter(program_id);
+
+    process(
+        &program_id,
+        &mut [&mut chairperson, &mut system, &mut voter],
+        &[IX_GIVE_RIGHT],
+    )
+    .unwrap();
+
+    logs()
+}
+
+#[cfg(feature = "profiling")]
+#[test]
+fn markers_surround_the_handler() {
+    let logs = grant_logs();
+    let start = logs
+        .iter()
+        .position(|message| message == "PROFILE give_right_to_vote start")
+        .unwrap();
+    let end = logs
+        .iter()
+        .position(|message| message == "PROFILE give_right_to_vote end")
+        .unwrap();
+    assert!(start < end);
+}
+
+#[cfg(not(feature = "profiling"))]
+#[test]
+fn markers_are_off_by_default() {
+    assert!(grant_logs()
+        .iter()
+        .all(|message| !message.starts_with("PROFILE")));
+}
@@ -0,0 +1,88 @@
+//! Votes on proposals stored one per account, at the address derived from
+//! each proposal's index, and the winner computed across those accounts.
+
+mod common;
+
+use common::{process, proposal, proposal_address, return_data, Ballot, TestAccount};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use solana_voting_program::{Proposal, VoteCount, VotingError, IX_VOTE, IX_WINNING_PROPOSAL};
+
+/// An open ballot with room for two voters, and its three proposal accounts.
+fn ballot() -> (Ballot, Vec<TestAccount>) {
+    let ballot = Ballot::new(Vec::new(), 2);
+    let proposals = (0..3)
+        .map(|index| {
+            TestAccount::new(ballot.program_id, proposal(b"p", 0).to_bytes()).with_key(
+                proposal_address(&ballot.program_id, &ballot.system.key, index),
+            )
+        })
+        .collect();
+
+    (ballot, proposals)
+}
+
+impl Ballot {
+    /// Votes for `index` through the proposal account `account`.
+    fn vote_at(
+        &mut self,
+        voter: &mut TestAccount,
+        account: &mut TestAccount,
+        index: u32,
+    ) -> Result<(), ProgramError> {
+        let mut vote = vec![IX_VOTE];
+        vote.extend_from_slice(&index.to_le_bytes());
+        process(
+            &self.program_id,
+            &mut [voter, account, &mut self.system],
+            &vote,
+        )
+    }
+}
+
+fn counts(proposals: &[TestAccount]) -> Vec<VoteCount> {
+    proposals
+        .iter()
+        .map(|account| Proposal::from_bytes(&account.data).unwrap().vote_count)
+        .collect()
+}
+
+#[test]
+fn votes_land_in_their_proposal_accounts() {
+    let (mut ballot, mut proposals) = ballot();
+    let mut first = ballot.voter(2);
+    let mut second = ballot.voter(5);
+    ballot.vote_at(&mut first, &mut proposals[0], 0).unwrap();
+    ballot.vote_at(&mut second, &mut proposals[2], 2).unwrap();
+
+    assert_eq!(counts(&proposals), vec![2, 0, 5]);
+
+    let mut accounts: Vec<&mut TestAccount> = vec![&mut ballot.system];
+    accounts.extend(proposals.iter_mut());
+    process(&ballot.program_id, &mut accounts, &[IX_WINNING_PROPOSAL]).unwrap();
+    assert_eq!(return_data()[..4], 2u32.to_le_bytes());
+}
+
+#[test]
+fn index_of_another_proposal_account_is_rejected() {
+    let (mut ballot, mut proposals) = ballot();
+    let mut voter = ballot.voter(1);
+
+    assert_eq!(
+        ballot.vote_at(&mut voter, &mut proposals[0], 1),
+        Err(VotingError::InvalidProposalIndex.into())
+    );
+    assert_eq!(counts(&proposals), vec![0, 0, 0]);
+}
+
+#[test]
+fn proposal_account_of_another_ballot_is_rejected() {
+    let (mut ballot, mut proposals) = ballot();
+    let mut voter = ballot.voter(1);
+    proposals[0].key = proposal_address(&ballot.program_id, &Pubkey::new_unique(), 0);
+
+    assert_eq!(
+        ballot.vote_at(&mut voter, &mut proposals[0], 0),
+        Err(VotingError::InvalidProposalIndex.into())
+    );
+    assert_eq!(counts(&proposals), vec![0, 0, 0]);
+}
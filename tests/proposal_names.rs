@@ -0,0 +1,41 @@
+use solana_program::pubkey::Pubkey;
+use solana_voting_program::{trim_name, Proposal};
+
+fn padded(name: &str) -> [u8; 32] {
+    let mut bytes = [0; 32];
+    bytes[..name.len()].copy_from_slice(name.as_bytes());
+    bytes
+}
+
+#[test]
+fn trailing_padding_is_stripped() {
+    assert_eq!(trim_name(&padded("Budget")), b"Budget");
+    assert_eq!(trim_name(&padded("Größe")), "Größe".as_bytes());
+}
+
+#[test]
+fn full_width_and_empty_names() {
+    assert_eq!(trim_name(&[b'x'; 32]), &[b'x'; 32][..]);
+    assert!(trim_name(&[0; 32]).is_empty());
+}
+
+#[test]
+fn legacy_proposal_names_are_trimmed() {
+    let proposal = Proposal {
+        name: b"Parks".to_vec(),
+        vote_count: 0,
+        nonce: 0,
+        max_expected_votes: 0,
+        reject_unexpected: false,
+        desc_hash: Pubkey::default().to_bytes(),
+    };
+
+    // A legacy record holds the name in a fixed 32-byte field, zero padded
+    let current = proposal.to_bytes();
+    let mut legacy = padded("Parks").to_vec();
+    legacy.extend_from_slice(&current[Proposal::LEN - Proposal::LEGACY_LEN + 32..]);
+
+    let read = Proposal::from_legacy_bytes(&legacy).unwrap();
+    assert_eq!(read, proposal);
+    assert_eq!(read.name_bytes(), b"Parks");
+}
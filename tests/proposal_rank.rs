@@ -0,0 +1,38 @@
+//! A proposal's rank by vote count, with tied proposals sharing the better
+//! rank.
+
+mod common;
+
+use common::{open_ballot, process, proposal, return_data, TestAccount};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use solana_voting_program::{VoteCount, IX_PROPOSAL_RANK};
+
+/// Ranks proposal `index` of a ballot whose proposals hold `counts`.
+fn rank(counts: &[VoteCount], index: u32) -> Result<u32, ProgramError> {
+    let program_id = Pubkey::new_unique();
+    let mut ballot = open_ballot(Pubkey::new_unique());
+    ballot.proposals = counts.iter().map(|&count| proposal(b"p", count)).collect();
+    let mut system = TestAccount::state(program_id, &ballot, 0);
+
+    let mut query = vec![IX_PROPOSAL_RANK];
+    query.extend_from_slice(&index.to_le_bytes());
+    process(&program_id, &mut [&mut system], &query)?;
+
+    Ok(u32::from_le_bytes(return_data().try_into().unwrap()))
+}
+
+#[test]
+fn middle_proposal_ranks_second() {
+    assert_eq!(rank(&[9, 5, 2], 1), Ok(2));
+}
+
+#[test]
+fn tied_proposals_share_the_better_rank() {
+    assert_eq!(rank(&[9, 5, 5, 2], 2), Ok(2));
+    assert_eq!(rank(&[9, 5, 5, 2], 3), Ok(4));
+}
+
+#[test]
+fn index_past_the_end_is_rejected() {
+    assert!(rank(&[9, 5, 2], 3).is_err());
+}
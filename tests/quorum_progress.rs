@@ -0,0 +1,53 @@
+//! Progress toward the weight quorum and the voter quorum, capped once each is
+//! met.
+
+mod common;
+
+use std::mem::size_of;
+
+use common::{open_ballot, process, return_data, roll_entry, TestAccount};
+use solana_program::pubkey::Pubkey;
+use solana_voting_program::{VoteTotal, IX_QUORUM_PROGRESS};
+
+/// Progress of a roll where voters of weight 2 and 3 have voted and one of
+/// weight 5 has not: the weight voted, the voters who voted, and the basis
+/// points toward each quorum.
+fn progress(weight_quorum: u64, voter_quorum: u32) -> (VoteTotal, u32, u16, u16) {
+    let program_id = Pubkey::new_unique();
+    let mut ballot = open_ballot(Pubkey::new_unique());
+    ballot.voters = vec![
+        roll_entry(2, true),
+        roll_entry(5, false),
+        roll_entry(3, true),
+    ];
+    let mut system = TestAccount::state(program_id, &ballot, 0);
+
+    let mut query = vec![IX_QUORUM_PROGRESS];
+    query.extend_from_slice(&weight_quorum.to_le_bytes());
+    query.extend_from_slice(&voter_quorum.to_le_bytes());
+    process(&program_id, &mut [&mut system], &query).unwrap();
+
+    let data = return_data();
+    let (weight, rest) = data.split_at(size_of::<VoteTotal>());
+    (
+        VoteTotal::from_le_bytes(weight.try_into().unwrap()),
+        u32::from_le_bytes(rest[..4].try_into().unwrap()),
+        u16::from_le_bytes(rest[4..6].try_into().unwrap()),
+        u16::from_le_bytes(rest[6..8].try_into().unwrap()),
+    )
+}
+
+#[test]
+fn partial_progress_toward_each_quorum() {
+    assert_eq!(progress(8, 3), (5, 2, 6250, 6666));
+}
+
+#[test]
+fn progress_is_capped_once_a_quorum_is_met() {
+    assert_eq!(progress(4, 2), (5, 2, 10_000, 10_000));
+}
+
+#[test]
+fn zero_quorums_count_as_met() {
+    assert_eq!(progress(0, 0), (5, 2, 10_000, 10_000));
+}
@@ -0,0 +1,60 @@
+//! The race status weighs the quorum, the lead and the weight still
+//! outstanding, read from the proposals held by the state account.
+
+mod common;
+
+use common::{open_ballot, process, proposal, return_data, roll_entry, TestAccount};
+use solana_program::pubkey::Pubkey;
+use solana_voting_program::{VoteCount, IX_RACE_STATUS};
+
+const QUORUM_NOT_MET: u8 = 0;
+const UNDECIDED: u8 = 1;
+const DECIDED: u8 = 2;
+const TIED: u8 = 3;
+
+/// The race status of a ballot whose proposals hold `counts`, cast by voters
+/// of those weights, with `outstanding` weight yet to vote.
+fn race_status(counts: &[u64], outstanding: u64, weight_quorum: u64) -> u8 {
+    let program_id = Pubkey::new_unique();
+    let mut ballot = open_ballot(Pubkey::new_unique());
+    ballot.voters = counts
+        .iter()
+        .map(|&count| roll_entry(count, true))
+        .collect();
+    if outstanding > 0 {
+        ballot.voters.push(roll_entry(outstanding, false));
+    }
+    ballot.proposals = counts
+        .iter()
+        .map(|&count| proposal(b"Parks", VoteCount::from(count)))
+        .collect();
+    let mut system = TestAccount::state(program_id, &ballot, 0);
+    let mut proposals = TestAccount::new(program_id, system.data.clone()).with_key(system.key);
+
+    let mut query = vec![IX_RACE_STATUS];
+    query.extend_from_slice(&weight_quorum.to_le_bytes());
+    query.extend_from_slice(&0u32.to_le_bytes());
+    process(&program_id, &mut [&mut system, &mut proposals], &query).unwrap();
+
+    return_data()[0]
+}
+
+#[test]
+fn race_below_the_quorum_is_not_met() {
+    assert_eq!(race_status(&[5, 1], 0, 7), QUORUM_NOT_MET);
+}
+
+#[test]
+fn lead_the_outstanding_weight_could_overturn_is_undecided() {
+    assert_eq!(race_status(&[5, 1], 4, 6), UNDECIDED);
+}
+
+#[test]
+fn lead_beyond_the_outstanding_weight_is_decided() {
+    assert_eq!(race_status(&[5, 1], 3, 6), DECIDED);
+}
+
+#[test]
+fn even_counts_with_no_weight_left_are_tied() {
+    assert_eq!(race_status(&[3, 3], 0, 6), TIED);
+}
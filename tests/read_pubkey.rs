@@ -0,0 +1,57 @@
+//! Keys are read from the state account and the instruction payload whole, or
+//! the read is rejected.
+
+mod common;
+
+use common::{open_ballot, process, return_data, TestAccount};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use solana_voting_program::{SimpleVotingSystem, IX_CONFIG, IX_SET_GOV_MINT, IX_WEIGHT_STATS};
+
+#[test]
+fn chairperson_key_is_read_whole() {
+    let program_id = Pubkey::new_unique();
+    let chairperson = Pubkey::new_unique();
+    let mut system = TestAccount::state(program_id, &open_ballot(chairperson), 0);
+
+    let mut config = vec![IX_CONFIG];
+    config.extend_from_slice(&0u32.to_le_bytes());
+    process(&program_id, &mut [&mut system], &config).unwrap();
+
+    // The summary opens with the layout version, then the chairperson
+    assert_eq!(return_data()[1..33], chairperson.to_bytes());
+}
+
+#[test]
+fn state_cut_inside_the_chairperson_key_is_rejected() {
+    let program_id = Pubkey::new_unique();
+    let mut system = TestAccount::state(program_id, &open_ballot(Pubkey::new_unique()), 0);
+    system.data.truncate(1 + 20);
+
+    assert_eq!(
+        process(&program_id, &mut [&mut system], &[IX_WEIGHT_STATS]),
+        Err(ProgramError::InvalidAccountData)
+    );
+}
+
+#[test]
+fn governance_mint_is_read_whole_from_the_payload() {
+    let program_id = Pubkey::new_unique();
+    let mut chairperson = TestAccount::signer();
+    let mut ballot = open_ballot(chairperson.key);
+    ballot.voting_started = false;
+    let mut system = TestAccount::state(program_id, &ballot, 0);
+    let gov_mint = Pubkey::new_unique();
+
+    let mut set = vec![IX_SET_GOV_MINT];
+    set.extend_from_slice(gov_mint.as_ref());
+    assert!(process(
+        &program_id,
+        &mut [&mut chairperson, &mut system],
+        &set[..32]
+    )
+    .is_err());
+    process(&program_id, &mut [&mut chairperson, &mut system], &set).unwrap();
+
+    let state = SimpleVotingSystem::deserialize(&system.data).unwrap();
+    assert_eq!(state.gov_mint, gov_mint);
+}
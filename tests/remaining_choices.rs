@@ -0,0 +1,37 @@
+use solana_voting_program::Voter;
+
+#[test]
+fn voter_with_a_right_has_one_choice_left() {
+    let voter = Voter {
+        weight: 3,
+        voted: false,
+        vote: 0,
+        ..Voter::default()
+    };
+
+    assert_eq!(voter.remaining_choices(), 1);
+}
+
+#[test]
+fn voter_has_no_choices_left_after_voting() {
+    let voter = Voter {
+        weight: 3,
+        voted: true,
+        vote: 1,
+        ..Voter::default()
+    };
+
+    assert_eq!(voter.remaining_choices(), 0);
+}
+
+#[test]
+fn voter_without_a_right_has_no_choices() {
+    let voter = Voter {
+        weight: 0,
+        voted: false,
+        vote: 0,
+        ..Voter::default()
+    };
+
+    assert_eq!(voter.remaining_choices(), 0);
+}
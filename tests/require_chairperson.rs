@@ -0,0 +1,56 @@
+mod common;
+
+use common::{open_ballot, TestAccount};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use solana_voting_program::{require_chairperson, VotingError};
+
+fn check(chairperson: &Pubkey, signer: &Pubkey, is_signer: bool) -> Result<(), ProgramError> {
+    let program_id = Pubkey::new_unique();
+    let mut system = TestAccount::state(program_id, &open_ballot(*chairperson), 0);
+    let mut signer = TestAccount {
+        is_signer,
+        ..TestAccount::signer().with_key(*signer)
+    };
+
+    require_chairperson(&program_id, &system.info(), &signer.info()).map(|_| ())
+}
+
+#[test]
+fn chairperson_signer_is_authorized() {
+    let chairperson = Pubkey::new_unique();
+
+    assert_eq!(check(&chairperson, &chairperson, true), Ok(()));
+}
+
+#[test]
+fn chairperson_without_signature_is_rejected() {
+    let chairperson = Pubkey::new_unique();
+
+    assert_eq!(
+        check(&chairperson, &chairperson, false),
+        Err(ProgramError::MissingRequiredSignature)
+    );
+}
+
+#[test]
+fn other_signer_is_rejected() {
+    let chairperson = Pubkey::new_unique();
+    let other = Pubkey::new_unique();
+
+    assert_eq!(
+        check(&chairperson, &other, true),
+        Err(VotingError::NotChairperson.into())
+    );
+}
+
+#[test]
+fn state_owned_by_another_program_is_rejected() {
+    let program_id = Pubkey::new_unique();
+    let mut chairperson = TestAccount::signer();
+    let mut system = TestAccount::state(Pubkey::new_unique(), &open_ballot(chairperson.key), 0);
+
+    assert_eq!(
+        require_chairperson(&program_id, &system.info(), &chairperson.info()).map(|_| ()),
+        Err(ProgramError::IncorrectProgramId)
+    );
+}
@@ -0,0 +1,74 @@
+mod common;
+
+use common::{open_ballot, process, TestAccount};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use solana_voting_program::{
+    check_quorum, Proposal, SimpleVotingSystem, VoteCount, VotingError, IX_SEAL_RESULTS,
+};
+
+/// Seals a ballot with a quorum of 10 votes, whose only proposal holds
+/// `votes` votes next to `abstained` abstentions.
+fn seal(votes: VoteCount, abstained: u64) -> (Result<(), ProgramError>, bool) {
+    let program_id = Pubkey::new_unique();
+    let mut chairperson = TestAccount::signer();
+    let mut ballot = open_ballot(chairperson.key);
+    ballot.quorum = 10;
+    ballot.abstain_weight = abstained;
+    ballot.proposals.push(Proposal {
+        name: vec![b'x'; 32],
+        vote_count: votes,
+        nonce: 0,
+        max_expected_votes: 0,
+        reject_unexpected: false,
+        desc_hash: [0; 32],
+    });
+    let mut system = TestAccount::state(program_id, &ballot, 0);
+
+    let result = process(
+        &program_id,
+        &mut [&mut chairperson, &mut system],
+        &[IX_SEAL_RESULTS],
+    );
+    let sealed = SimpleVotingSystem::deserialize(&system.data)
+        .unwrap()
+        .results_sealed;
+    (result, sealed)
+}
+
+#[test]
+fn sealing_below_the_ballot_quorum_fails() {
+    assert_eq!(seal(4, 5), (Err(VotingError::QuorumNotMet.into()), false));
+}
+
+#[test]
+fn abstentions_count_toward_the_ballot_quorum() {
+    assert_eq!(seal(4, 6), (Ok(()), true));
+    assert_eq!(seal(10, 0), (Ok(()), true));
+}
+
+#[test]
+fn sealing_below_the_weight_quorum_fails() {
+    assert_eq!(
+        check_quorum(40, 3, 50, 0),
+        Err(VotingError::QuorumNotMet.into())
+    );
+}
+
+#[test]
+fn sealing_below_the_voter_quorum_fails() {
+    assert_eq!(
+        check_quorum(60, 2, 50, 3),
+        Err(VotingError::QuorumNotMet.into())
+    );
+}
+
+#[test]
+fn sealing_at_or_above_the_quorums_succeeds() {
+    assert_eq!(check_quorum(50, 3, 50, 3), Ok(()));
+    assert_eq!(check_quorum(80, 5, 50, 3), Ok(()));
+}
+
+#[test]
+fn zero_quorums_are_always_met() {
+    assert_eq!(check_quorum(0, 0, 0, 0), Ok(()));
+}
@@ -0,0 +1,73 @@
+//! Sealed results are final: votes and count edits are refused afterwards.
+
+mod common;
+
+use std::mem::size_of;
+
+use common::{logs, process, proposal, Ballot};
+use solana_program::program_error::ProgramError;
+use solana_voting_program::{VoteCount, IX_CHANGE_VOTE, IX_SEAL_RESULTS, IX_SET_EXPECTED_VOTES};
+
+fn ballot() -> Ballot {
+    Ballot::new(vec![proposal(b"Parks", 0), proposal(b"Roads", 0)], 2)
+}
+
+impl Ballot {
+    fn seal(&mut self) {
+        process(
+            &self.program_id,
+            &mut [&mut self.chairperson, &mut self.system],
+            &[IX_SEAL_RESULTS],
+        )
+        .unwrap();
+    }
+}
+
+#[test]
+fn vote_after_sealing_is_rejected() {
+    let mut ballot = ballot();
+    let mut voter = ballot.voter(1);
+    ballot.seal();
+
+    // The dispatcher reports the rejection as its generic failure
+    assert_eq!(ballot.vote(&mut voter, 0), Err(ProgramError::Custom(0)));
+    assert!(logs()
+        .iter()
+        .any(|message| message.contains("InvalidArgument")));
+    assert_eq!(ballot.counts(), vec![0, 0]);
+}
+
+#[test]
+fn counts_cannot_be_edited_after_sealing() {
+    let mut ballot = ballot();
+    let mut voter = ballot.voter(1);
+    ballot.vote(&mut voter, 0).unwrap();
+    ballot.seal();
+
+    let mut change = vec![IX_CHANGE_VOTE];
+    change.extend_from_slice(&0u32.to_le_bytes());
+    change.extend_from_slice(&1u32.to_le_bytes());
+    let mut old = ballot.state_alias();
+    let mut new = ballot.state_alias();
+    assert_eq!(
+        process(
+            &ballot.program_id,
+            &mut [&mut voter, &mut old, &mut new, &mut ballot.system],
+            &change,
+        ),
+        Err(ProgramError::InvalidArgument)
+    );
+
+    let mut expect = vec![IX_SET_EXPECTED_VOTES];
+    expect.resize(1 + 5 + size_of::<VoteCount>(), 0);
+    let mut proposals = ballot.state_alias();
+    assert_eq!(
+        process(
+            &ballot.program_id,
+            &mut [&mut ballot.chairperson, &mut ballot.system, &mut proposals],
+            &expect,
+        ),
+        Err(ProgramError::InvalidArgument)
+    );
+    assert_eq!(ballot.counts(), vec![1, 0]);
+}
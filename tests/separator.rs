@@ -0,0 +1,40 @@
+//! The voter roll and the proposals of a state account are divided by an
+//! explicit separator, checked when the state is read.
+
+mod common;
+
+use common::{open_ballot, proposal, roll_entry};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use solana_voting_program::{SimpleVotingSystem, ROLL_ENTRY_LEN};
+
+/// Offset of the voter roll, after the fixed fields and the voter count.
+const ROLL_OFFSET: usize = 1 + 32 + 4 + 64 + 1 + 1 + 8 + 1 + 8 + 8 + 8 + 8 + 32 + 4;
+
+fn ballot() -> SimpleVotingSystem {
+    let mut ballot = open_ballot(Pubkey::new_unique());
+    ballot.voters = vec![roll_entry(1, false), roll_entry(2, true)];
+    ballot.proposals = vec![proposal(b"Parks", 2)];
+    ballot
+}
+
+#[test]
+fn separator_follows_the_roll() {
+    let ballot = ballot();
+    let bytes = ballot.to_bytes();
+    let separator = ROLL_OFFSET + 2 * ROLL_ENTRY_LEN;
+
+    assert_eq!(&bytes[separator..separator + 4], b"PRPS");
+    assert_eq!(SimpleVotingSystem::deserialize(&bytes).unwrap(), ballot);
+}
+
+#[test]
+fn missing_separator_is_rejected() {
+    let mut bytes = ballot().to_bytes();
+    let separator = ROLL_OFFSET + 2 * ROLL_ENTRY_LEN;
+    bytes[separator..separator + 4].copy_from_slice(&[0; 4]);
+
+    assert_eq!(
+        SimpleVotingSystem::deserialize(&bytes),
+        Err(ProgramError::InvalidAccountData)
+    );
+}
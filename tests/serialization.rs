@@ -0,0 +1,357 @@
+//! Round-trip checks for every serialized record: randomly generated
+//! instances plus the edge cases most likely to trip the offset arithmetic.
+
+use borsh::BorshDeserialize;
+use solana_program::{program_error::ProgramError, program_pack::Pack, pubkey::Pubkey};
+use solana_voting_program::{
+    BallotArchive, Endian, Proposal, SimpleVotingSystem, VoteCount, Voter, MAX_NAME_LEN,
+};
+
+/// Minimal xorshift generator, so the cases are reproducible without pulling
+/// in a randomness crate.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next() % bound
+    }
+
+    fn flag(&mut self) -> bool {
+        self.next() & 1 == 1
+    }
+
+    fn count(&mut self) -> VoteCount {
+        self.next() as VoteCount
+    }
+
+    fn bytes<const N: usize>(&mut self) -> [u8; N] {
+        let mut bytes = [0; N];
+        for byte in bytes.iter_mut() {
+            *byte = self.next() as u8;
+        }
+        bytes
+    }
+
+    /// A name of random length, up to the longest a proposal can hold.
+    fn name(&mut self) -> Vec<u8> {
+        let len = self.below(MAX_NAME_LEN as u64 + 1) as usize;
+        (0..len).map(|_| self.next() as u8).collect()
+    }
+
+    fn voter(&mut self) -> Voter {
+        Voter {
+            weight: self.next(),
+            voted: self.flag(),
+            vote: self.next() as u32,
+            delegate: Pubkey::new_from_array(self.bytes()),
+            ballot: Pubkey::new_from_array(self.bytes()),
+            approvals: self.next() as u32,
+        }
+    }
+
+    fn proposal(&mut self) -> Proposal {
+        Proposal {
+            name: self.name(),
+            vote_count: self.count(),
+            nonce: self.next(),
+            max_expected_votes: self.count(),
+            reject_unexpected: self.flag(),
+            desc_hash: self.bytes(),
+        }
+    }
+
+    fn system(&mut self, voters: usize, proposals: usize) -> SimpleVotingSystem {
+        SimpleVotingSystem {
+            chairperson: Pubkey::new_from_array(self.bytes()),
+            min_participation_weight: self.next() as u32,
+            title: self.bytes(),
+            decimals: self.next() as u8,
+            results_sealed: self.flag(),
+            snapshot_slot: self.next(),
+            voting_started: self.flag(),
+            created_slot: self.next(),
+            end_timestamp: self.next() as i64,
+            quorum: self.next(),
+            abstain_weight: self.next(),
+            gov_mint: Pubkey::new_from_array(self.bytes()),
+            voters: (0..voters)
+                .map(|_| (Pubkey::new_from_array(self.bytes()), self.voter()))
+                .collect(),
+            proposals: (0..proposals).map(|_| self.proposal()).collect(),
+        }
+    }
+}
+
+const CASES: usize = 200;
+
+fn round_trip_voter(voter: &Voter) {
+    let bytes = voter.to_bytes();
+    assert_eq!(bytes.len(), Voter::LEN);
+    assert_eq!(&Voter::from_bytes(&bytes).unwrap(), voter);
+
+    let bytes = voter.to_bytes_endian(Endian::Big);
+    assert_eq!(
+        &Voter::from_bytes_endian(&bytes, Endian::Big).unwrap(),
+        voter
+    );
+
+    let mut packed = [0; <Voter as Pack>::LEN];
+    Pack::pack_into_slice(voter, &mut packed);
+    assert_eq!(&Voter::unpack_unchecked(&packed).unwrap(), voter);
+}
+
+fn round_trip_proposal(proposal: &Proposal) {
+    let bytes = proposal.to_bytes();
+    assert_eq!(bytes.len(), Proposal::LEN);
+    assert_eq!(&Proposal::from_bytes(&bytes).unwrap(), proposal);
+    assert_eq!(&Proposal::try_from_slice(&bytes).unwrap(), proposal);
+
+    let bytes = proposal.to_bytes_endian(Endian::Big);
+    assert_eq!(
+        &Proposal::from_bytes_endian(&bytes, Endian::Big).unwrap(),
+        proposal
+    );
+
+    let mut packed = [0; <Proposal as Pack>::LEN];
+    Pack::pack_into_slice(proposal, &mut packed);
+    assert_eq!(&Proposal::unpack_unchecked(&packed).unwrap(), proposal);
+}
+
+fn round_trip_system(system: &SimpleVotingSystem) {
+    let bytes = system.to_bytes();
+    assert_eq!(&SimpleVotingSystem::deserialize(&bytes).unwrap(), system);
+    assert_eq!(&SimpleVotingSystem::try_from_slice(&bytes).unwrap(), system);
+
+    // Spare capacity left at the end of the account is ignored
+    let mut padded = bytes.clone();
+    padded.resize(bytes.len() + 64, 0);
+    assert_eq!(&SimpleVotingSystem::deserialize(&padded).unwrap(), system);
+}
+
+#[test]
+fn random_voters_round_trip() {
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+    for _ in 0..CASES {
+        round_trip_voter(&rng.voter());
+    }
+}
+
+#[test]
+fn random_proposals_round_trip() {
+    let mut rng = Rng(0xdead_beef_cafe_f00d);
+    for _ in 0..CASES {
+        round_trip_proposal(&rng.proposal());
+    }
+}
+
+#[test]
+fn random_systems_round_trip() {
+    let mut rng = Rng(0x0123_4567_89ab_cdef);
+    for _ in 0..CASES / 4 {
+        let voters = rng.below(16) as usize;
+        let proposals = rng.below(16) as usize;
+        round_trip_system(&rng.system(voters, proposals));
+    }
+}
+
+#[test]
+fn random_archives_round_trip() {
+    let mut rng = Rng(0x5555_aaaa_3333_cccc);
+    for _ in 0..CASES {
+        let archive = BallotArchive {
+            winning_proposal: rng.next() as u32,
+            winner_name: rng.name(),
+            total_votes: u128::from(rng.next()) << 64 | u128::from(rng.next()),
+            participants: rng.next() as u32,
+            end_slot: rng.next(),
+            decimals: rng.next() as u8,
+        };
+        let bytes = archive.to_bytes();
+        assert_eq!(bytes.len(), BallotArchive::LEN);
+        assert_eq!(BallotArchive::from_bytes(&bytes).unwrap(), archive);
+    }
+}
+
+#[test]
+fn empty_roll_and_proposal_list_round_trip() {
+    let mut rng = Rng(42);
+    round_trip_system(&rng.system(0, 0));
+    round_trip_system(&rng.system(0, 3));
+    round_trip_system(&rng.system(3, 0));
+}
+
+#[test]
+fn max_size_names_round_trip() {
+    let mut rng = Rng(7);
+    let mut proposal = rng.proposal();
+    proposal.name = vec![0xff; MAX_NAME_LEN];
+    round_trip_proposal(&proposal);
+
+    let mut system = rng.system(1, 2);
+    system.title = [0xff; 64];
+    for proposal in &mut system.proposals {
+        proposal.name = vec![b'x'; MAX_NAME_LEN];
+    }
+    round_trip_system(&system);
+}
+
+#[test]
+fn boundary_weights_and_counts_round_trip() {
+    let mut rng = Rng(99);
+    for weight in [0, 1, u64::from(u32::MAX), u64::from(u32::MAX) + 1, u64::MAX] {
+        let voter = Voter {
+            weight,
+            voted: true,
+            vote: u32::MAX,
+            ..Voter::default()
+        };
+        round_trip_voter(&voter);
+
+        let mut system = rng.system(0, 0);
+        system.voters.push((Pubkey::new_unique(), voter));
+        round_trip_system(&system);
+    }
+
+    for count in [0, 1, VoteCount::MAX - 1, VoteCount::MAX] {
+        let mut proposal = rng.proposal();
+        proposal.vote_count = count;
+        proposal.max_expected_votes = count;
+        round_trip_proposal(&proposal);
+    }
+}
+
+#[test]
+fn records_read_from_larger_accounts() {
+    let mut rng = Rng(1234);
+    let voter = rng.voter();
+    let mut bytes = voter.to_bytes();
+    bytes.extend_from_slice(&[0xaa; 8]);
+    assert_eq!(Voter::from_bytes(&bytes).unwrap(), voter);
+
+    let proposal = rng.proposal();
+    let mut bytes = proposal.to_bytes();
+    bytes.extend_from_slice(&[0xaa; 8]);
+    assert_eq!(Proposal::from_bytes(&bytes).unwrap(), proposal);
+}
+
+#[test]
+fn overlong_proposal_names_are_rejected() {
+    let mut rng = Rng(8080);
+    let mut bytes = rng.proposal().to_bytes();
+    bytes[..2].copy_from_slice(&(MAX_NAME_LEN as u16 + 1).to_le_bytes());
+    assert!(Proposal::from_bytes(&bytes).is_err());
+}
+
+#[test]
+fn overlong_names_are_cut_to_their_slot() {
+    let mut proposal = Rng(9090).proposal();
+    proposal.name = vec![b'n'; MAX_NAME_LEN + 7];
+
+    let bytes = proposal.to_bytes();
+    assert_eq!(bytes.len(), Proposal::LEN);
+    assert_eq!(
+        Proposal::from_bytes(&bytes).unwrap().name,
+        vec![b'n'; MAX_NAME_LEN]
+    );
+}
+
+#[test]
+fn truncated_records_are_rejected() {
+    let mut rng = Rng(4321);
+    let bytes = rng.voter().to_bytes();
+    assert!(Voter::from_bytes(&bytes[..Voter::LEN - 1]).is_err());
+
+    let bytes = rng.proposal().to_bytes();
+    assert!(Proposal::from_bytes(&bytes[..Proposal::LEN - 1]).is_err());
+
+    let bytes = BallotArchive {
+        winning_proposal: 1,
+        winner_name: rng.name(),
+        total_votes: 7,
+        participants: 2,
+        end_slot: rng.next(),
+        decimals: 0,
+    }
+    .to_bytes();
+    assert_eq!(
+        BallotArchive::from_bytes(&bytes[..BallotArchive::LEN - 1]),
+        Err(ProgramError::InvalidAccountData)
+    );
+
+    let bytes = rng.system(2, 2).to_bytes();
+    assert!(SimpleVotingSystem::deserialize(&bytes[..bytes.len() - 1]).is_err());
+}
+
+#[test]
+fn proposal_buffer_cut_inside_the_name_is_rejected() {
+    let bytes = Rng(2468).proposal().to_bytes();
+
+    // 34 bytes end inside the name slot, well before the vote count
+    assert_eq!(
+        Proposal::from_bytes(&bytes[..34]),
+        Err(ProgramError::InvalidAccountData)
+    );
+    assert_eq!(
+        Proposal::from_bytes(&bytes[..2]),
+        Err(ProgramError::InvalidAccountData)
+    );
+    assert_eq!(
+        Proposal::from_bytes(&[]),
+        Err(ProgramError::InvalidAccountData)
+    );
+}
+
+#[test]
+fn every_truncation_of_a_state_account_is_rejected() {
+    let mut rng = Rng(8642);
+    let bytes = rng.system(2, 2).to_bytes();
+
+    // Each cut ends the account inside a different field, none of which may
+    // be read past the end
+    for len in 1..bytes.len() {
+        assert_eq!(
+            SimpleVotingSystem::deserialize(&bytes[..len]),
+            Err(ProgramError::InvalidAccountData),
+            "state cut to {} bytes",
+            len
+        );
+    }
+}
+
+#[test]
+fn pack_checks_length_and_initialization() {
+    let mut rng = Rng(2468);
+    let mut voter = rng.voter();
+    voter.weight = 1;
+    let mut packed = vec![0; <Voter as Pack>::LEN];
+    Voter::pack(voter, &mut packed).unwrap();
+    assert_eq!(Voter::unpack(&packed).unwrap().weight, 1);
+
+    // Pack expects the exact record size, unlike from_bytes
+    packed.push(0);
+    assert_eq!(
+        Voter::unpack(&packed),
+        Err(ProgramError::InvalidAccountData)
+    );
+
+    // A voter without a right to vote has not been initialized
+    let blank = [0; <Voter as Pack>::LEN];
+    assert_eq!(
+        Voter::unpack(&blank),
+        Err(ProgramError::UninitializedAccount)
+    );
+    assert_eq!(Voter::unpack_unchecked(&blank).unwrap().weight, 0);
+
+    let blank = [0; <Proposal as Pack>::LEN];
+    assert_eq!(
+        Proposal::unpack(&blank),
+        Err(ProgramError::UninitializedAccount)
+    );
+}
@@ -0,0 +1,40 @@
+//! Queries given a proposal list account too short for its header are
+//! rejected rather than read past its end.
+
+mod common;
+
+use common::{open_ballot, process, TestAccount};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use solana_voting_program::{IX_WINNER_NAME, IX_WINNING_PROPOSAL};
+
+/// A proposal list account holding only 2 of its 4 header bytes.
+fn short_list(program_id: Pubkey) -> TestAccount {
+    TestAccount::new(program_id, vec![0; 2])
+}
+
+#[test]
+fn winning_proposal_rejects_a_short_list() {
+    let program_id = Pubkey::new_unique();
+    let mut system = TestAccount::state(program_id, &open_ballot(Pubkey::new_unique()), 0);
+    let mut proposals = short_list(program_id);
+
+    assert_eq!(
+        process(
+            &program_id,
+            &mut [&mut system, &mut proposals],
+            &[IX_WINNING_PROPOSAL]
+        ),
+        Err(ProgramError::InvalidAccountData)
+    );
+}
+
+#[test]
+fn winner_name_rejects_a_short_list() {
+    let program_id = Pubkey::new_unique();
+    let mut proposals = short_list(program_id);
+
+    assert_eq!(
+        process(&program_id, &mut [&mut proposals], &[IX_WINNER_NAME]),
+        Err(ProgramError::InvalidAccountData)
+    );
+}
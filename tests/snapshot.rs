@@ -0,0 +1,65 @@
+//! Voting weights stop changing once the ballot's snapshot slot has passed.
+
+mod common;
+
+use common::{process, warp_to_slot, Ballot, TestAccount, SLOT};
+use solana_program::program_error::ProgramError;
+use solana_voting_program::{VotingError, IX_BATCH_REVOKE, IX_SET_SNAPSHOT_SLOT};
+
+/// A ballot whose weights are frozen after `snapshot_slot`, 0 for never.
+fn with_snapshot(snapshot_slot: u64) -> Ballot {
+    let mut ballot = Ballot::new(Vec::new(), 2);
+    let mut snapshot = vec![IX_SET_SNAPSHOT_SLOT];
+    snapshot.extend_from_slice(&snapshot_slot.to_le_bytes());
+    process(
+        &ballot.program_id,
+        &mut [&mut ballot.chairperson, &mut ballot.system],
+        &snapshot,
+    )
+    .unwrap();
+
+    ballot
+}
+
+impl Ballot {
+    fn revoke(&mut self, voter: &mut TestAccount) -> Result<(), ProgramError> {
+        process(
+            &self.program_id,
+            &mut [&mut self.chairperson, &mut self.system, voter],
+            &[IX_BATCH_REVOKE],
+        )
+    }
+}
+
+#[test]
+fn weights_change_until_the_snapshot_slot() {
+    let mut ballot = with_snapshot(SLOT + 500);
+    let mut voter = TestAccount::voter(ballot.program_id);
+
+    assert_eq!(ballot.grant(&mut voter, 3), Ok(()));
+    warp_to_slot(SLOT + 500);
+    assert_eq!(ballot.revoke(&mut voter), Ok(()));
+}
+
+#[test]
+fn weights_are_frozen_after_the_snapshot_slot() {
+    let mut ballot = with_snapshot(SLOT + 500);
+    let mut granted = TestAccount::voter(ballot.program_id);
+    ballot.grant(&mut granted, 3).unwrap();
+    warp_to_slot(SLOT + 501);
+
+    let frozen = Err(VotingError::WeightsFrozen.into());
+    let mut voter = TestAccount::voter(ballot.program_id);
+    assert_eq!(ballot.grant(&mut voter, 3), frozen);
+    assert_eq!(ballot.revoke(&mut granted), frozen);
+    assert!(voter.data.iter().all(|&byte| byte == 0));
+}
+
+#[test]
+fn ballots_without_a_snapshot_never_freeze() {
+    let mut ballot = with_snapshot(0);
+    warp_to_slot(u64::MAX);
+
+    let mut voter = TestAccount::voter(ballot.program_id);
+    assert_eq!(ballot.grant(&mut voter, 3), Ok(()));
+}
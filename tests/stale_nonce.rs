@@ -0,0 +1,68 @@
+//! Votes carrying the proposal nonce the client read are rejected once the
+//! proposal has been written since.
+
+mod common;
+
+use common::{logs, open_ballot, process, proposal_address, TestAccount};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use solana_voting_program::{Proposal, IX_GIVE_RIGHT, IX_VOTE, ROLL_ENTRY_LEN};
+
+/// Casts a vote for proposal 0 that expects the proposal's nonce to be
+/// `expected`, against a proposal written `nonce` times so far.
+fn vote_expecting(nonce: u64, expected: u64) -> (Result<(), ProgramError>, Proposal) {
+    let program_id = Pubkey::new_unique();
+    let mut chairperson = TestAccount::signer();
+    let mut system = TestAccount::state(program_id, &open_ballot(chairperson.key), ROLL_ENTRY_LEN);
+    let mut voter = TestAccount::voter(program_id);
+    let record = Proposal {
+        name: b"Parks".to_vec(),
+        vote_count: 0,
+        nonce,
+        max_expected_votes: 0,
+        reject_unexpected: false,
+        desc_hash: [0; 32],
+    };
+    let mut proposal = TestAccount::new(program_id, record.to_bytes()).with_key(proposal_address(
+        &program_id,
+        &system.key,
+        0,
+    ));
+
+    process(
+        &program_id,
+        &mut [&mut chairperson, &mut system, &mut voter],
+        &[IX_GIVE_RIGHT],
+    )
+    .unwrap();
+
+    let mut vote = vec![IX_VOTE];
+    vote.extend_from_slice(&0u32.to_le_bytes());
+    vote.extend_from_slice(&expected.to_le_bytes());
+    let result = process(
+        &program_id,
+        &mut [&mut voter, &mut proposal, &mut system],
+        &vote,
+    );
+
+    (result, Proposal::from_bytes(&proposal.data).unwrap())
+}
+
+#[test]
+fn stale_nonce_is_rejected() {
+    let (result, proposal) = vote_expecting(3, 2);
+
+    // The dispatcher reports the rejection as its generic failure
+    assert_eq!(result, Err(ProgramError::Custom(0)));
+    assert!(logs().contains(&"Stale proposal nonce: expected 2, found 3".to_string()));
+    assert_eq!(proposal.vote_count, 0);
+    assert_eq!(proposal.nonce, 3);
+}
+
+#[test]
+fn current_nonce_is_accepted_and_bumped() {
+    let (result, proposal) = vote_expecting(3, 3);
+
+    assert_eq!(result, Ok(()));
+    assert_eq!(proposal.vote_count, 1);
+    assert_eq!(proposal.nonce, 4);
+}
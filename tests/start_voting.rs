@@ -0,0 +1,72 @@
+//! Votes are refused during the setup phase, until the chairperson opens
+//! voting.
+
+mod common;
+
+use common::{process, proposal, Ballot, TestAccount};
+use solana_program::program_error::ProgramError;
+use solana_voting_program::{VotingError, IX_START_VOTING};
+
+/// A ballot still in its setup phase, and a voter granted the right to vote
+/// on it.
+fn in_setup() -> (Ballot, TestAccount) {
+    let mut ballot = Ballot::with_state(1, |state| {
+        state.voting_started = false;
+        state.proposals = vec![proposal(b"Parks", 0)];
+    });
+    let voter = ballot.voter(1);
+
+    (ballot, voter)
+}
+
+impl Ballot {
+    fn start_voting(&mut self) -> Result<(), ProgramError> {
+        process(
+            &self.program_id,
+            &mut [&mut self.chairperson, &mut self.system],
+            &[IX_START_VOTING],
+        )
+    }
+}
+
+#[test]
+fn votes_are_refused_before_voting_starts() {
+    let (mut ballot, mut voter) = in_setup();
+
+    assert_eq!(
+        ballot.vote(&mut voter, 0),
+        Err(VotingError::VotingNotStarted.into())
+    );
+    assert_eq!(ballot.state().proposals[0].vote_count, 0);
+}
+
+#[test]
+fn votes_are_accepted_once_voting_starts() {
+    let (mut ballot, mut voter) = in_setup();
+
+    ballot.start_voting().unwrap();
+
+    assert!(ballot.state().voting_started);
+    assert_eq!(ballot.vote(&mut voter, 0), Ok(()));
+    assert_eq!(ballot.state().proposals[0].vote_count, 1);
+}
+
+#[test]
+fn only_the_chairperson_starts_voting() {
+    let (mut ballot, _) = in_setup();
+    ballot.chairperson = TestAccount::signer();
+
+    assert!(ballot.start_voting().is_err());
+    assert!(!ballot.state().voting_started);
+}
+
+#[test]
+fn a_ballot_without_proposals_cannot_start() {
+    let (mut ballot, _) = in_setup();
+    let mut state = ballot.state();
+    state.proposals.clear();
+    ballot.system = TestAccount::state(ballot.program_id, &state, 0).with_key(ballot.system.key);
+
+    assert_eq!(ballot.start_voting(), Err(ProgramError::InvalidArgument));
+    assert!(!ballot.state().voting_started);
+}
@@ -0,0 +1,174 @@
+//! Votes on the proposals held by the state account itself, named at
+//! initialization or added later.
+
+mod common;
+
+use common::{open_ballot, process, return_data, Ballot, TestAccount};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use solana_voting_program::{
+    Proposal, SimpleVotingSystem, Voter, VotingError, IX_ADD_PROPOSAL, IX_APPROVAL_VOTE,
+    IX_CHANGE_VOTE, IX_WINNER_NAME, IX_WINNING_PROPOSAL, MAX_NAME_LEN,
+};
+
+fn named(name: u8) -> Proposal {
+    Proposal {
+        name: vec![name; 32],
+        vote_count: 0,
+        nonce: 0,
+        max_expected_votes: 0,
+        reject_unexpected: false,
+        desc_hash: [0; 32],
+    }
+}
+
+/// An open ballot holding proposals `a` and `b`, with room for two voters on
+/// the roll.
+fn ballot() -> Ballot {
+    Ballot::new(vec![named(b'a'), named(b'b')], 2)
+}
+
+#[test]
+fn vote_is_counted_in_the_state() {
+    let mut ballot = ballot();
+    let mut voter = ballot.voter(3);
+
+    ballot.vote(&mut voter, 1).unwrap();
+
+    assert_eq!(ballot.counts(), vec![0, 3]);
+    let state = ballot.state();
+    assert_eq!(state.proposals[1].nonce, 1);
+    let record = Voter::from_bytes(&voter.data).unwrap();
+    assert_eq!(state.voters, vec![(voter.key, record)]);
+}
+
+#[test]
+fn index_past_the_state_proposals_is_rejected() {
+    let mut ballot = ballot();
+    let mut voter = ballot.voter(1);
+
+    assert_eq!(
+        ballot.vote(&mut voter, 2),
+        Err(VotingError::InvalidProposalIndex.into())
+    );
+    assert!(!Voter::from_bytes(&voter.data).unwrap().voted);
+}
+
+#[test]
+fn winner_is_read_from_the_state() {
+    let mut ballot = ballot();
+    let mut voter = ballot.voter(2);
+    ballot.vote(&mut voter, 1).unwrap();
+
+    let mut proposals = ballot.state_alias();
+    process(
+        &ballot.program_id,
+        &mut [&mut ballot.system, &mut proposals],
+        &[IX_WINNING_PROPOSAL],
+    )
+    .unwrap();
+    assert_eq!(return_data(), [1, 0, 0, 0, 1, 0, 0, 0]);
+
+    process(
+        &ballot.program_id,
+        &mut [&mut ballot.system],
+        &[IX_WINNER_NAME],
+    )
+    .unwrap();
+    assert_eq!(return_data(), vec![b'b'; 32]);
+}
+
+#[test]
+fn vote_moves_between_state_proposals() {
+    let mut ballot = ballot();
+    let mut voter = ballot.voter(4);
+    ballot.vote(&mut voter, 0).unwrap();
+
+    let mut change = vec![IX_CHANGE_VOTE];
+    change.extend_from_slice(&0u32.to_le_bytes());
+    change.extend_from_slice(&1u32.to_le_bytes());
+    let mut old = ballot.state_alias();
+    let mut new = ballot.state_alias();
+    process(
+        &ballot.program_id,
+        &mut [&mut voter, &mut old, &mut new, &mut ballot.system],
+        &change,
+    )
+    .unwrap();
+
+    assert_eq!(ballot.counts(), vec![0, 4]);
+    assert_eq!(Voter::from_bytes(&voter.data).unwrap().vote, 1);
+}
+
+#[test]
+fn approvals_are_counted_in_the_state() {
+    let mut ballot = ballot();
+    let mut voter = ballot.voter(5);
+
+    let mut approve = vec![IX_APPROVAL_VOTE];
+    approve.extend_from_slice(&0b11u32.to_le_bytes());
+    let mut proposals = ballot.state_alias();
+    process(
+        &ballot.program_id,
+        &mut [&mut voter, &mut ballot.system, &mut proposals],
+        &approve,
+    )
+    .unwrap();
+
+    assert_eq!(ballot.counts(), vec![5, 5]);
+}
+
+#[test]
+fn added_proposal_keeps_its_description_hash() {
+    let program_id = Pubkey::new_unique();
+    let mut chairperson = TestAccount::signer();
+    let mut closed = open_ballot(chairperson.key);
+    closed.voting_started = false;
+    let mut system = TestAccount::state(program_id, &closed, Proposal::LEN);
+
+    let mut add = vec![IX_ADD_PROPOSAL];
+    add.extend_from_slice(&32u16.to_le_bytes());
+    add.extend_from_slice(&[b'c'; 32]);
+    add.extend_from_slice(&[7; 32]);
+    process(&program_id, &mut [&mut chairperson, &mut system], &add).unwrap();
+
+    let proposals = SimpleVotingSystem::deserialize(&system.data)
+        .unwrap()
+        .proposals;
+    assert_eq!(proposals.len(), 1);
+    assert_eq!(proposals[0].name, vec![b'c'; 32]);
+    assert_eq!(proposals[0].desc_hash, [7; 32]);
+}
+
+/// Adds a proposal named `name`, with no description hash, to a ballot that
+/// has not started.
+fn add_named(name: &[u8]) -> Result<Vec<Proposal>, ProgramError> {
+    let program_id = Pubkey::new_unique();
+    let mut chairperson = TestAccount::signer();
+    let mut closed = open_ballot(chairperson.key);
+    closed.voting_started = false;
+    let mut system = TestAccount::state(program_id, &closed, Proposal::LEN);
+
+    let mut add = vec![IX_ADD_PROPOSAL];
+    add.extend_from_slice(&(name.len() as u16).to_le_bytes());
+    add.extend_from_slice(name);
+    process(&program_id, &mut [&mut chairperson, &mut system], &add)?;
+
+    Ok(SimpleVotingSystem::deserialize(&system.data)
+        .unwrap()
+        .proposals)
+}
+
+#[test]
+fn names_up_to_the_limit_are_kept_whole() {
+    for len in [1, 33, MAX_NAME_LEN] {
+        let name = vec![b'n'; len];
+        assert_eq!(add_named(&name).unwrap()[0].name, name);
+    }
+}
+
+#[test]
+fn empty_and_overlong_names_are_rejected() {
+    assert!(add_named(b"").is_err());
+    assert!(add_named(&[b'n'; MAX_NAME_LEN + 1]).is_err());
+    assert!(add_named(b"Pa\0rks").is_err());
+}
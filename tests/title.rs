@@ -0,0 +1,46 @@
+//! The chairperson titles the ballot; anyone reads the title back without its
+//! padding.
+
+mod common;
+
+use common::{process, return_data, Ballot};
+use solana_voting_program::{IX_GET_TITLE, IX_SET_TITLE};
+
+impl Ballot {
+    fn set_title(&mut self, title: &[u8]) {
+        let mut set = vec![IX_SET_TITLE];
+        set.extend_from_slice(title);
+        set.resize(1 + 64, 0);
+        process(
+            &self.program_id,
+            &mut [&mut self.chairperson, &mut self.system],
+            &set,
+        )
+        .unwrap();
+    }
+
+    fn get_title(&mut self) -> Vec<u8> {
+        process(&self.program_id, &mut [&mut self.system], &[IX_GET_TITLE]).unwrap();
+        return_data()
+    }
+}
+
+#[test]
+fn title_round_trips_without_its_padding() {
+    let mut ballot = Ballot::new(Vec::new(), 0);
+    ballot.set_title(b"City budget 2027");
+
+    assert_eq!(ballot.get_title(), b"City budget 2027");
+    let mut stored = [0; 64];
+    stored[..16].copy_from_slice(b"City budget 2027");
+    assert_eq!(ballot.state().title, stored);
+}
+
+#[test]
+fn full_width_title_and_no_title() {
+    let mut ballot = Ballot::new(Vec::new(), 0);
+    assert!(ballot.get_title().is_empty());
+
+    ballot.set_title(&[b't'; 64]);
+    assert_eq!(ballot.get_title(), vec![b't'; 64]);
+}
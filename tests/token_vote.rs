@@ -0,0 +1,160 @@
+//! Token-weighted votes count the holder's balance of the ballot's mint, once
+//! per holder.
+
+mod common;
+
+use common::{process, proposal, proposal_address, token_voter_address, Ballot, TestAccount};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use solana_voting_program::{
+    Proposal, Voter, VotingError, IX_VOTE_WITH_TOKEN, TOKEN_PROGRAM_ID, TOKEN_VOTER_LEN,
+};
+
+/// An initialized SPL Token account holding `amount` of `mint` for `owner`.
+fn token_account(mint: &Pubkey, owner: &Pubkey, amount: u64) -> TestAccount {
+    let mut data = vec![0; 165];
+    data[..32].copy_from_slice(mint.as_ref());
+    data[32..64].copy_from_slice(owner.as_ref());
+    data[64..72].copy_from_slice(&amount.to_le_bytes());
+    data[108] = 1;
+    TestAccount::new(TOKEN_PROGRAM_ID, data)
+}
+
+/// A blank token voter record, already created at the address derived for
+/// `owner`.
+fn token_voter(program_id: &Pubkey, system: &Pubkey, owner: &Pubkey) -> TestAccount {
+    TestAccount::new(*program_id, vec![0; TOKEN_VOTER_LEN])
+        .with_key(token_voter_address(program_id, system, owner))
+}
+
+/// A ballot weighing votes by balances of `mint`, with the holder casting
+/// them, their token voter record and the account of proposal 0.
+struct TokenBallot {
+    ballot: Ballot,
+    mint: Pubkey,
+    holder: TestAccount,
+    voter: TestAccount,
+    proposal: TestAccount,
+}
+
+impl TokenBallot {
+    fn new() -> Self {
+        let mint = Pubkey::new_unique();
+        let ballot = Ballot::with_state(2, |state| state.gov_mint = mint);
+        let holder = TestAccount::signer();
+        let voter = token_voter(&ballot.program_id, &ballot.system.key, &holder.key);
+        let proposal = TestAccount::new(ballot.program_id, proposal(&[b'x'; 32], 0).to_bytes())
+            .with_key(proposal_address(&ballot.program_id, &ballot.system.key, 0));
+
+        TokenBallot {
+            ballot,
+            mint,
+            holder,
+            voter,
+            proposal,
+        }
+    }
+
+    fn vote(&mut self, token: &mut TestAccount) -> Result<(), ProgramError> {
+        let mut vote = vec![IX_VOTE_WITH_TOKEN];
+        vote.extend_from_slice(&0u32.to_le_bytes());
+        process(
+            &self.ballot.program_id,
+            &mut [
+                &mut self.voter,
+                token,
+                &mut self.holder,
+                &mut self.proposal,
+                &mut self.ballot.system,
+            ],
+            &vote,
+        )
+    }
+}
+
+#[test]
+fn token_balance_weighs_the_vote() {
+    let mut ballot = TokenBallot::new();
+    let mut token = token_account(&ballot.mint, &ballot.holder.key, 250);
+
+    ballot.vote(&mut token).unwrap();
+
+    let proposal = Proposal::from_bytes(&ballot.proposal.data).unwrap();
+    assert_eq!(proposal.vote_count, 250);
+    let voter = Voter::from_bytes(&ballot.voter.data).unwrap();
+    assert!(voter.voted);
+    assert_eq!(voter.weight, 250);
+    assert_eq!(
+        ballot.voter.data[Voter::LEN..],
+        ballot.holder.key.to_bytes()
+    );
+}
+
+#[test]
+fn holder_cannot_vote_again_with_another_balance() {
+    let mut ballot = TokenBallot::new();
+    let mut token = token_account(&ballot.mint, &ballot.holder.key, 250);
+    ballot.vote(&mut token).unwrap();
+
+    // The holder's own record has already voted
+    let mut refilled = token_account(&ballot.mint, &ballot.holder.key, 400);
+    assert_eq!(
+        ballot.vote(&mut refilled),
+        Err(VotingError::AlreadyVoted.into())
+    );
+
+    // and a fresh record at any other address is refused
+    ballot.voter = TestAccount::new(ballot.ballot.program_id, vec![0; TOKEN_VOTER_LEN]);
+    assert_eq!(
+        ballot.vote(&mut refilled),
+        Err(ProgramError::InvalidArgument)
+    );
+
+    // as is the record derived for the holder on another ballot
+    let other_ballot = Pubkey::new_unique();
+    ballot.voter = token_voter(&ballot.ballot.program_id, &other_ballot, &ballot.holder.key);
+    assert_eq!(
+        ballot.vote(&mut refilled),
+        Err(ProgramError::InvalidArgument)
+    );
+    assert_eq!(
+        Proposal::from_bytes(&ballot.proposal.data)
+            .unwrap()
+            .vote_count,
+        250
+    );
+}
+
+#[test]
+fn foreign_tokens_are_rejected() {
+    let mut ballot = TokenBallot::new();
+
+    let mut other_mint = token_account(&Pubkey::new_unique(), &ballot.holder.key, 250);
+    assert_eq!(
+        ballot.vote(&mut other_mint),
+        Err(ProgramError::InvalidArgument)
+    );
+
+    let mut other_owner = token_account(&ballot.mint, &Pubkey::new_unique(), 250);
+    assert_eq!(
+        ballot.vote(&mut other_owner),
+        Err(ProgramError::InvalidArgument)
+    );
+
+    let mut forged = token_account(&ballot.mint, &ballot.holder.key, 250);
+    forged.owner = ballot.ballot.program_id;
+    assert_eq!(
+        ballot.vote(&mut forged),
+        Err(ProgramError::IncorrectProgramId)
+    );
+}
+
+#[test]
+fn empty_balance_has_no_right_to_vote() {
+    let mut ballot = TokenBallot::new();
+    let mut token = token_account(&ballot.mint, &ballot.holder.key, 0);
+
+    assert_eq!(
+        ballot.vote(&mut token),
+        Err(VotingError::NoRightToVote.into())
+    );
+}
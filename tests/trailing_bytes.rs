@@ -0,0 +1,59 @@
+//! Instructions without a payload reject any bytes after their tag.
+
+mod common;
+
+use common::{open_ballot, process, TestAccount};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use solana_voting_program::{
+    IX_CONTESTED_COUNT, IX_CREATED_AT, IX_START_VOTING, IX_TOTAL_VOTES, IX_WEIGHT_STATS,
+    IX_WINNER_NAME, IX_WINNING_PROPOSAL,
+};
+
+const TAG_ONLY: [u8; 7] = [
+    IX_WINNING_PROPOSAL,
+    IX_WINNER_NAME,
+    IX_TOTAL_VOTES,
+    IX_WEIGHT_STATS,
+    IX_CONTESTED_COUNT,
+    IX_START_VOTING,
+    IX_CREATED_AT,
+];
+
+/// Runs `instruction_data` with a chairperson and a state account, enough
+/// accounts for every instruction tested here.
+fn run(instruction_data: &[u8]) -> Result<(), ProgramError> {
+    let program_id = Pubkey::new_unique();
+    let mut system = TestAccount::state(program_id, &open_ballot(Pubkey::new_unique()), 0);
+    let mut other = TestAccount::signer();
+    let mut third = TestAccount::signer();
+    process(
+        &program_id,
+        &mut [&mut system, &mut other, &mut third],
+        instruction_data,
+    )
+}
+
+#[test]
+fn trailing_junk_is_rejected() {
+    for tag in TAG_ONLY {
+        for junk in [&[0][..], &[0xde, 0xad, 0xbe, 0xef]] {
+            let mut instruction_data = vec![tag];
+            instruction_data.extend_from_slice(junk);
+            assert_eq!(
+                run(&instruction_data),
+                Err(ProgramError::InvalidInstructionData),
+                "instruction {} with {} trailing bytes",
+                tag,
+                junk.len()
+            );
+        }
+    }
+}
+
+#[test]
+fn bare_tag_passes_the_payload_check() {
+    // The accounts are wrong for most of these, but the tag alone is accepted
+    for tag in TAG_ONLY {
+        assert_ne!(run(&[tag]), Err(ProgramError::InvalidInstructionData));
+    }
+}
@@ -0,0 +1,64 @@
+//! The voters holding weight who have not voted yet, listed a page at a time.
+
+mod common;
+
+use common::{open_ballot, process, return_data, roll_entry, TestAccount};
+use solana_program::pubkey::Pubkey;
+use solana_voting_program::IX_UNVOTED_VOTERS;
+
+struct Roll {
+    program_id: Pubkey,
+    system: TestAccount,
+    unvoted: Vec<Pubkey>,
+}
+
+impl Roll {
+    /// A roll of voted, unvoted and revoked voters, interleaved.
+    fn new() -> Self {
+        let program_id = Pubkey::new_unique();
+        let mut ballot = open_ballot(Pubkey::new_unique());
+        ballot.voters = vec![
+            roll_entry(2, true),
+            roll_entry(3, false),
+            roll_entry(0, false),
+            roll_entry(1, false),
+            roll_entry(4, true),
+            roll_entry(6, false),
+        ];
+        let unvoted = [1, 3, 5].iter().map(|&i| ballot.voters[i].0).collect();
+
+        Roll {
+            program_id,
+            system: TestAccount::state(program_id, &ballot, 0),
+            unvoted,
+        }
+    }
+
+    fn page(&mut self, offset: u32, limit: u32) -> Vec<Pubkey> {
+        let mut query = vec![IX_UNVOTED_VOTERS];
+        query.extend_from_slice(&offset.to_le_bytes());
+        query.extend_from_slice(&limit.to_le_bytes());
+        process(&self.program_id, &mut [&mut self.system], &query).unwrap();
+
+        return_data()
+            .chunks(32)
+            .map(|key| Pubkey::new_from_array(key.try_into().unwrap()))
+            .collect()
+    }
+}
+
+#[test]
+fn only_unvoted_voters_with_weight_are_listed() {
+    let mut roll = Roll::new();
+
+    assert_eq!(roll.page(0, 10), roll.unvoted);
+}
+
+#[test]
+fn list_is_paginated() {
+    let mut roll = Roll::new();
+
+    assert_eq!(roll.page(1, 1), roll.unvoted[1..2]);
+    assert_eq!(roll.page(2, 5), roll.unvoted[2..]);
+    assert!(roll.page(3, 5).is_empty());
+}
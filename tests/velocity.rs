@@ -0,0 +1,120 @@
+//! Records the slot of each vote in the ballot's velocity account, and no
+//! other, and counts the recent ones.
+
+mod common;
+
+use common::{
+    process, process_infos, proposal, return_data, velocity_address, warp_to_slot, Ballot,
+    TestAccount, SLOT,
+};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use solana_voting_program::{IX_RECENT_VELOCITY, IX_VOTE};
+
+/// Size of a velocity account: the next entry to write, then 64 slots.
+const VELOCITY_LEN: usize = 4 + 64 * 8;
+
+/// An open ballot holding one proposal, with room for `voters` voters on the
+/// roll, and its velocity account.
+fn with_velocity(voters: usize) -> (Ballot, TestAccount) {
+    let ballot = Ballot::new(vec![proposal(b"Parks", 0)], voters);
+    let velocity = TestAccount::new(ballot.program_id, vec![0; VELOCITY_LEN])
+        .with_key(velocity_address(&ballot.program_id, &ballot.system.key));
+
+    (ballot, velocity)
+}
+
+impl Ballot {
+    /// Grants a new voter the right to vote and votes in the current slot,
+    /// with `velocity` passed as the velocity account.
+    fn vote_with(&mut self, velocity: &mut TestAccount) -> Result<(), ProgramError> {
+        let mut voter = self.voter(1);
+        let mut proposals = self.state_alias();
+        process(
+            &self.program_id,
+            &mut [&mut voter, &mut proposals, &mut self.system, velocity],
+            &vote_data(),
+        )
+    }
+
+    fn recent_votes(&self, velocity: &mut TestAccount, slots: u64) -> u32 {
+        let mut query = vec![IX_RECENT_VELOCITY];
+        query.extend_from_slice(&slots.to_le_bytes());
+        process(&self.program_id, &mut [velocity], &query).unwrap();
+
+        u32::from_le_bytes(return_data().try_into().unwrap())
+    }
+}
+
+fn vote_data() -> Vec<u8> {
+    let mut vote = vec![IX_VOTE];
+    vote.extend_from_slice(&0u32.to_le_bytes());
+    vote
+}
+
+#[test]
+fn votes_are_counted_within_the_window() {
+    let (mut ballot, mut velocity) = with_velocity(3);
+    for slot in [SLOT, SLOT + 5, SLOT + 10] {
+        warp_to_slot(slot);
+        ballot.vote_with(&mut velocity).unwrap();
+    }
+
+    assert_eq!(ballot.recent_votes(&mut velocity, 1), 1);
+    assert_eq!(ballot.recent_votes(&mut velocity, 6), 2);
+    assert_eq!(ballot.recent_votes(&mut velocity, 11), 3);
+
+    // Later on, the same votes have left the shorter windows
+    warp_to_slot(SLOT + 20);
+    assert_eq!(ballot.recent_votes(&mut velocity, 6), 0);
+    assert_eq!(ballot.recent_votes(&mut velocity, 21), 3);
+}
+
+#[test]
+fn burst_past_the_buffer_saturates() {
+    let (mut ballot, mut velocity) = with_velocity(70);
+    for _ in 0..70 {
+        ballot.vote_with(&mut velocity).unwrap();
+    }
+
+    assert_eq!(ballot.recent_votes(&mut velocity, 1), 64);
+}
+
+#[test]
+fn velocity_account_of_another_ballot_is_rejected() {
+    let (mut ballot, _) = with_velocity(1);
+    let (mut other, _) = with_velocity(0);
+    other.program_id = ballot.program_id;
+    let mut velocity = TestAccount::new(ballot.program_id, vec![0; VELOCITY_LEN])
+        .with_key(velocity_address(&ballot.program_id, &other.system.key));
+
+    assert_eq!(
+        ballot.vote_with(&mut velocity),
+        Err(ProgramError::InvalidAccountData)
+    );
+    assert!(velocity.data.iter().all(|&byte| byte == 0));
+}
+
+#[test]
+fn velocity_account_owned_by_another_program_is_rejected() {
+    let (mut ballot, velocity) = with_velocity(1);
+    let mut foreign =
+        TestAccount::new(Pubkey::new_unique(), vec![0; VELOCITY_LEN]).with_key(velocity.key);
+
+    // An incorrect owner is reported as the generic custom error 0
+    assert_eq!(ballot.vote_with(&mut foreign), Err(ProgramError::Custom(0)));
+    assert!(foreign.data.iter().all(|&byte| byte == 0));
+}
+
+#[test]
+fn state_passed_as_the_velocity_account_is_rejected() {
+    let (mut ballot, _) = with_velocity(1);
+    let mut voter = ballot.voter(1);
+    let program_id = ballot.program_id;
+    let system = ballot.system.info();
+    let infos = [voter.info(), system.clone(), system.clone(), system];
+
+    assert_eq!(
+        process_infos(&program_id, &infos, &vote_data()),
+        Err(ProgramError::InvalidAccountData)
+    );
+}
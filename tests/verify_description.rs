@@ -0,0 +1,62 @@
+mod common;
+
+use common::{open_ballot, process, proposal, return_data, TestAccount};
+use solana_program::{hash::hash, pubkey::Pubkey};
+use solana_voting_program::{Proposal, IX_VERIFY_DESCRIPTION};
+
+fn proposal_with_description(description: &[u8]) -> Proposal {
+    Proposal {
+        name: b"Budget".to_vec(),
+        vote_count: 0,
+        nonce: 0,
+        max_expected_votes: 0,
+        reject_unexpected: false,
+        desc_hash: hash(description).to_bytes(),
+    }
+}
+
+#[test]
+fn matching_description_verifies() {
+    let description = b"Allocate the 2027 budget to the community fund";
+    let proposal = proposal_with_description(description);
+
+    assert!(proposal.matches_description(description));
+}
+
+#[test]
+fn mismatching_description_fails() {
+    let proposal = proposal_with_description(b"Allocate the 2027 budget to the community fund");
+
+    assert!(!proposal.matches_description(b"Allocate the 2027 budget to the treasury"));
+    assert!(!proposal.matches_description(b""));
+}
+
+#[test]
+fn description_hash_survives_serialization() {
+    let description = b"Allocate the 2027 budget to the community fund";
+    let bytes = proposal_with_description(description).to_bytes();
+    let proposal = Proposal::from_bytes(&bytes).unwrap();
+
+    assert!(proposal.matches_description(description));
+}
+
+#[test]
+fn description_of_a_proposal_held_by_the_state_verifies() {
+    let program_id = Pubkey::new_unique();
+    let description = b"Allocate the 2027 budget to the community fund";
+    let mut ballot = open_ballot(Pubkey::new_unique());
+    ballot.proposals = vec![
+        proposal(b"Parks", 0),
+        proposal_with_description(description),
+    ];
+    let mut system = TestAccount::state(program_id, &ballot, 0);
+
+    for (description, matches) in [(&description[..], 1), (b"Fund the treasury", 0)] {
+        let mut verify = vec![IX_VERIFY_DESCRIPTION];
+        verify.extend_from_slice(&1u32.to_le_bytes());
+        verify.extend_from_slice(description);
+        process(&program_id, &mut [&mut system], &verify).unwrap();
+
+        assert_eq!(return_data(), [matches]);
+    }
+}
@@ -0,0 +1,149 @@
+//! Pins the errors the entrypoint reports for rejected grants and votes.
+
+mod common;
+
+use common::{process, proposal, proposal_address, Ballot, TestAccount};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use solana_voting_program::{Proposal, VoteCount, Voter, VotingError, IX_VOTE};
+
+/// An open ballot with room for one voter, a voter yet to be granted the
+/// right to vote and the account of proposal 0.
+fn with_proposal() -> (Ballot, TestAccount, TestAccount) {
+    let ballot = Ballot::new(Vec::new(), 1);
+    let voter = TestAccount::voter(ballot.program_id);
+    let proposal = TestAccount::new(ballot.program_id, proposal(&[b'x'; 32], 0).to_bytes())
+        .with_key(proposal_address(&ballot.program_id, &ballot.system.key, 0));
+
+    (ballot, voter, proposal)
+}
+
+impl Ballot {
+    /// Votes for proposal 0 through its proposal account.
+    fn vote_at(
+        &mut self,
+        voter: &mut TestAccount,
+        proposal: &mut TestAccount,
+    ) -> Result<(), ProgramError> {
+        let mut vote = vec![IX_VOTE];
+        vote.extend_from_slice(&0u32.to_le_bytes());
+        process(
+            &self.program_id,
+            &mut [voter, proposal, &mut self.system],
+            &vote,
+        )
+    }
+}
+
+fn vote_count(proposal: &TestAccount) -> VoteCount {
+    Proposal::from_bytes(&proposal.data).unwrap().vote_count
+}
+
+#[test]
+fn voting_twice_is_rejected() {
+    let (mut ballot, mut voter, mut proposal) = with_proposal();
+    ballot.grant(&mut voter, 1).unwrap();
+    ballot.vote_at(&mut voter, &mut proposal).unwrap();
+
+    assert_eq!(
+        ballot.vote_at(&mut voter, &mut proposal),
+        Err(VotingError::AlreadyVoted.into())
+    );
+    assert_eq!(vote_count(&proposal), 1);
+}
+
+#[test]
+fn voter_without_a_right_cannot_vote() {
+    let (mut ballot, mut voter, mut proposal) = with_proposal();
+
+    assert_eq!(
+        ballot.vote_at(&mut voter, &mut proposal),
+        Err(VotingError::NoRightToVote.into())
+    );
+    assert_eq!(vote_count(&proposal), 0);
+}
+
+#[test]
+fn grant_from_another_signer_is_rejected() {
+    let (mut ballot, mut voter, _) = with_proposal();
+    ballot.chairperson = TestAccount::signer();
+
+    assert_eq!(
+        ballot.grant(&mut voter, 1),
+        Err(VotingError::NotChairperson.into())
+    );
+    assert_eq!(voter.data, vec![0; voter.data.len()]);
+}
+
+#[test]
+fn unsigned_grant_is_rejected() {
+    let (mut ballot, mut voter, _) = with_proposal();
+    ballot.chairperson.is_signer = false;
+
+    // Errors that are not program-specific are reported as the generic custom
+    // error 0
+    assert_eq!(ballot.grant(&mut voter, 1), Err(ProgramError::Custom(0)));
+    assert_eq!(voter.data, vec![0; voter.data.len()]);
+}
+
+#[test]
+fn unsigned_vote_is_rejected() {
+    let (mut ballot, mut voter, mut proposal) = with_proposal();
+    ballot.grant(&mut voter, 1).unwrap();
+    voter.is_signer = false;
+
+    assert_eq!(
+        ballot.vote_at(&mut voter, &mut proposal),
+        Err(ProgramError::Custom(0))
+    );
+    assert_eq!(vote_count(&proposal), 0);
+    assert!(!Voter::from_bytes(&voter.data).unwrap().voted);
+}
+
+#[test]
+fn voter_passed_as_the_proposal_is_rejected() {
+    let (mut ballot, mut voter, _) = with_proposal();
+    ballot.grant(&mut voter, 1).unwrap();
+    let mut alias = TestAccount::new(ballot.program_id, voter.data.clone()).with_key(voter.key);
+
+    // Invalid account data is passed through rather than reported as the
+    // generic error
+    let mut vote = vec![IX_VOTE];
+    vote.extend_from_slice(&0u32.to_le_bytes());
+    assert_eq!(
+        process(
+            &ballot.program_id,
+            &mut [&mut voter, &mut alias, &mut ballot.system],
+            &vote,
+        ),
+        Err(ProgramError::InvalidAccountData)
+    );
+    assert!(!Voter::from_bytes(&voter.data).unwrap().voted);
+}
+
+#[test]
+fn state_owned_by_another_program_is_rejected() {
+    let (mut ballot, mut voter, _) = with_proposal();
+    ballot.system.owner = Pubkey::new_unique();
+
+    assert_eq!(ballot.grant(&mut voter, 1), Err(ProgramError::Custom(0)));
+    assert_eq!(voter.data, vec![0; voter.data.len()]);
+}
+
+#[test]
+fn voter_granted_on_another_ballot_cannot_vote() {
+    let (mut ballot, mut voter, _) = with_proposal();
+    ballot.grant(&mut voter, 1).unwrap();
+    let (mut other, _, mut proposal) = with_proposal();
+    other.program_id = ballot.program_id;
+    other.system.owner = ballot.program_id;
+    proposal.owner = ballot.program_id;
+    proposal.key = proposal_address(&ballot.program_id, &other.system.key, 0);
+    let mut copied = TestAccount::new(ballot.program_id, voter.data.clone());
+    copied.is_signer = true;
+
+    assert_eq!(
+        other.vote_at(&mut copied, &mut proposal),
+        Err(VotingError::NoRightToVote.into())
+    );
+    assert_eq!(vote_count(&proposal), 0);
+}
@@ -0,0 +1,147 @@
+//! A grant followed by a vote, and the records it leaves behind in the voter
+//! account and the proposals.
+
+mod common;
+
+use common::{open_ballot, process, proposal_address, proposal_list, TestAccount};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use solana_voting_program::{
+    Proposal, SimpleVotingSystem, Voter, VotingError, IX_GIVE_RIGHT, IX_VOTE, ROLL_ENTRY_LEN,
+};
+
+fn named(name: &str) -> Proposal {
+    Proposal {
+        name: name.as_bytes().to_vec(),
+        vote_count: 0,
+        nonce: 0,
+        max_expected_votes: 0,
+        reject_unexpected: false,
+        desc_hash: [0; 32],
+    }
+}
+
+#[test]
+fn granted_voter_casts_a_vote() {
+    let program_id = Pubkey::new_unique();
+    let mut chairperson = TestAccount::signer();
+    let mut system = TestAccount::state(program_id, &open_ballot(chairperson.key), ROLL_ENTRY_LEN);
+    let mut voter = TestAccount::voter(program_id);
+    let mut proposal = TestAccount::new(program_id, named("Parks").to_bytes())
+        .with_key(proposal_address(&program_id, &system.key, 0));
+
+    process(
+        &program_id,
+        &mut [&mut chairperson, &mut system, &mut voter],
+        &[IX_GIVE_RIGHT],
+    )
+    .unwrap();
+    assert_eq!(Voter::from_bytes(&voter.data).unwrap().weight, 1);
+
+    let mut vote = vec![IX_VOTE];
+    vote.extend_from_slice(&0u32.to_le_bytes());
+    process(
+        &program_id,
+        &mut [&mut voter, &mut proposal, &mut system],
+        &vote,
+    )
+    .unwrap();
+
+    let proposal = Proposal::from_bytes(&proposal.data).unwrap();
+    assert_eq!(proposal.vote_count, 1);
+    assert_eq!(proposal.nonce, 1);
+    let record = Voter::from_bytes(&voter.data).unwrap();
+    assert!(record.voted);
+    assert_eq!(record.vote, 0);
+
+    // The roll in the state account mirrors the voter record
+    let roll = SimpleVotingSystem::deserialize(&system.data)
+        .unwrap()
+        .voters;
+    assert_eq!(roll, vec![(voter.key, record)]);
+}
+
+#[test]
+fn grant_needs_room_for_the_roll_entry() {
+    let program_id = Pubkey::new_unique();
+    let mut chairperson = TestAccount::signer();
+    let mut system = TestAccount::state(program_id, &open_ballot(chairperson.key), 0);
+    let mut voter = TestAccount::voter(program_id);
+
+    assert_eq!(
+        process(
+            &program_id,
+            &mut [&mut chairperson, &mut system, &mut voter],
+            &[IX_GIVE_RIGHT],
+        ),
+        Err(ProgramError::Custom(0))
+    );
+    assert_eq!(voter.data, vec![0; Voter::LEN]);
+}
+
+#[test]
+fn weighted_vote_is_counted_in_a_proposal_list() {
+    let program_id = Pubkey::new_unique();
+    let mut chairperson = TestAccount::signer();
+    let mut system = TestAccount::state(program_id, &open_ballot(chairperson.key), ROLL_ENTRY_LEN);
+    let mut voter = TestAccount::voter(program_id);
+    let list_data = proposal_list(&[named("Parks"), named("Roads")]);
+    let mut proposals = TestAccount::new(program_id, list_data);
+
+    let mut grant = vec![IX_GIVE_RIGHT];
+    grant.extend_from_slice(&5u64.to_le_bytes());
+    process(
+        &program_id,
+        &mut [&mut chairperson, &mut system, &mut voter],
+        &grant,
+    )
+    .unwrap();
+
+    let mut vote = vec![IX_VOTE];
+    vote.extend_from_slice(&1u32.to_le_bytes());
+    process(
+        &program_id,
+        &mut [&mut voter, &mut proposals, &mut system],
+        &vote,
+    )
+    .unwrap();
+
+    let records = &proposals.data[4..];
+    let first = Proposal::from_bytes(records).unwrap();
+    let second = Proposal::from_bytes(&records[Proposal::LEN..]).unwrap();
+    assert_eq!(first.vote_count, 0);
+    assert_eq!(second.vote_count, 5);
+    let voter = Voter::from_bytes(&voter.data).unwrap();
+    assert!(voter.voted);
+    assert_eq!(voter.vote, 1);
+}
+
+#[test]
+fn index_one_past_the_proposal_list_is_rejected() {
+    let program_id = Pubkey::new_unique();
+    let mut chairperson = TestAccount::signer();
+    let mut system = TestAccount::state(program_id, &open_ballot(chairperson.key), ROLL_ENTRY_LEN);
+    let mut voter = TestAccount::voter(program_id);
+    let list_data = proposal_list(&[named("Parks"), named("Roads")]);
+    let mut proposals = TestAccount::new(program_id, list_data.clone());
+
+    process(
+        &program_id,
+        &mut [&mut chairperson, &mut system, &mut voter],
+        &[IX_GIVE_RIGHT],
+    )
+    .unwrap();
+    let granted = voter.data.clone();
+
+    let mut vote = vec![IX_VOTE];
+    vote.extend_from_slice(&2u32.to_le_bytes());
+    assert_eq!(
+        process(
+            &program_id,
+            &mut [&mut voter, &mut proposals, &mut system],
+            &vote,
+        ),
+        Err(VotingError::InvalidProposalIndex.into())
+    );
+    assert_eq!(proposals.data, list_data);
+    assert_eq!(voter.data, granted);
+}
@@ -0,0 +1,80 @@
+//! The vote path updates the voter record in place, with the same result as
+//! writing the whole record.
+
+mod common;
+
+use common::{open_ballot, process, proposal, TestAccount};
+use solana_program::pubkey::Pubkey;
+use solana_voting_program::{SimpleVotingSystem, Voter, IX_GIVE_RIGHT, IX_VOTE, ROLL_ENTRY_LEN};
+
+/// Grants a weight of 4 to a voter account with `spare` bytes past the
+/// record, which are all set, and votes for proposal 1.
+fn vote(spare: usize) -> (TestAccount, TestAccount) {
+    let program_id = Pubkey::new_unique();
+    let mut chairperson = TestAccount::signer();
+    let mut ballot = open_ballot(chairperson.key);
+    ballot.proposals = vec![proposal(b"Parks", 0), proposal(b"Roads", 0)];
+    let mut system = TestAccount::state(program_id, &ballot, ROLL_ENTRY_LEN);
+    let mut data = vec![0; Voter::LEN];
+    data.resize(Voter::LEN + spare, 0xaa);
+    let mut voter = TestAccount {
+        is_signer: true,
+        ..TestAccount::new(program_id, data)
+    };
+
+    let mut grant = vec![IX_GIVE_RIGHT];
+    grant.extend_from_slice(&4u64.to_le_bytes());
+    process(
+        &program_id,
+        &mut [&mut chairperson, &mut system, &mut voter],
+        &grant,
+    )
+    .unwrap();
+
+    let mut vote = vec![IX_VOTE];
+    vote.extend_from_slice(&1u32.to_le_bytes());
+    let mut proposals = TestAccount::new(program_id, system.data.clone()).with_key(system.key);
+    process(
+        &program_id,
+        &mut [&mut voter, &mut proposals, &mut system],
+        &vote,
+    )
+    .unwrap();
+
+    (voter, system)
+}
+
+#[test]
+fn voter_record_matches_a_full_rewrite() {
+    let (voter, system) = vote(0);
+    let expected = Voter {
+        weight: 4,
+        voted: true,
+        vote: 1,
+        ballot: system.key,
+        ..Voter::default()
+    };
+
+    assert_eq!(voter.data, expected.to_bytes());
+    let system = SimpleVotingSystem::deserialize(&system.data).unwrap();
+    assert_eq!(system.voters, vec![(voter.key, expected)]);
+    assert_eq!(system.proposals[1].vote_count, 4);
+}
+
+#[test]
+fn bytes_past_the_record_are_left_alone() {
+    let (voter, _) = vote(11);
+
+    assert_eq!(voter.data[Voter::LEN..], [0xaa; 11]);
+    assert!(Voter::from_bytes(&voter.data).unwrap().voted);
+}
+
+#[cfg(feature = "profiling")]
+#[test]
+fn vote_logs_its_compute_units() {
+    vote(0);
+
+    let logs = common::logs();
+    assert!(logs.contains(&"PROFILE vote start".to_string()));
+    assert!(logs.contains(&"PROFILE vote end".to_string()));
+}
@@ -0,0 +1,79 @@
+//! The receipt handed out with each vote verifies, and any altered receipt does
+//! not.
+
+mod common;
+
+use common::{open_ballot, process, proposal, return_data, TestAccount};
+use solana_program::pubkey::Pubkey;
+use solana_voting_program::{IX_GIVE_RIGHT, IX_VERIFY_RECEIPT, IX_VOTE, ROLL_ENTRY_LEN};
+
+struct Receipt {
+    program_id: Pubkey,
+    voter: TestAccount,
+    receipt: Vec<u8>,
+}
+
+impl Receipt {
+    /// Votes for the second of two proposals and keeps the receipt.
+    fn issue() -> Self {
+        let program_id = Pubkey::new_unique();
+        let mut chairperson = TestAccount::signer();
+        let mut ballot = open_ballot(chairperson.key);
+        ballot.proposals = vec![proposal(b"Parks", 0), proposal(b"Roads", 0)];
+        let mut system = TestAccount::state(program_id, &ballot, ROLL_ENTRY_LEN);
+        let mut voter = TestAccount::voter(program_id);
+
+        process(
+            &program_id,
+            &mut [&mut chairperson, &mut system, &mut voter],
+            &[IX_GIVE_RIGHT],
+        )
+        .unwrap();
+        let mut vote = vec![IX_VOTE];
+        vote.extend_from_slice(&1u32.to_le_bytes());
+        let mut proposals = TestAccount::new(program_id, system.data.clone()).with_key(system.key);
+        process(
+            &program_id,
+            &mut [&mut voter, &mut proposals, &mut system],
+            &vote,
+        )
+        .unwrap();
+
+        Receipt {
+            program_id,
+            voter,
+            receipt: return_data(),
+        }
+    }
+
+    fn verify(&mut self, receipt: &[u8]) -> bool {
+        let mut verify = vec![IX_VERIFY_RECEIPT];
+        verify.extend_from_slice(receipt);
+        process(&self.program_id, &mut [&mut self.voter], &verify).unwrap();
+
+        return_data() == [1]
+    }
+}
+
+#[test]
+fn issued_receipt_verifies() {
+    let mut issued = Receipt::issue();
+    let receipt = issued.receipt.clone();
+
+    assert_eq!(receipt.len(), 36);
+    assert_eq!(receipt[..4], 1u32.to_le_bytes());
+    assert!(issued.verify(&receipt));
+}
+
+#[test]
+fn altered_receipts_do_not_verify() {
+    let mut issued = Receipt::issue();
+
+    let mut other_vote = issued.receipt.clone();
+    other_vote[..4].copy_from_slice(&0u32.to_le_bytes());
+    assert!(!issued.verify(&other_vote));
+
+    let mut forged = issued.receipt.clone();
+    forged[35] ^= 1;
+    assert!(!issued.verify(&forged));
+}
@@ -0,0 +1,50 @@
+//! Vote counts and abstentions sum exactly up to the overflow of the total,
+//! which is rejected.
+
+mod common;
+
+use common::{open_ballot, process, proposal, return_data, TestAccount};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use solana_voting_program::{VoteCount, VoteTotal, IX_TOTAL_VOTES};
+
+fn total_votes(counts: &[VoteCount], abstain_weight: u64) -> Result<VoteTotal, ProgramError> {
+    let program_id = Pubkey::new_unique();
+    let mut ballot = open_ballot(Pubkey::new_unique());
+    ballot.abstain_weight = abstain_weight;
+    ballot.proposals = counts.iter().map(|&count| proposal(b"p", count)).collect();
+    let mut system = TestAccount::state(program_id, &ballot, 0);
+    let mut proposals = TestAccount::new(program_id, system.data.clone()).with_key(system.key);
+
+    process(
+        &program_id,
+        &mut [&mut system, &mut proposals],
+        &[IX_TOTAL_VOTES],
+    )?;
+
+    Ok(VoteTotal::from_le_bytes(
+        return_data()[1..].try_into().unwrap(),
+    ))
+}
+
+#[test]
+fn counts_at_the_u64_boundary_sum_exactly() {
+    let max = VoteCount::from(u64::MAX);
+
+    assert_eq!(
+        total_votes(&[max, max, 1], u64::MAX),
+        Ok(3 * VoteTotal::from(u64::MAX) + 1)
+    );
+}
+
+#[cfg(feature = "u128-counts")]
+#[test]
+fn overflowing_total_is_rejected() {
+    let half = VoteCount::MAX / 2 + 1;
+
+    assert_eq!(total_votes(&[half, half - 1], 0), Ok(VoteTotal::MAX));
+    assert_eq!(
+        total_votes(&[half, half - 1], 1),
+        Err(ProgramError::InvalidAccountData)
+    );
+    assert!(total_votes(&[half, half], 0).is_err());
+}
@@ -0,0 +1,45 @@
+//! Voter records are written into the front of their account, whatever its
+//! size past the record.
+
+mod common;
+
+use common::{open_ballot, process, TestAccount};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use solana_voting_program::{Voter, VotingError, IX_GIVE_RIGHT, ROLL_ENTRY_LEN};
+
+/// Grants a weight of 3 to a voter account of `len` bytes, all set to a
+/// stale pattern past the record.
+fn grant(len: usize) -> (Result<(), ProgramError>, TestAccount) {
+    let program_id = Pubkey::new_unique();
+    let mut chairperson = TestAccount::signer();
+    let mut system = TestAccount::state(program_id, &open_ballot(chairperson.key), ROLL_ENTRY_LEN);
+    let mut data = vec![0; len.min(Voter::LEN)];
+    data.resize(len, 0xee);
+    let mut voter = TestAccount::new(program_id, data);
+
+    let mut grant = vec![IX_GIVE_RIGHT];
+    grant.extend_from_slice(&3u64.to_le_bytes());
+    let result = process(
+        &program_id,
+        &mut [&mut chairperson, &mut system, &mut voter],
+        &grant,
+    );
+    (result, voter)
+}
+
+#[test]
+fn stale_trailing_bytes_are_left_untouched() {
+    let (result, voter) = grant(Voter::LEN + 20);
+
+    assert_eq!(result, Ok(()));
+    assert_eq!(Voter::from_bytes(&voter.data).unwrap().weight, 3);
+    assert_eq!(voter.data[Voter::LEN..], [0xee; 20]);
+}
+
+#[test]
+fn account_shorter_than_the_record_is_rejected() {
+    let (result, voter) = grant(20);
+
+    assert_eq!(result, Err(VotingError::AccountTooSmall.into()));
+    assert_eq!(voter.data, vec![0; 20]);
+}
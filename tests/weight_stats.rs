@@ -0,0 +1,39 @@
+//! The minimum, maximum and mean weight of the voter roll, the mean rounded
+//! down.
+
+mod common;
+
+use common::{open_ballot, process, return_data, roll_entry, TestAccount};
+use solana_program::pubkey::Pubkey;
+use solana_voting_program::IX_WEIGHT_STATS;
+
+/// The minimum, maximum and mean weight of a roll of voters of `weights`.
+fn weight_stats(weights: &[u64]) -> [u64; 3] {
+    let program_id = Pubkey::new_unique();
+    let mut ballot = open_ballot(Pubkey::new_unique());
+    ballot.voters = weights
+        .iter()
+        .map(|&weight| roll_entry(weight, false))
+        .collect();
+    let mut system = TestAccount::state(program_id, &ballot, 0);
+
+    process(&program_id, &mut [&mut system], &[IX_WEIGHT_STATS]).unwrap();
+
+    let data = return_data();
+    [0, 1, 2].map(|field| u64::from_le_bytes(data[field * 8..field * 8 + 8].try_into().unwrap()))
+}
+
+#[test]
+fn stats_span_the_roll() {
+    assert_eq!(weight_stats(&[3, 1, 5]), [1, 5, 3]);
+}
+
+#[test]
+fn mean_is_rounded_down() {
+    assert_eq!(weight_stats(&[1, 2]), [1, 2, 1]);
+}
+
+#[test]
+fn empty_roll_reports_zeros() {
+    assert_eq!(weight_stats(&[]), [0, 0, 0]);
+}
@@ -0,0 +1,73 @@
+//! Vote counts past `u64::MAX`, kept by builds with the `u128-counts`
+//! feature and refused by the default 64-bit counts.
+
+mod common;
+
+use common::{open_ballot, process, proposal, TestAccount};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use solana_voting_program::{
+    Proposal, SimpleVotingSystem, VoteCount, IX_GIVE_RIGHT, IX_VOTE, ROLL_ENTRY_LEN,
+};
+
+/// Votes with a weight of 5 for a proposal that already holds `u64::MAX`
+/// votes, and returns the proposal afterwards.
+fn vote_past_u64() -> (Result<(), ProgramError>, Proposal) {
+    let program_id = Pubkey::new_unique();
+    let mut chairperson = TestAccount::signer();
+    let mut ballot = open_ballot(chairperson.key);
+    ballot.proposals = vec![proposal(b"Parks", VoteCount::from(u64::MAX))];
+    let mut system = TestAccount::state(program_id, &ballot, ROLL_ENTRY_LEN);
+    let mut voter = TestAccount::voter(program_id);
+
+    let mut grant = vec![IX_GIVE_RIGHT];
+    grant.extend_from_slice(&5u64.to_le_bytes());
+    process(
+        &program_id,
+        &mut [&mut chairperson, &mut system, &mut voter],
+        &grant,
+    )
+    .unwrap();
+
+    let mut vote = vec![IX_VOTE];
+    vote.extend_from_slice(&0u32.to_le_bytes());
+    let mut proposals = TestAccount::new(program_id, system.data.clone()).with_key(system.key);
+    let result = process(
+        &program_id,
+        &mut [&mut voter, &mut proposals, &mut system],
+        &vote,
+    );
+
+    let system = SimpleVotingSystem::deserialize(&system.data).unwrap();
+    (result, system.proposals[0].clone())
+}
+
+#[cfg(feature = "u128-counts")]
+#[test]
+fn counts_past_u64_round_trip() {
+    let record = proposal(b"Parks", VoteCount::from(u64::MAX) * 3);
+    let bytes = record.to_bytes();
+
+    assert_eq!(bytes.len(), Proposal::LEN);
+    assert_eq!(Proposal::from_bytes(&bytes).unwrap(), record);
+}
+
+#[cfg(feature = "u128-counts")]
+#[test]
+fn tally_grows_past_u64() {
+    let (result, proposal) = vote_past_u64();
+
+    assert_eq!(result, Ok(()));
+    assert_eq!(proposal.vote_count, VoteCount::from(u64::MAX) + 5);
+}
+
+#[cfg(not(feature = "u128-counts"))]
+#[test]
+fn tally_past_u64_overflows() {
+    let (result, proposal) = vote_past_u64();
+
+    assert_eq!(
+        result,
+        Err(solana_voting_program::VotingError::VoteCountOverflow.into())
+    );
+    assert_eq!(proposal.vote_count, u64::MAX);
+}
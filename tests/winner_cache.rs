@@ -0,0 +1,63 @@
+//! The winner of a proposal list is its leading proposal, and it follows the
+//! counts as they change. A list is told apart by its tag, not by what its
+//! first byte happens to hold.
+
+mod common;
+
+use common::{process, proposal, proposal_list, return_data, TestAccount};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use solana_voting_program::{Proposal, IX_WINNER_NAME};
+
+struct List {
+    program_id: Pubkey,
+    proposals: TestAccount,
+}
+
+impl List {
+    /// A proposal list led by `Roads`.
+    fn new() -> Self {
+        let program_id = Pubkey::new_unique();
+        let data = proposal_list(&[proposal(b"Parks", 2), proposal(b"Roads", 5)]);
+
+        List {
+            program_id,
+            proposals: TestAccount::new(program_id, data),
+        }
+    }
+
+    fn winner_name(&mut self) -> Result<Vec<u8>, ProgramError> {
+        process(
+            &self.program_id,
+            &mut [&mut self.proposals],
+            &[IX_WINNER_NAME],
+        )?;
+        Ok(return_data())
+    }
+}
+
+#[test]
+fn winner_is_the_leader() {
+    assert_eq!(List::new().winner_name().unwrap(), b"Roads");
+}
+
+#[test]
+fn winner_follows_the_edited_counts() {
+    let mut list = List::new();
+
+    let mut parks = Proposal::from_bytes(&list.proposals.data[4..]).unwrap();
+    parks.vote_count = 9;
+    list.proposals.data[4..4 + Proposal::LEN].copy_from_slice(&parks.to_bytes());
+
+    assert_eq!(list.winner_name().unwrap(), b"Parks");
+}
+
+#[test]
+fn untagged_lists_are_rejected() {
+    // A header of 1 once read as a state layout version
+    for header in [0u32, 1, u32::MAX] {
+        let mut list = List::new();
+        list.proposals.data[..4].copy_from_slice(&header.to_le_bytes());
+
+        assert_eq!(list.winner_name(), Err(ProgramError::InvalidAccountData));
+    }
+}
@@ -0,0 +1,42 @@
+//! The leading proposal's share of the votes, in basis points.
+
+mod common;
+
+use common::{open_ballot, process, proposal, return_data, TestAccount};
+use solana_program::pubkey::Pubkey;
+use solana_voting_program::{VoteCount, IX_WINNER_SHARE};
+
+/// The leading proposal and its share in basis points, on a ballot whose
+/// proposals hold `counts`.
+fn winner_share(counts: Vec<VoteCount>) -> (u32, u16) {
+    let program_id = Pubkey::new_unique();
+    let mut ballot = open_ballot(Pubkey::new_unique());
+    ballot.proposals = counts
+        .into_iter()
+        .map(|count| proposal(b"Parks", count))
+        .collect();
+    let mut system = TestAccount::state(program_id, &ballot, 0);
+
+    process(&program_id, &mut [&mut system], &[IX_WINNER_SHARE]).unwrap();
+
+    let data = return_data();
+    (
+        u32::from_le_bytes(data[..4].try_into().unwrap()),
+        u16::from_le_bytes(data[4..6].try_into().unwrap()),
+    )
+}
+
+#[test]
+fn winner_with_sixty_percent_has_6000_bps() {
+    assert_eq!(winner_share(vec![3, 6, 1]), (1, 6000));
+}
+
+#[test]
+fn unanimous_winner_has_10000_bps() {
+    assert_eq!(winner_share(vec![0, 4]), (1, 10_000));
+}
+
+#[test]
+fn ballot_without_votes_reports_zero() {
+    assert_eq!(winner_share(vec![0, 0]), (0, 0));
+}
@@ -0,0 +1,94 @@
+//! Write-in votes add a new proposal, or count toward the existing one of that
+//! name. The stubs do not run the system program, so the account a write-in
+//! creates is handed over already allocated at its address.
+
+mod common;
+
+use common::{process, proposal, proposal_address, return_data, Ballot, TestAccount};
+use solana_program::pubkey::Pubkey;
+use solana_voting_program::{Proposal, IX_WINNING_PROPOSAL, IX_WRITE_IN_VOTE};
+
+/// An open ballot with one voter granted the right to vote, and per-proposal
+/// accounts for `names`.
+fn with_proposals(names: &[&[u8]]) -> (Ballot, TestAccount, Vec<TestAccount>) {
+    let mut ballot = Ballot::new(Vec::new(), 1);
+    let voter = ballot.voter(1);
+    let proposals = (0..)
+        .zip(names)
+        .map(|(index, name)| {
+            TestAccount::new(ballot.program_id, proposal(name, 0).to_bytes()).with_key(
+                proposal_address(&ballot.program_id, &ballot.system.key, index),
+            )
+        })
+        .collect();
+
+    (ballot, voter, proposals)
+}
+
+impl Ballot {
+    /// Casts `voter`'s vote for `name` among `proposals`, offering `write_in`
+    /// as the account of a new proposal.
+    fn write_in(
+        &mut self,
+        voter: &mut TestAccount,
+        proposals: &mut [TestAccount],
+        name: &[u8],
+        write_in: &mut TestAccount,
+    ) {
+        let mut payer = TestAccount::signer();
+        let mut system_program = TestAccount::new(Pubkey::default(), Vec::new());
+        let mut data = vec![IX_WRITE_IN_VOTE];
+        data.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        data.extend_from_slice(name);
+
+        let mut accounts = vec![
+            voter,
+            &mut self.system,
+            &mut payer,
+            &mut system_program,
+            write_in,
+        ];
+        accounts.extend(proposals.iter_mut());
+        process(&self.program_id, &mut accounts, &data).unwrap();
+    }
+
+    /// The index of the leading proposal among `proposals`.
+    fn winner(&mut self, proposals: &mut [TestAccount]) -> u32 {
+        let mut accounts = vec![&mut self.system];
+        accounts.extend(proposals.iter_mut());
+        process(&self.program_id, &mut accounts, &[IX_WINNING_PROPOSAL]).unwrap();
+        u32::from_le_bytes(return_data()[..4].try_into().unwrap())
+    }
+}
+
+#[test]
+fn new_candidate_is_written_in_and_tallied() {
+    let (mut ballot, mut voter, mut proposals) = with_proposals(&[b"Parks", b"Roads"]);
+    let mut write_in = TestAccount::new(ballot.program_id, vec![0; Proposal::LEN])
+        .with_key(proposal_address(&ballot.program_id, &ballot.system.key, 2));
+
+    ballot.write_in(&mut voter, &mut proposals, b"Libraries", &mut write_in);
+
+    let written = Proposal::from_bytes(&write_in.data).unwrap();
+    assert_eq!(written.name, b"Libraries");
+    assert_eq!(written.vote_count, 1);
+    proposals.push(write_in);
+    assert_eq!(ballot.winner(&mut proposals), 2);
+}
+
+#[test]
+fn existing_name_counts_toward_that_proposal() {
+    let (mut ballot, mut voter, mut proposals) = with_proposals(&[b"Parks", b"Roads"]);
+    let mut write_in = TestAccount::new(ballot.program_id, Vec::new()).with_key(proposal_address(
+        &ballot.program_id,
+        &ballot.system.key,
+        2,
+    ));
+
+    ballot.write_in(&mut voter, &mut proposals, b"Roads", &mut write_in);
+
+    let roads = Proposal::from_bytes(&proposals[1].data).unwrap();
+    assert_eq!(roads.vote_count, 1);
+    assert!(write_in.data.is_empty());
+    assert_eq!(ballot.winner(&mut proposals), 1);
+}